@@ -77,7 +77,14 @@ fn run(args: &Args) -> Result<(), git2::Error> {
 
     for (i, line) in reader.lines().enumerate() {
         if let (Ok(line), Some(hunk)) = (line, blame.get_line(i+1)) {
-            let sig = hunk.final_signature();
+            let sig = match hunk.final_signature() {
+                Some(sig) => sig,
+                None => {
+                    println!("{} Not Committed Yet {}",
+                             hunk.final_commit_id(), line);
+                    continue
+                }
+            };
             println!("{} {} <{}> {}", hunk.final_commit_id(),
                      String::from_utf8_lossy(sig.name_bytes()),
                      String::from_utf8_lossy(sig.email_bytes()), line);
@@ -20,6 +20,7 @@ fn main() {
     let https = env::var("CARGO_FEATURE_HTTPS").is_ok();
     let ssh = env::var("CARGO_FEATURE_SSH").is_ok();
     let curl = env::var("CARGO_FEATURE_CURL").is_ok();
+    let libgit2_0_28 = env::var("CARGO_FEATURE_LIBGIT2_0_28").is_ok();
     if ssh {
         register_dep("SSH2");
     }
@@ -31,6 +32,14 @@ fn main() {
     }
     let has_pkgconfig = Command::new("pkg-config").output().is_ok();
 
+    // The bundled libgit2 predates the APIs enabled by this feature, so a
+    // newer system library is required instead.
+    if libgit2_0_28 {
+        t!(pkg_config::Config::new().atleast_version("0.28.0")
+                                    .probe("libgit2"));
+        return
+    }
+
     if env::var("LIBGIT2_SYS_USE_PKG_CONFIG").is_ok() {
         if pkg_config::find_library("libgit2").is_ok() {
             return
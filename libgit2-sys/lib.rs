@@ -11,6 +11,8 @@ extern crate openssl_sys;
 extern crate libz_sys as libz;
 
 use libc::{c_int, c_char, c_uint, size_t, c_uchar, c_void};
+#[cfg(feature = "libgit2-0-28")]
+use libc::c_ushort;
 
 pub const GIT_OID_RAWSZ: usize = 20;
 pub const GIT_OID_HEXSZ: usize = GIT_OID_RAWSZ * 2;
@@ -23,6 +25,11 @@ pub const GIT_STATUS_OPTIONS_VERSION: c_uint = 1;
 pub const GIT_BLAME_OPTIONS_VERSION: c_uint = 1;
 pub const GIT_PROXY_OPTIONS_VERSION: c_uint = 1;
 pub const GIT_SUBMODULE_UPDATE_OPTIONS_VERSION: c_uint = 1;
+#[cfg(feature = "libgit2-0-28")]
+pub const GIT_WORKTREE_ADD_OPTIONS_VERSION: c_uint = 1;
+#[cfg(feature = "libgit2-0-28")]
+pub const GIT_WORKTREE_PRUNE_OPTIONS_VERSION: c_uint = 1;
+pub const GIT_REBASE_OPTIONS_VERSION: c_uint = 1;
 
 macro_rules! git_enum {
     (pub enum $name:ident { $($variants:tt)* }) => {
@@ -56,7 +63,6 @@ pub enum git_config_iterator {}
 pub enum git_index {}
 pub enum git_object {}
 pub enum git_reference {}
-pub enum git_reference_iterator {}
 pub enum git_annotated_commit {}
 pub enum git_refspec {}
 pub enum git_remote {}
@@ -82,6 +88,15 @@ pub enum git_describe_result {}
 pub enum git_packbuilder {}
 pub enum git_odb {}
 pub enum git_odb_stream {}
+#[cfg(feature = "libgit2-0-28")]
+pub enum git_worktree {}
+pub enum git_mailmap {}
+pub enum git_indexer {}
+pub enum git_filter_list {}
+pub enum git_rebase {}
+pub enum git_transaction {}
+pub enum git_odb_writepack {}
+pub enum git_refdb {}
 
 #[repr(C)]
 pub struct git_revspec {
@@ -584,6 +599,7 @@ git_enum! {
         GIT_CHECKOUT_DONT_OVERWRITE_IGNORED = (1 << 19),
         GIT_CHECKOUT_CONFLICT_STYLE_MERGE = (1 << 20),
         GIT_CHECKOUT_CONFLICT_STYLE_DIFF3 = (1 << 21),
+        GIT_CHECKOUT_DRY_RUN = (1 << 24),
 
         GIT_CHECKOUT_UPDATE_SUBMODULES = (1 << 16),
         GIT_CHECKOUT_UPDATE_SUBMODULES_IF_CHANGED = (1 << 17),
@@ -883,6 +899,24 @@ git_enum! {
     }
 }
 
+git_enum! {
+    pub enum git_indexcap_t: c_int {
+        GIT_INDEXCAP_IGNORE_CASE = 1,
+        GIT_INDEXCAP_NO_FILEMODE = 2,
+        GIT_INDEXCAP_NO_SYMLINKS = 4,
+        GIT_INDEXCAP_FROM_OWNER = -1,
+    }
+}
+
+git_enum! {
+    pub enum git_reference_format_t {
+        GIT_REFERENCE_FORMAT_NORMAL = 0,
+        GIT_REFERENCE_FORMAT_ALLOW_ONELEVEL = 1 << 0,
+        GIT_REFERENCE_FORMAT_REFSPEC_PATTERN = 1 << 1,
+        GIT_REFERENCE_FORMAT_REFSPEC_SHORTHAND = 1 << 2,
+    }
+}
+
 git_enum! {
     pub enum git_repository_open_flag_t {
         GIT_REPOSITORY_OPEN_NO_SEARCH = (1 << 0),
@@ -1130,6 +1164,25 @@ pub struct git_diff_similarity_metric {
 
 pub const GIT_DIFF_FIND_OPTIONS_VERSION: c_uint = 1;
 
+#[cfg(feature = "libgit2-0-28")]
+pub type git_apply_delta_cb = extern fn(*const git_diff_delta,
+                                        *mut c_void) -> c_int;
+#[cfg(feature = "libgit2-0-28")]
+pub type git_apply_hunk_cb = extern fn(*const git_diff_hunk,
+                                       *mut c_void) -> c_int;
+
+#[repr(C)]
+#[cfg(feature = "libgit2-0-28")]
+pub struct git_apply_options {
+    pub version: c_uint,
+    pub delta_cb: Option<git_apply_delta_cb>,
+    pub hunk_cb: Option<git_apply_hunk_cb>,
+    pub payload: *mut c_void,
+}
+
+#[cfg(feature = "libgit2-0-28")]
+pub const GIT_APPLY_OPTIONS_VERSION: c_uint = 1;
+
 pub const GIT_DIFF_FIND_BY_CONFIG: u32 = 0;
 pub const GIT_DIFF_FIND_RENAMES: u32 = 1 << 0;
 pub const GIT_DIFF_FIND_RENAMES_FROM_REWRITES: u32 = 1 << 1;
@@ -1216,6 +1269,40 @@ git_enum! {
     }
 }
 
+#[repr(C)]
+pub struct git_merge_file_input {
+    pub version: c_uint,
+    pub ptr: *const c_char,
+    pub size: size_t,
+    pub path: *const c_char,
+    pub mode: c_uint,
+}
+
+pub const GIT_MERGE_FILE_INPUT_VERSION: c_uint = 1;
+
+#[repr(C)]
+pub struct git_merge_file_options {
+    pub version: c_uint,
+    pub ancestor_label: *const c_char,
+    pub our_label: *const c_char,
+    pub their_label: *const c_char,
+    pub favor: git_merge_file_favor_t,
+    pub flags: git_merge_file_flag_t,
+    #[cfg(feature = "libgit2-0-28")]
+    pub marker_size: c_ushort,
+}
+
+pub const GIT_MERGE_FILE_OPTIONS_VERSION: c_uint = 1;
+
+#[repr(C)]
+pub struct git_merge_file_result {
+    pub automergeable: c_uint,
+    pub path: *const c_char,
+    pub mode: c_uint,
+    pub ptr: *const c_char,
+    pub len: size_t,
+}
+
 git_enum! {
     pub enum git_merge_analysis_t {
         GIT_MERGE_ANALYSIS_NONE = 0,
@@ -1421,15 +1508,241 @@ pub type git_packbuilder_foreach_cb = extern fn(*const c_void, size_t,
 
 pub type git_odb_foreach_cb = extern fn(id: *const git_oid, payload: *mut c_void) -> c_int;
 
+#[repr(C)]
+pub struct git_odb_backend {
+    pub version: c_uint,
+    pub odb: *mut git_odb,
+    pub read: Option<extern fn(*mut *mut c_void,
+                               *mut size_t,
+                               *mut git_otype,
+                               *mut git_odb_backend,
+                               *const git_oid) -> c_int>,
+    pub read_prefix: Option<extern fn(*mut git_oid,
+                                      *mut *mut c_void,
+                                      *mut size_t,
+                                      *mut git_otype,
+                                      *mut git_odb_backend,
+                                      *const git_oid,
+                                      size_t) -> c_int>,
+    pub read_header: Option<extern fn(*mut size_t,
+                                      *mut git_otype,
+                                      *mut git_odb_backend,
+                                      *const git_oid) -> c_int>,
+    pub write: Option<extern fn(*mut git_odb_backend,
+                                *const git_oid,
+                                *const c_void,
+                                size_t,
+                                git_otype) -> c_int>,
+    pub writestream: Option<extern fn(*mut *mut git_odb_stream,
+                                      *mut git_odb_backend,
+                                      git_off_t,
+                                      git_otype) -> c_int>,
+    pub readstream: Option<extern fn(*mut *mut git_odb_stream,
+                                     *mut git_odb_backend,
+                                     *const git_oid) -> c_int>,
+    pub exists: Option<extern fn(*mut git_odb_backend,
+                                 *const git_oid) -> c_int>,
+    pub exists_prefix: Option<extern fn(*mut git_oid,
+                                        *mut git_odb_backend,
+                                        *const git_oid,
+                                        size_t) -> c_int>,
+    pub refresh: Option<extern fn(*mut git_odb_backend) -> c_int>,
+    pub foreach: Option<extern fn(*mut git_odb_backend,
+                                  git_odb_foreach_cb,
+                                  *mut c_void) -> c_int>,
+    pub writepack: Option<extern fn(*mut *mut git_odb_writepack,
+                                    *mut git_odb_backend,
+                                    *mut git_odb,
+                                    git_transfer_progress_cb,
+                                    *mut c_void) -> c_int>,
+    pub freshen: Option<extern fn(*mut git_odb_backend,
+                                  *const git_oid) -> c_int>,
+    pub free: Option<extern fn(*mut git_odb_backend)>,
+}
+
+pub const GIT_ODB_BACKEND_VERSION: c_uint = 1;
+
+#[repr(C)]
+#[cfg(feature = "libgit2-0-28")]
+pub struct git_worktree_add_options {
+    pub version: c_uint,
+    pub lock: c_int,
+    pub reference: *mut git_reference,
+}
+
+#[cfg(feature = "libgit2-0-28")]
+git_enum! {
+    pub enum git_worktree_prune_t {
+        GIT_WORKTREE_PRUNE_VALID = 1 << 0,
+        GIT_WORKTREE_PRUNE_LOCKED = 1 << 1,
+        GIT_WORKTREE_PRUNE_WORKING_TREE = 1 << 2,
+    }
+}
+
+#[repr(C)]
+#[cfg(feature = "libgit2-0-28")]
+pub struct git_worktree_prune_options {
+    pub version: c_uint,
+    pub flags: u32,
+}
+
+git_enum! {
+    pub enum git_filter_mode_t {
+        GIT_FILTER_TO_WORKTREE = 0,
+        GIT_FILTER_TO_ODB = 1,
+    }
+}
+
+git_enum! {
+    pub enum git_filter_flag_t {
+        GIT_FILTER_DEFAULT = 0,
+        GIT_FILTER_ALLOW_UNSAFE = 1 << 0,
+    }
+}
+
+#[repr(C)]
+pub struct git_rebase_options {
+    pub version: c_uint,
+    pub quiet: c_int,
+    pub inmemory: c_int,
+    pub rewrite_notes_ref: *const c_char,
+    pub merge_options: git_merge_options,
+    pub checkout_options: git_checkout_options,
+}
+
+git_enum! {
+    pub enum git_rebase_operation_t {
+        GIT_REBASE_OPERATION_PICK = 0,
+        GIT_REBASE_OPERATION_REWORD,
+        GIT_REBASE_OPERATION_EDIT,
+        GIT_REBASE_OPERATION_SQUASH,
+        GIT_REBASE_OPERATION_FIXUP,
+        GIT_REBASE_OPERATION_EXEC,
+    }
+}
+
+#[repr(C)]
+pub struct git_rebase_operation {
+    pub kind: git_rebase_operation_t,
+    pub id: git_oid,
+    pub exec: *const c_char,
+}
+
+git_enum! {
+    pub enum git_libgit2_opt_t {
+        GIT_OPT_GET_MWINDOW_SIZE = 0,
+        GIT_OPT_SET_MWINDOW_SIZE,
+        GIT_OPT_GET_MWINDOW_MAPPED_LIMIT,
+        GIT_OPT_SET_MWINDOW_MAPPED_LIMIT,
+        GIT_OPT_GET_SEARCH_PATH,
+        GIT_OPT_SET_SEARCH_PATH,
+        GIT_OPT_SET_CACHE_OBJECT_LIMIT,
+        GIT_OPT_SET_CACHE_MAX_SIZE,
+        GIT_OPT_ENABLE_CACHING,
+        GIT_OPT_GET_CACHED_MEMORY,
+        GIT_OPT_GET_TEMPLATE_PATH,
+        GIT_OPT_SET_TEMPLATE_PATH,
+        GIT_OPT_SET_SSL_CERT_LOCATIONS,
+        GIT_OPT_SET_USER_AGENT,
+        GIT_OPT_ENABLE_STRICT_OBJECT_CREATION,
+        GIT_OPT_ENABLE_STRICT_SYMBOLIC_REF_CREATION,
+        GIT_OPT_SET_SSL_CIPHERS,
+        GIT_OPT_GET_USER_AGENT,
+        GIT_OPT_ENABLE_OFS_DELTA,
+        GIT_OPT_ENABLE_FSYNC_GITDIR,
+        GIT_OPT_GET_WINDOWS_SHAREMODE,
+        GIT_OPT_SET_WINDOWS_SHAREMODE,
+        GIT_OPT_ENABLE_STRICT_HASH_VERIFICATION,
+        GIT_OPT_SET_ALLOCATOR,
+        GIT_OPT_ENABLE_UNSAVED_INDEX_SAFETY,
+        GIT_OPT_GET_PACK_MAX_OBJECTS,
+        GIT_OPT_SET_PACK_MAX_OBJECTS,
+        GIT_OPT_DISABLE_PACK_KEEP_FILE_CHECKS,
+    }
+}
+
+#[repr(C)]
+pub struct git_reference_iterator {
+    pub db: *mut git_refdb,
+    pub next: Option<extern fn(*mut *mut git_reference,
+                               *mut git_reference_iterator) -> c_int>,
+    pub next_name: Option<extern fn(*mut *const c_char,
+                                    *mut git_reference_iterator) -> c_int>,
+    pub free: Option<extern fn(*mut git_reference_iterator)>,
+}
+
+#[repr(C)]
+pub struct git_refdb_backend {
+    pub version: c_uint,
+    pub exists: Option<extern fn(*mut c_int,
+                                 *mut git_refdb_backend,
+                                 *const c_char) -> c_int>,
+    pub lookup: Option<extern fn(*mut *mut git_reference,
+                                 *mut git_refdb_backend,
+                                 *const c_char) -> c_int>,
+    pub iterator: Option<extern fn(*mut *mut git_reference_iterator,
+                                   *mut git_refdb_backend,
+                                   *const c_char) -> c_int>,
+    pub write: Option<extern fn(*mut git_refdb_backend,
+                                *const git_reference,
+                                c_int,
+                                *const git_signature,
+                                *const c_char,
+                                *const git_oid,
+                                *const c_char) -> c_int>,
+    pub rename: Option<extern fn(*mut *mut git_reference,
+                                 *mut git_refdb_backend,
+                                 *const c_char,
+                                 *const c_char,
+                                 c_int,
+                                 *const git_signature,
+                                 *const c_char) -> c_int>,
+    pub del: Option<extern fn(*mut git_refdb_backend,
+                              *const c_char,
+                              *const git_oid,
+                              *const c_char) -> c_int>,
+    pub compress: Option<extern fn(*mut git_refdb_backend) -> c_int>,
+    pub has_log: Option<extern fn(*mut git_refdb_backend,
+                                  *const c_char) -> c_int>,
+    pub ensure_log: Option<extern fn(*mut git_refdb_backend,
+                                     *const c_char) -> c_int>,
+    pub free: Option<extern fn(*mut git_refdb_backend)>,
+    pub reflog_read: Option<extern fn(*mut *mut git_reflog,
+                                      *mut git_refdb_backend,
+                                      *const c_char) -> c_int>,
+    pub reflog_write: Option<extern fn(*mut git_refdb_backend,
+                                       *mut git_reflog) -> c_int>,
+    pub reflog_rename: Option<extern fn(*mut git_refdb_backend,
+                                        *const c_char,
+                                        *const c_char) -> c_int>,
+    pub reflog_delete: Option<extern fn(*mut git_refdb_backend,
+                                        *const c_char) -> c_int>,
+    pub lock: Option<extern fn(*mut *mut c_void,
+                               *mut git_refdb_backend,
+                               *const c_char) -> c_int>,
+    pub unlock: Option<extern fn(*mut git_refdb_backend,
+                                 *mut c_void,
+                                 c_int,
+                                 c_int,
+                                 *const git_reference,
+                                 *const git_signature,
+                                 *const c_char) -> c_int>,
+}
+
+pub const GIT_REFDB_BACKEND_VERSION: c_uint = 1;
+
 extern {
     // threads
     pub fn git_libgit2_init() -> c_int;
     pub fn git_libgit2_shutdown() -> c_int;
+    pub fn git_libgit2_opts(option: c_int, ...) -> c_int;
 
     // repository
     pub fn git_repository_free(repo: *mut git_repository);
     pub fn git_repository_open(repo: *mut *mut git_repository,
                                path: *const c_char) -> c_int;
+    pub fn git_repository_open_bare(repo: *mut *mut git_repository,
+                                    bare_path: *const c_char) -> c_int;
     pub fn git_repository_open_ext(repo: *mut *mut git_repository,
                                    path: *const c_char,
                                    flags: c_uint,
@@ -1460,6 +1773,7 @@ extern {
     pub fn git_repository_set_workdir(repo: *mut git_repository,
                                       workdir: *const c_char,
                                       update_gitlink: c_int) -> c_int;
+    pub fn git_repository_set_bare(repo: *mut git_repository) -> c_int;
     pub fn git_repository_index(out: *mut *mut git_index,
                                 repo: *mut git_repository) -> c_int;
     pub fn git_repository_set_index(repo: *mut git_repository,
@@ -1472,6 +1786,11 @@ extern {
                                    start_path: *const c_char,
                                    across_fs: c_int,
                                    ceiling_dirs: *const c_char) -> c_int;
+    pub fn git_repository_hashfile(out: *mut git_oid,
+                                   repo: *mut git_repository,
+                                   path: *const c_char,
+                                   kind: git_otype,
+                                   as_path: *const c_char) -> c_int;
 
     // revparse
     pub fn git_revparse(revspec: *mut git_revspec,
@@ -1503,6 +1822,7 @@ extern {
     pub fn git_object_type2string(kind: git_otype) -> *const c_char;
     pub fn git_object_string2type(s: *const c_char) -> git_otype;
     pub fn git_object_typeisloose(kind: git_otype) -> c_int;
+    pub fn git_object__size(kind: git_otype) -> size_t;
 
     // oid
     pub fn git_oid_fromraw(out: *mut git_oid, raw: *const c_uchar);
@@ -1597,12 +1917,19 @@ extern {
     pub fn git_remote_ls(out: *mut *mut *const git_remote_head,
                          size: *mut size_t,
                          remote: *mut git_remote) -> c_int;
+    pub fn git_remote_default_branch(out: *mut git_buf,
+                                     remote: *mut git_remote) -> c_int;
     pub fn git_remote_set_autotag(repo: *mut git_repository,
                                   remote: *const c_char,
                                   value: git_remote_autotag_option_t) -> c_int;
     pub fn git_remote_prune(remote: *mut git_remote,
                             callbacks: *const git_remote_callbacks) -> c_int;
 
+    // diff
+    pub fn git_diff_from_buffer(diff: *mut *mut git_diff,
+                                content: *const c_char,
+                                content_len: size_t) -> c_int;
+
     // refspec
     pub fn git_refspec_direction(spec: *const git_refspec) -> git_direction;
     pub fn git_refspec_dst(spec: *const git_refspec) -> *const c_char;
@@ -1613,6 +1940,12 @@ extern {
                                    refname: *const c_char) -> c_int;
     pub fn git_refspec_force(spec: *const git_refspec) -> c_int;
     pub fn git_refspec_string(spec: *const git_refspec) -> *const c_char;
+    pub fn git_refspec_transform(out: *mut git_buf,
+                                 spec: *const git_refspec,
+                                 name: *const c_char) -> c_int;
+    pub fn git_refspec_rtransform(out: *mut git_buf,
+                                  spec: *const git_refspec,
+                                  name: *const c_char) -> c_int;
 
     // strarray
     pub fn git_strarray_free(array: *mut git_strarray);
@@ -1634,6 +1967,8 @@ extern {
                              email: *const c_char) -> c_int;
     pub fn git_signature_dup(dest: *mut *mut git_signature,
                              sig: *const git_signature) -> c_int;
+    pub fn git_signature_from_buffer(out: *mut *mut git_signature,
+                                     buf: *const c_char) -> c_int;
 
     // status
     pub fn git_status_list_new(out: *mut *mut git_status_list,
@@ -1679,6 +2014,10 @@ extern {
     pub fn git_reference_is_remote(r: *const git_reference) -> c_int;
     pub fn git_reference_is_tag(r: *const git_reference) -> c_int;
     pub fn git_reference_is_valid_name(name: *const c_char) -> c_int;
+    pub fn git_reference_normalize_name(buffer_out: *mut c_char,
+                                        buffer_size: size_t,
+                                        name: *const c_char,
+                                        flags: c_uint) -> c_int;
     pub fn git_reference_lookup(out: *mut *mut git_reference,
                                 repo: *mut git_repository,
                                 name: *const c_char) -> c_int;
@@ -1705,6 +2044,8 @@ extern {
                                     id: *const git_oid,
                                     log_message: *const c_char) -> c_int;
     pub fn git_reference_type(r: *const git_reference) -> git_ref_t;
+    pub fn git_reference_list(array: *mut git_strarray,
+                              repo: *mut git_repository) -> c_int;
     pub fn git_reference_iterator_new(out: *mut *mut git_reference_iterator,
                                       repo: *mut git_repository) -> c_int;
     pub fn git_reference_iterator_glob_new(out: *mut *mut git_reference_iterator,
@@ -1837,12 +2178,17 @@ extern {
     pub fn git_blob_is_binary(blob: *const git_blob) -> c_int;
     pub fn git_blob_lookup(blob: *mut *mut git_blob, repo: *mut git_repository,
                            id: *const git_oid) -> c_int;
+    pub fn git_blob_dup(out: *mut *mut git_blob, source: *mut git_blob) -> c_int;
     pub fn git_blob_lookup_prefix(blob: *mut *mut git_blob,
                                   repo: *mut git_repository,
                                   id: *const git_oid,
                                   len: size_t) -> c_int;
     pub fn git_blob_rawcontent(blob: *const git_blob) -> *const c_void;
     pub fn git_blob_rawsize(blob: *const git_blob) -> git_off_t;
+    pub fn git_blob_filtered_content(out: *mut git_buf,
+                                     blob: *mut git_blob,
+                                     as_path: *const c_char,
+                                     check_for_binary_data: c_int) -> c_int;
     pub fn git_blob_create_frombuffer(id: *mut git_oid,
                                       repo: *mut git_repository,
                                       buffer: *const c_void,
@@ -1888,6 +2234,7 @@ extern {
     pub fn git_tree_lookup(tree: *mut *mut git_tree,
                            repo: *mut git_repository,
                            id: *const git_oid) -> c_int;
+    pub fn git_tree_dup(out: *mut *mut git_tree, source: *mut git_tree) -> c_int;
     pub fn git_tree_walk(tree: *const git_tree,
                          mode: git_treewalk_mode,
                          callback: git_treewalk_cb,
@@ -1918,6 +2265,7 @@ extern {
     // buf
     pub fn git_buf_free(buffer: *mut git_buf);
     pub fn git_buf_grow(buffer: *mut git_buf, target_size: size_t) -> c_int;
+    pub fn git_buf_is_binary(buf: *const git_buf) -> c_int;
     pub fn git_buf_set(buffer: *mut git_buf, data: *const c_void,
                        datalen: size_t) -> c_int;
 
@@ -1929,6 +2277,12 @@ extern {
     pub fn git_commit_lookup(commit: *mut *mut git_commit,
                              repo: *mut git_repository,
                              id: *const git_oid) -> c_int;
+    pub fn git_commit_dup(out: *mut *mut git_commit,
+                          source: *mut git_commit) -> c_int;
+    pub fn git_commit_lookup_prefix(commit: *mut *mut git_commit,
+                                    repo: *mut git_repository,
+                                    id: *const git_oid,
+                                    len: size_t) -> c_int;
     pub fn git_commit_message(commit: *const git_commit) -> *const c_char;
     pub fn git_commit_message_encoding(commit: *const git_commit) -> *const c_char;
     pub fn git_commit_message_raw(commit: *const git_commit) -> *const c_char;
@@ -1969,6 +2323,27 @@ extern {
     pub fn git_commit_header_field(out: *mut git_buf,
                                    commit: *const git_commit,
                                    field: *const c_char) -> c_int;
+    pub fn git_commit_create_buffer(out: *mut git_buf,
+                                    repo: *mut git_repository,
+                                    author: *const git_signature,
+                                    committer: *const git_signature,
+                                    message_encoding: *const c_char,
+                                    message: *const c_char,
+                                    tree: *const git_tree,
+                                    parent_count: size_t,
+                                    parents: *mut *const git_commit) -> c_int;
+    pub fn git_commit_create_with_signature(id: *mut git_oid,
+                                            repo: *mut git_repository,
+                                            commit_content: *const c_char,
+                                            signature: *const c_char,
+                                            signature_field: *const c_char)
+                                            -> c_int;
+    pub fn git_commit_extract_signature(signature: *mut git_buf,
+                                        signed_data: *mut git_buf,
+                                        repo: *mut git_repository,
+                                        commit_id: *mut git_oid,
+                                        field: *const c_char) -> c_int;
+    pub fn git_commit_owner(commit: *const git_commit) -> *mut git_repository;
     pub fn git_annotated_commit_lookup(out: *mut *mut git_annotated_commit,
                                        repo: *mut git_repository,
                                        id: *const git_oid) -> c_int;
@@ -2000,6 +2375,12 @@ extern {
                            iter: *mut git_branch_iterator) -> c_int;
     pub fn git_branch_set_upstream(branch: *mut git_reference,
                                    upstream_name: *const c_char) -> c_int;
+    pub fn git_branch_remote_name(out: *mut git_buf,
+                                  repo: *mut git_repository,
+                                  refname: *const c_char) -> c_int;
+    pub fn git_branch_upstream_remote(out: *mut git_buf,
+                                      repo: *mut git_repository,
+                                      refname: *const c_char) -> c_int;
     pub fn git_branch_upstream(out: *mut *mut git_reference,
                                branch: *const git_reference) -> c_int;
 
@@ -2034,6 +2415,8 @@ extern {
                                 path: *const c_char,
                                 stage: c_int) -> *const git_index_entry;
     pub fn git_index_has_conflicts(index: *const git_index) -> c_int;
+    pub fn git_index_caps(index: *const git_index) -> c_int;
+    pub fn git_index_set_caps(index: *mut git_index, caps: c_int) -> c_int;
     pub fn git_index_new(index: *mut *mut git_index) -> c_int;
     pub fn git_index_open(index: *mut *mut git_index,
                           index_path: *const c_char) -> c_int;
@@ -2141,6 +2524,8 @@ extern {
                                  value: *const c_char) -> c_int;
     pub fn git_config_snapshot(out: *mut *mut git_config,
                                config: *mut git_config) -> c_int;
+    pub fn git_config_lock(tx: *mut *mut git_transaction,
+                           cfg: *mut git_config) -> c_int;
     pub fn git_config_entry_free(entry: *mut git_config_entry);
 
     // cred
@@ -2250,6 +2635,11 @@ extern {
                                          repo: *mut git_repository,
                                          reference: *const git_reference)
                                          -> c_int;
+    pub fn git_annotated_commit_from_revspec(out: *mut *mut git_annotated_commit,
+                                             repo: *mut git_repository,
+                                             revspec: *const c_char) -> c_int;
+    pub fn git_annotated_commit_ref(commit: *const git_annotated_commit)
+                                    -> *const c_char;
     pub fn git_annotated_commit_free(commit: *mut git_annotated_commit);
     pub fn git_merge_init_options(opts: *mut git_merge_options,
                                   version: c_uint) -> c_int;
@@ -2269,6 +2659,23 @@ extern {
                            our_tree: *const git_tree,
                            their_tree: *const git_tree,
                            opts: *const git_merge_options) -> c_int;
+    pub fn git_merge_file_init_input(opts: *mut git_merge_file_input,
+                                     version: c_uint) -> c_int;
+    pub fn git_merge_file_init_options(opts: *mut git_merge_file_options,
+                                       version: c_uint) -> c_int;
+    pub fn git_merge_file(out: *mut git_merge_file_result,
+                          ancestor: *const git_merge_file_input,
+                          ours: *const git_merge_file_input,
+                          theirs: *const git_merge_file_input,
+                          opts: *const git_merge_file_options) -> c_int;
+    pub fn git_merge_file_from_index(out: *mut git_merge_file_result,
+                                     repo: *mut git_repository,
+                                     ancestor: *const git_index_entry,
+                                     ours: *const git_index_entry,
+                                     theirs: *const git_index_entry,
+                                     opts: *const git_merge_file_options)
+                                     -> c_int;
+    pub fn git_merge_file_result_free(result: *mut git_merge_file_result);
     pub fn git_repository_state_cleanup(repo: *mut git_repository) -> c_int;
 
     // merge analysis
@@ -2318,6 +2725,10 @@ extern {
                           path: *const c_char,
                           options: *mut git_blame_options) -> c_int;
     pub fn git_blame_free(blame: *mut git_blame);
+    pub fn git_blame_buffer(out: *mut *mut git_blame,
+                            reference: *mut git_blame,
+                            buffer: *const c_char,
+                            buffer_len: size_t) -> c_int;
 
     pub fn git_blame_init_options(opts: *mut git_blame_options,
                                   version: c_uint) -> c_int;
@@ -2669,6 +3080,14 @@ extern {
     pub fn git_odb_stream_read(stream: *mut git_odb_stream,
                                buffer: *mut c_char,
                                len: size_t) -> c_int;
+    pub fn git_odb_exists(db: *mut git_odb, id: *const git_oid) -> c_int;
+    pub fn git_odb_read_header(len_out: *mut size_t,
+                               type_out: *mut git_otype,
+                               db: *mut git_odb,
+                               id: *const git_oid) -> c_int;
+    pub fn git_odb_num_backends(odb: *mut git_odb) -> size_t;
+    pub fn git_odb_add_disk_alternate(odb: *mut git_odb,
+                                      path: *const c_char) -> c_int;
     pub fn git_odb_open_wstream(out: *mut *mut git_odb_stream,
                                 db: *mut git_odb,
                                 size: git_off_t,
@@ -2680,6 +3099,208 @@ extern {
                                          stream: *mut git_odb_stream) -> c_int;
     pub fn git_odb_stream_free(stream: *mut git_odb_stream);
     pub fn git_odb_foreach(db: *mut git_odb, cb: git_odb_foreach_cb, payload: *mut c_void) -> c_int;
+    pub fn git_odb_hash(out: *mut git_oid,
+                        data: *const c_void,
+                        len: size_t,
+                        otype: git_otype) -> c_int;
+    pub fn git_odb_hashfile(out: *mut git_oid,
+                            path: *const c_char,
+                            otype: git_otype) -> c_int;
+    pub fn git_odb_new(out: *mut *mut git_odb) -> c_int;
+    pub fn git_odb_add_backend(odb: *mut git_odb,
+                               backend: *mut git_odb_backend,
+                               priority: c_int) -> c_int;
+    pub fn git_odb_init_backend(backend: *mut git_odb_backend,
+                                version: c_uint) -> c_int;
+    pub fn git_odb_backend_malloc(backend: *mut git_odb_backend,
+                                  len: size_t) -> *mut c_void;
+    pub fn git_repository_set_odb(repo: *mut git_repository,
+                                  odb: *mut git_odb);
+
+    // indexer
+    pub fn git_indexer_new(out: *mut *mut git_indexer,
+                           path: *const c_char,
+                           mode: c_uint,
+                           odb: *mut git_odb,
+                           progress_cb: Option<git_transfer_progress_cb>,
+                           progress_cb_payload: *mut c_void) -> c_int;
+    pub fn git_indexer_append(idx: *mut git_indexer,
+                              data: *const c_void,
+                              size: size_t,
+                              stats: *mut git_transfer_progress) -> c_int;
+    pub fn git_indexer_commit(idx: *mut git_indexer,
+                              stats: *mut git_transfer_progress) -> c_int;
+    pub fn git_indexer_hash(idx: *const git_indexer) -> *const git_oid;
+    pub fn git_indexer_free(idx: *mut git_indexer);
+
+    // filter
+    pub fn git_filter_list_load(filters: *mut *mut git_filter_list,
+                                repo: *mut git_repository,
+                                blob: *mut git_blob,
+                                path: *const c_char,
+                                mode: git_filter_mode_t,
+                                flags: u32) -> c_int;
+    pub fn git_filter_list_contains(filters: *mut git_filter_list,
+                                    name: *const c_char) -> c_int;
+    pub fn git_filter_list_apply_to_data(out: *mut git_buf,
+                                         filters: *mut git_filter_list,
+                                         input: *mut git_buf) -> c_int;
+    pub fn git_filter_list_apply_to_blob(out: *mut git_buf,
+                                         filters: *mut git_filter_list,
+                                         blob: *mut git_blob) -> c_int;
+    pub fn git_filter_list_free(filters: *mut git_filter_list);
+
+    // rebase
+    pub fn git_rebase_init_options(opts: *mut git_rebase_options,
+                                   version: c_uint) -> c_int;
+    pub fn git_rebase_init(out: *mut *mut git_rebase,
+                           repo: *mut git_repository,
+                           branch: *const git_annotated_commit,
+                           upstream: *const git_annotated_commit,
+                           onto: *const git_annotated_commit,
+                           opts: *const git_rebase_options) -> c_int;
+    pub fn git_rebase_open(out: *mut *mut git_rebase,
+                           repo: *mut git_repository,
+                           opts: *const git_rebase_options) -> c_int;
+    pub fn git_rebase_operation_entrycount(rebase: *mut git_rebase) -> size_t;
+    pub fn git_rebase_operation_current(rebase: *mut git_rebase) -> size_t;
+    pub fn git_rebase_operation_byindex(rebase: *mut git_rebase,
+                                        idx: size_t)
+                                        -> *mut git_rebase_operation;
+    pub fn git_rebase_next(operation: *mut *mut git_rebase_operation,
+                           rebase: *mut git_rebase) -> c_int;
+    pub fn git_rebase_inmemory_index(index: *mut *mut git_index,
+                                     rebase: *mut git_rebase) -> c_int;
+    pub fn git_rebase_commit(id: *mut git_oid,
+                             rebase: *mut git_rebase,
+                             author: *const git_signature,
+                             committer: *const git_signature,
+                             message_encoding: *const c_char,
+                             message: *const c_char) -> c_int;
+    pub fn git_rebase_abort(rebase: *mut git_rebase) -> c_int;
+    pub fn git_rebase_finish(rebase: *mut git_rebase,
+                             signature: *const git_signature) -> c_int;
+    pub fn git_rebase_free(rebase: *mut git_rebase);
+
+    // transaction
+    pub fn git_transaction_new(out: *mut *mut git_transaction,
+                               repo: *mut git_repository) -> c_int;
+    pub fn git_transaction_lock_ref(tx: *mut git_transaction,
+                                    refname: *const c_char) -> c_int;
+    pub fn git_transaction_set_target(tx: *mut git_transaction,
+                                      refname: *const c_char,
+                                      target: *const git_oid,
+                                      sig: *const git_signature,
+                                      msg: *const c_char) -> c_int;
+    pub fn git_transaction_set_symbolic_target(tx: *mut git_transaction,
+                                               refname: *const c_char,
+                                               target: *const c_char,
+                                               sig: *const git_signature,
+                                               msg: *const c_char) -> c_int;
+    pub fn git_transaction_remove(tx: *mut git_transaction,
+                                  refname: *const c_char) -> c_int;
+    pub fn git_transaction_commit(tx: *mut git_transaction) -> c_int;
+    pub fn git_transaction_free(tx: *mut git_transaction);
+
+    // refdb
+    pub fn git_refdb_new(out: *mut *mut git_refdb,
+                         repo: *mut git_repository) -> c_int;
+    pub fn git_refdb_free(refdb: *mut git_refdb);
+    pub fn git_refdb_init_backend(backend: *mut git_refdb_backend,
+                                  version: c_uint) -> c_int;
+    pub fn git_refdb_set_backend(refdb: *mut git_refdb,
+                                 backend: *mut git_refdb_backend) -> c_int;
+    pub fn git_repository_set_refdb(repo: *mut git_repository,
+                                    refdb: *mut git_refdb);
+    pub fn git_reference__alloc(name: *const c_char,
+                                oid: *const git_oid,
+                                peel: *const git_oid) -> *mut git_reference;
+    pub fn git_reference__alloc_symbolic(name: *const c_char,
+                                         target: *const c_char)
+                                         -> *mut git_reference;
+}
+
+// Functions which the bundled libgit2 does not have yet.
+#[cfg(feature = "libgit2-0-28")]
+extern {
+    // refspec
+    pub fn git_refspec_parse(out: *mut *mut git_refspec,
+                             input: *const c_char,
+                             is_fetch: c_int) -> c_int;
+    pub fn git_refspec_free(rs: *mut git_refspec);
+
+    // worktree
+    pub fn git_worktree_list(out: *mut git_strarray,
+                             repo: *mut git_repository) -> c_int;
+    pub fn git_worktree_lookup(out: *mut *mut git_worktree,
+                               repo: *mut git_repository,
+                               name: *const c_char) -> c_int;
+    pub fn git_worktree_open_from_repository(out: *mut *mut git_worktree,
+                                             repo: *mut git_repository)
+                                             -> c_int;
+    pub fn git_worktree_free(wt: *mut git_worktree);
+    pub fn git_worktree_validate(wt: *const git_worktree) -> c_int;
+    pub fn git_worktree_add_init_options(opts: *mut git_worktree_add_options,
+                                         version: c_uint) -> c_int;
+    pub fn git_worktree_add(out: *mut *mut git_worktree,
+                            repo: *mut git_repository,
+                            name: *const c_char,
+                            path: *const c_char,
+                            opts: *const git_worktree_add_options) -> c_int;
+    pub fn git_worktree_lock(wt: *mut git_worktree,
+                             reason: *const c_char) -> c_int;
+    pub fn git_worktree_unlock(wt: *mut git_worktree) -> c_int;
+    pub fn git_worktree_is_locked(reason: *mut git_buf,
+                                  wt: *const git_worktree) -> c_int;
+    pub fn git_worktree_name(wt: *const git_worktree) -> *const c_char;
+    pub fn git_worktree_path(wt: *const git_worktree) -> *const c_char;
+    pub fn git_worktree_prune_init_options(opts: *mut git_worktree_prune_options,
+                                           version: c_uint) -> c_int;
+    pub fn git_worktree_is_prunable(wt: *mut git_worktree,
+                                    opts: *mut git_worktree_prune_options)
+                                    -> c_int;
+    pub fn git_worktree_prune(wt: *mut git_worktree,
+                              opts: *mut git_worktree_prune_options) -> c_int;
+    pub fn git_repository_open_from_worktree(out: *mut *mut git_repository,
+                                             wt: *mut git_worktree) -> c_int;
+    pub fn git_repository_is_worktree(repo: *mut git_repository) -> c_int;
+    pub fn git_repository_commondir(repo: *mut git_repository) -> *const c_char;
+
+    // mailmap
+    pub fn git_mailmap_new(out: *mut *mut git_mailmap) -> c_int;
+    pub fn git_mailmap_from_buffer(out: *mut *mut git_mailmap,
+                                   buf: *const c_char,
+                                   len: size_t) -> c_int;
+    pub fn git_mailmap_from_repository(out: *mut *mut git_mailmap,
+                                       repo: *mut git_repository) -> c_int;
+    pub fn git_mailmap_free(mm: *mut git_mailmap);
+    pub fn git_mailmap_add_entry(mm: *mut git_mailmap,
+                                 real_name: *const c_char,
+                                 real_email: *const c_char,
+                                 replace_name: *const c_char,
+                                 replace_email: *const c_char) -> c_int;
+    pub fn git_mailmap_resolve(real_name: *mut *const c_char,
+                               real_email: *mut *const c_char,
+                               mm: *const git_mailmap,
+                               name: *const c_char,
+                               email: *const c_char) -> c_int;
+    pub fn git_mailmap_resolve_signature(out: *mut *mut git_signature,
+                                         mm: *const git_mailmap,
+                                         sig: *const git_signature) -> c_int;
+    pub fn git_commit_author_with_mailmap(out: *mut *mut git_signature,
+                                          commit: *const git_commit,
+                                          mailmap: *const git_mailmap) -> c_int;
+    pub fn git_commit_committer_with_mailmap(out: *mut *mut git_signature,
+                                             commit: *const git_commit,
+                                             mailmap: *const git_mailmap)
+                                             -> c_int;
+
+    // apply
+    pub fn git_apply_to_tree(out: *mut *mut git_index,
+                             repo: *mut git_repository,
+                             preimage: *mut git_tree,
+                             diff: *mut git_diff,
+                             options: *const git_apply_options) -> c_int;
 }
 
 pub fn init() {
@@ -1,5 +1,7 @@
 use std::marker;
-use {raw, Repository, Oid, signature, Signature};
+use std::ptr;
+use libc::c_char;
+use {raw, Error, Repository, Oid, signature, Signature};
 use util::{self, Binding};
 use std::path::Path;
 use std::ops::Range;
@@ -70,6 +72,23 @@ impl<'repo> Blame<'repo> {
         BlameIter { range: 0..self.len(), blame: self }
     }
 
+    /// Get blame data for a modified version of the file this blame is for,
+    /// such as the contents of an editor buffer with unsaved changes.
+    ///
+    /// Lines of `buffer` which are unchanged from the blamed file keep their
+    /// blame, while added or modified lines are put in hunks whose
+    /// `final_commit_id` is zero, as they are not committed yet. Such hunks
+    /// have no signature. This blame is not modified and remains usable.
+    pub fn blame_buffer(&self, buffer: &[u8]) -> Result<Blame<'repo>, Error> {
+        let mut raw = ptr::null_mut();
+        unsafe {
+            try_call!(raw::git_blame_buffer(&mut raw, self.raw,
+                                            buffer.as_ptr() as *const c_char,
+                                            buffer.len()));
+            Ok(Binding::from_raw(raw))
+        }
+    }
+
 }
 
 impl<'blame> BlameHunk<'blame> {
@@ -88,8 +107,18 @@ impl<'blame> BlameHunk<'blame> {
     }
 
     /// Returns signature of the commit.
-    pub fn final_signature(&self) -> Signature {
-        unsafe { signature::from_raw_const(self, (*self.raw).final_signature) }
+    ///
+    /// Returns `None` if the hunk is for lines which are not committed yet,
+    /// see `Blame::blame_buffer`.
+    pub fn final_signature(&self) -> Option<Signature> {
+        unsafe {
+            let sig = (*self.raw).final_signature;
+            if sig.is_null() {
+                None
+            } else {
+                Some(signature::from_raw_const(self, sig))
+            }
+        }
     }
 
     /// Returns line number where this hunk begins.
@@ -109,8 +138,18 @@ impl<'blame> BlameHunk<'blame> {
     }
 
     /// Returns signature of the commit.
-    pub fn orig_signature(&self) -> Signature {
-        unsafe { signature::from_raw_const(self, (*self.raw).orig_signature) }
+    ///
+    /// Returns `None` if the hunk is for lines which are not committed yet,
+    /// see `Blame::blame_buffer`.
+    pub fn orig_signature(&self) -> Option<Signature> {
+        unsafe {
+            let sig = (*self.raw).orig_signature;
+            if sig.is_null() {
+                None
+            } else {
+                Some(signature::from_raw_const(self, sig))
+            }
+        }
     }
 
     /// Returns line number where this hunk begins.
@@ -276,6 +315,7 @@ impl<'blame> ExactSizeIterator for BlameIter<'blame> {}
 #[cfg(test)]
 mod tests {
     use std::fs::{self, File};
+    use std::io::prelude::*;
     use std::path::Path;
 
     #[test]
@@ -303,13 +343,47 @@ mod tests {
 
         let hunk = blame.get_index(0).unwrap();
         assert_eq!(hunk.final_commit_id(), commit);
-        assert_eq!(hunk.final_signature().name(), sig.name());
-        assert_eq!(hunk.final_signature().email(), sig.email());
+        assert_eq!(hunk.final_signature().unwrap().name(), sig.name());
+        assert_eq!(hunk.final_signature().unwrap().email(), sig.email());
         assert_eq!(hunk.final_start_line(), 1);
         assert_eq!(hunk.path(), Some(Path::new("foo/bar")));
         assert_eq!(hunk.lines_in_hunk(), 0);
         assert!(!hunk.is_boundary())
     }
 
+    #[test]
+    fn blame_buffer() {
+        let (td, repo) = ::test::repo_init();
+        let mut index = repo.index().unwrap();
+        File::create(&td.path().join("foo")).unwrap()
+            .write_all(b"a\nb\nc\n").unwrap();
+        index.add_path(Path::new("foo")).unwrap();
+        let id = index.write_tree().unwrap();
+        let tree = repo.find_tree(id).unwrap();
+        let sig = repo.signature().unwrap();
+        let id = repo.refname_to_id("HEAD").unwrap();
+        let parent = repo.find_commit(id).unwrap();
+        let commit = repo.commit(Some("HEAD"), &sig, &sig, "commit",
+                                 &tree, &[&parent]).unwrap();
+
+        let blame = repo.blame_file(Path::new("foo"), None).unwrap();
+        let dirty = blame.blame_buffer(b"a\nnew\nb\nc\n").unwrap();
+        assert_eq!(dirty.get_line(1).unwrap().final_commit_id(), commit);
+        let hunk = dirty.get_line(2).unwrap();
+        assert!(hunk.final_commit_id().is_zero());
+        assert!(hunk.final_signature().is_none());
+        assert!(hunk.orig_signature().is_none());
+        assert_eq!(hunk.final_start_line(), 2);
+        assert_eq!(hunk.lines_in_hunk(), 1);
+        assert_eq!(dirty.get_line(3).unwrap().final_commit_id(), commit);
+        assert_eq!(dirty.get_line(4).unwrap().final_commit_id(), commit);
+
+        // the original blame is unaffected
+        assert_eq!(blame.len(), 1);
+        assert_eq!(blame.get_line(2).unwrap().final_commit_id(), commit);
+        assert_eq!(blame.get_line(2).unwrap().final_signature().unwrap().name(),
+                   sig.name());
+    }
+
 }
 
@@ -1,11 +1,17 @@
+use std::cmp;
 use std::marker;
 use std::mem;
+use std::ptr;
 use std::slice;
 use std::io;
+use libc::{c_char, size_t};
 
 use {raw, Oid, Object, Error};
 use util::Binding;
 
+/// The number of bytes libgit2 inspects when guessing whether a blob is binary.
+const BINARY_CHECK_LEN: usize = 8000;
+
 /// A structure to represent a git [blob][1]
 ///
 /// [1]: http://git-scm.com/book/en/Git-Internals-Git-Objects
@@ -20,11 +26,39 @@ impl<'repo> Blob<'repo> {
         unsafe { Binding::from_raw(raw::git_blob_id(&*self.raw)) }
     }
 
+    /// Create an independently-owned handle to this blob.
+    ///
+    /// The new handle refers to the same underlying blob and remains valid
+    /// after this one is dropped.
+    pub fn dup(&self) -> Result<Blob<'repo>, Error> {
+        let mut raw = ptr::null_mut();
+        unsafe {
+            try_call!(raw::git_blob_dup(&mut raw, self.raw));
+            Ok(Binding::from_raw(raw))
+        }
+    }
+
     /// Determine if the blob content is most certainly binary or not.
     pub fn is_binary(&self) -> bool {
         unsafe { raw::git_blob_is_binary(&*self.raw) == 1 }
     }
 
+    /// Determine if a buffer of data is most certainly binary or not, using
+    /// the same heuristic as `is_binary`.
+    ///
+    /// Like `is_binary`, only the first 8000 bytes of `data` are inspected, so
+    /// this agrees with `is_binary` on a blob created from the same data.
+    pub fn data_is_binary(data: &[u8]) -> bool {
+        ::init();
+        let data = &data[..cmp::min(data.len(), BINARY_CHECK_LEN)];
+        let buf = raw::git_buf {
+            ptr: data.as_ptr() as *mut c_char,
+            asize: 0,
+            size: data.len() as size_t,
+        };
+        unsafe { raw::git_buf_is_binary(&buf) == 1 }
+    }
+
     /// Get the content of this blob.
     pub fn content(&self) -> &[u8] {
         unsafe {
@@ -84,11 +118,22 @@ impl<'repo> Drop for Blob<'repo> {
 pub struct BlobWriter<'repo> {
     raw: *mut raw::git_writestream,
     need_cleanup: bool,
+    bytes_written: u64,
     _marker: marker::PhantomData<Object<'repo>>,
 }
 
 impl<'repo> BlobWriter<'repo> {
+    /// Get the number of bytes written to this stream so far.
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes_written
+    }
+
     /// Finalize blob writing stream and write the blob to the object db
+    ///
+    /// All buffered data is flushed before the blob is written. The stream is
+    /// released whether or not this succeeds. If a `BlobWriter` is dropped
+    /// without being committed, the data written so far is discarded and no
+    /// object is created.
     pub fn commit(mut self) -> Result<Oid, Error> {
         // After commit we already doesn't need cleanup on drop
         self.need_cleanup = false;
@@ -107,6 +152,7 @@ impl<'repo> Binding for BlobWriter<'repo> {
         BlobWriter {
             raw: raw,
             need_cleanup: true,
+            bytes_written: 0,
             _marker: marker::PhantomData,
         }
     }
@@ -129,20 +175,23 @@ impl<'repo> io::Write for BlobWriter<'repo> {
             if res < 0 {
                 Err(io::Error::new(io::ErrorKind::Other, "Write error"))
             } else {
+                self.bytes_written += buf.len() as u64;
                 Ok(buf.len())
             }
         }
     }
+    // Data is buffered by libgit2 and only flushed in `commit`
     fn flush(&mut self) -> io::Result<()> { Ok(()) }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::io;
     use std::io::prelude::*;
     use std::fs::File;
     use std::path::Path;
     use tempdir::TempDir;
-    use Repository;
+    use {Blob, ObjectType, Odb, Repository};
 
     #[test]
     fn buffer() {
@@ -159,6 +208,22 @@ mod tests {
         repo.find_object(id, None).unwrap().into_blob().ok().unwrap();
     }
 
+    #[test]
+    fn data_is_binary() {
+        let (_td, repo) = ::test::repo_init();
+        let mut late_nul = vec![b'a'; 9000];
+        late_nul[8500] = 0;
+        let samples: [&[u8]; 6] = [b"", b"plain text\n", b"a\0b", &[5, 4, 6],
+                                   b"caf\xc3\xa9\r\n", &late_nul];
+        for data in samples.iter() {
+            let blob = repo.find_blob(repo.blob(data).unwrap()).unwrap();
+            assert_eq!(Blob::data_is_binary(data), blob.is_binary());
+        }
+        assert!(!Blob::data_is_binary(b"plain text\n"));
+        assert!(Blob::data_is_binary(b"a\0b"));
+        assert!(!Blob::data_is_binary(&late_nul));
+    }
+
     #[test]
     fn path() {
         let td = TempDir::new("test").unwrap();
@@ -176,11 +241,44 @@ mod tests {
         let td = TempDir::new("test").unwrap();
         let repo = Repository::init(td.path()).unwrap();
         let mut ws = repo.blob_writer(Some(Path::new("foo"))).unwrap();
+        assert_eq!(ws.bytes_written(), 0);
         let wl = ws.write(&[10, 11, 12]).unwrap();
         assert_eq!(wl, 3);
+        assert_eq!(ws.bytes_written(), 3);
         let id = ws.commit().unwrap();
         let blob = repo.find_blob(id).unwrap();
         assert_eq!(blob.content(), [10, 11, 12]);
         blob.into_object();
     }
+
+    #[test]
+    fn stream_copy() {
+        let td = TempDir::new("test").unwrap();
+        let path = td.path().join("large");
+        let data = (0..1024 * 1024).map(|i| i as u8).collect::<Vec<u8>>();
+        File::create(&path).unwrap().write_all(&data).unwrap();
+
+        let repo = Repository::init(td.path()).unwrap();
+        let mut ws = repo.blob_writer(None).unwrap();
+        let n = io::copy(&mut File::open(&path).unwrap(), &mut ws).unwrap();
+        assert_eq!(n, data.len() as u64);
+        assert_eq!(ws.bytes_written(), n);
+        let id = ws.commit().unwrap();
+        assert_eq!(id, repo.blob_path(&path).unwrap());
+        assert_eq!(repo.find_blob(id).unwrap().content(), &data[..]);
+    }
+
+    #[test]
+    fn stream_dropped() {
+        let (_td, repo) = ::test::repo_init();
+        let data = b"never committed";
+        {
+            let mut ws = repo.blob_writer(None).unwrap();
+            ws.write_all(data).unwrap();
+            assert_eq!(ws.bytes_written(), data.len() as u64);
+        }
+        let id = Odb::hash(data, ObjectType::Blob).unwrap();
+        assert!(!repo.odb().unwrap().exists(id));
+        assert!(repo.find_blob(id).is_err());
+    }
 }
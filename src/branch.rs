@@ -135,7 +135,7 @@ impl<'repo> Drop for Branches<'repo> {
 
 #[cfg(test)]
 mod tests {
-    use BranchType;
+    use {BranchType, ErrorCode};
 
     #[test]
     fn smoke() {
@@ -159,4 +159,16 @@ mod tests {
 
         b1.delete().unwrap();
     }
+
+    #[test]
+    fn find_branch() {
+        let (_td, repo) = ::test::repo_init();
+        let master = repo.find_branch("master", BranchType::Local).unwrap();
+        assert!(master.is_head());
+        assert_eq!(master.get().name(), Some("refs/heads/master"));
+
+        let err = repo.find_branch("missing", BranchType::Local).err().unwrap();
+        assert_eq!(err.code(), ErrorCode::NotFound);
+        assert!(repo.find_branch("master", BranchType::Remote).is_err());
+    }
 }
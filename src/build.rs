@@ -48,11 +48,14 @@ pub type Progress<'a> = FnMut(Option<&Path>, usize, usize) + 'a;
 ///
 /// The first argument is the notification type, the next is the path for the
 /// the notification, followed by the baseline diff, target diff, and workdir diff.
+/// Each of the diff files is `None` if the path doesn't exist on that side,
+/// e.g. an untracked file has no baseline or target.
 ///
 /// The callback must return a bool specifying whether the checkout should
 /// continue.
-pub type Notify<'a> = FnMut(CheckoutNotificationType, Option<&Path>, DiffFile,
-                            DiffFile, DiffFile) -> bool + 'a;
+pub type Notify<'a> = FnMut(CheckoutNotificationType, Option<&Path>,
+                            Option<DiffFile>, Option<DiffFile>,
+                            Option<DiffFile>) -> bool + 'a;
 
 
 impl<'cb> Default for RepoBuilder<'cb> {
@@ -201,10 +204,16 @@ impl<'cb> CheckoutBuilder<'cb> {
 
     /// Indicate that this checkout should perform a dry run by checking for
     /// conflicts but not make any actual changes.
+    ///
+    /// Nothing is written to the working directory or the index. This
+    /// replaces the checkout strategy, so it should be called after `force`
+    /// or `safe` rather than before.
     pub fn dry_run(&mut self) -> &mut CheckoutBuilder<'cb> {
+        // Versions of libgit2 without GIT_CHECKOUT_DRY_RUN ignore it, so the
+        // strategy is also set to GIT_CHECKOUT_NONE for them.
         self.checkout_opts &= !((1 << 4) - 1);
         self.checkout_opts |= raw::GIT_CHECKOUT_NONE as u32;
-        self
+        self.flag(raw::GIT_CHECKOUT_DRY_RUN, true)
     }
 
     /// Take any action necessary to get the working directory to match the
@@ -416,11 +425,13 @@ impl<'cb> CheckoutBuilder<'cb> {
 
     /// Set a callback to receive checkout notifications.
     ///
-    /// Callbacks are invoked prior to modifying any files on disk.
-    /// Returning `false` from the callback will cancel the checkout.
+    /// Callbacks are invoked prior to modifying any files on disk, for the
+    /// notification types selected with `notify_on`. Returning `false` from
+    /// the callback will cancel the checkout. See `Notify` for the arguments.
     pub fn notify<F>(&mut self, cb: F) -> &mut CheckoutBuilder<'cb>
-        where F: FnMut(CheckoutNotificationType, Option<&Path>, DiffFile,
-                       DiffFile, DiffFile) -> bool + 'cb
+        where F: FnMut(CheckoutNotificationType, Option<&Path>,
+                       Option<DiffFile>, Option<DiffFile>,
+                       Option<DiffFile>) -> bool + 'cb
     {
         self.notify = Some(Box::new(cb) as Box<Notify<'cb>>);
         self
@@ -509,9 +520,9 @@ extern fn notify_cb(why: raw::git_checkout_notify_t,
         let why = CheckoutNotificationType::from_bits_truncate(why as u32);
         let keep_going = callback(why,
                                   path,
-                                  DiffFile::from_raw(baseline),
-                                  DiffFile::from_raw(target),
-                                  DiffFile::from_raw(workdir));
+                                  Binding::from_raw_opt(baseline),
+                                  Binding::from_raw_opt(target),
+                                  Binding::from_raw_opt(workdir));
         if keep_going {0} else {1}
     }).unwrap_or(2)
 }
@@ -519,10 +530,13 @@ extern fn notify_cb(why: raw::git_checkout_notify_t,
 #[cfg(test)]
 mod tests {
     use std::fs;
+    use std::fs::File;
+    use std::io::prelude::*;
     use std::path::Path;
     use tempdir::TempDir;
-    use super::RepoBuilder;
-    use Repository;
+    use super::{CheckoutBuilder, RepoBuilder};
+    use {Repository, CHECKOUT_NOTIFICATION_CONFLICT};
+    use CHECKOUT_NOTIFICATION_UNTRACKED;
 
     #[test]
     fn smoke() {
@@ -553,4 +567,82 @@ mod tests {
                                   .clone(&url, &dst).is_err());
     }
 
+    #[test]
+    fn notify() {
+        let (td, repo) = ::test::repo_init();
+        File::create(td.path().join("foo")).unwrap()
+            .write_all(b"original").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("foo")).unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+
+        File::create(td.path().join("foo")).unwrap()
+            .write_all(b"modified").unwrap();
+        File::create(td.path().join("bar")).unwrap()
+            .write_all(b"untracked").unwrap();
+
+        let mut seen = Vec::new();
+        {
+            let mut opts = CheckoutBuilder::new();
+            opts.notify_on(CHECKOUT_NOTIFICATION_CONFLICT |
+                           CHECKOUT_NOTIFICATION_UNTRACKED);
+            opts.notify(|kind, path, baseline, target, workdir| {
+                seen.push((kind, path.unwrap().to_path_buf(),
+                           baseline.is_some(), target.is_some(),
+                           workdir.is_some()));
+                true
+            });
+            assert!(repo.checkout_tree(tree.as_object(), Some(&mut opts))
+                        .is_err());
+        }
+        seen.sort_by(|a, b| a.1.cmp(&b.1));
+        assert_eq!(seen, vec![
+            (CHECKOUT_NOTIFICATION_UNTRACKED, Path::new("bar").to_path_buf(),
+             false, false, true),
+            (CHECKOUT_NOTIFICATION_CONFLICT, Path::new("foo").to_path_buf(),
+             false, true, true),
+        ]);
+
+        // returning false aborts the checkout at the first notification
+        let mut calls = 0;
+        {
+            let mut opts = CheckoutBuilder::new();
+            opts.notify_on(CHECKOUT_NOTIFICATION_UNTRACKED);
+            opts.notify(|_, _, _, _, _| { calls += 1; false });
+            assert!(repo.checkout_tree(tree.as_object(), Some(&mut opts))
+                        .is_err());
+        }
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn dry_run() {
+        let (td, repo) = ::test::repo_init();
+        File::create(td.path().join("foo")).unwrap()
+            .write_all(b"original").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("foo")).unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        fs::remove_file(td.path().join("foo")).unwrap();
+        index.clear().unwrap();
+        index.write().unwrap();
+
+        let mut opts = CheckoutBuilder::new();
+        opts.force().dry_run();
+        repo.checkout_tree(tree.as_object(), Some(&mut opts)).unwrap();
+        assert!(!td.path().join("foo").exists());
+        assert_eq!(repo.index().unwrap().len(), 0);
+
+        // a file in the way is left alone
+        File::create(td.path().join("foo")).unwrap()
+            .write_all(b"modified").unwrap();
+        let mut opts = CheckoutBuilder::new();
+        opts.force().dry_run();
+        let _ = repo.checkout_tree(tree.as_object(), Some(&mut opts));
+        let mut contents = String::new();
+        File::open(td.path().join("foo")).unwrap()
+            .read_to_string(&mut contents).unwrap();
+        assert_eq!(contents, "modified");
+    }
+
 }
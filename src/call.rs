@@ -45,9 +45,7 @@ pub fn try(ret: libc::c_int) -> Result<libc::c_int, Error> {
 pub fn last_error(code: libc::c_int) -> Error {
     // Apparently libgit2 isn't necessarily guaranteed to set the last error
     // whenever a function returns a negative value!
-    Error::last_error(code).unwrap_or_else(|| {
-        Error::from_str("an unknown error occurred")
-    })
+    Error::last_error(code).unwrap_or_else(|| ::error::unknown(code))
 }
 
 mod impls {
@@ -58,6 +56,7 @@ mod impls {
 
     use {raw, ConfigLevel, ResetType, ObjectType, BranchType, Direction};
     use {DiffFormat, FileFavor, SubmoduleIgnore, AutotagOption, FetchPrune};
+    use FilterMode;
     use call::Convert;
 
     impl<T: Copy> Convert<T> for T {
@@ -130,6 +129,15 @@ mod impls {
         }
     }
 
+    impl Convert<raw::git_filter_mode_t> for FilterMode {
+        fn convert(&self) -> raw::git_filter_mode_t {
+            match *self {
+                FilterMode::ToWorktree => raw::GIT_FILTER_TO_WORKTREE,
+                FilterMode::ToOdb => raw::GIT_FILTER_TO_ODB,
+            }
+        }
+    }
+
     impl Convert<raw::git_branch_t> for BranchType {
         fn convert(&self) -> raw::git_branch_t {
             match *self {
@@ -1,3 +1,4 @@
+use std::ffi::CString;
 use std::marker;
 use std::mem;
 use std::ops::Range;
@@ -5,7 +6,9 @@ use std::ptr;
 use std::str;
 use libc;
 
-use {raw, signature, Oid, Error, Signature, Tree, Time, Object};
+use {raw, signature, Buf, Oid, Error, Signature, Tree, Time, Object};
+#[cfg(feature = "libgit2-0-28")]
+use Mailmap;
 use util::Binding;
 
 /// A structure to represent a git [commit][1]
@@ -28,12 +31,30 @@ pub struct ParentIds<'commit> {
     commit: &'commit Commit<'commit>,
 }
 
+/// An iterator over a commit and its first-parent ancestors, created by
+/// `Commit::ancestors_first_parent`.
+pub struct FirstParentAncestors<'repo> {
+    next: Option<Result<Commit<'repo>, Error>>,
+}
+
 impl<'repo> Commit<'repo> {
     /// Get the id (SHA1) of a repository commit
     pub fn id(&self) -> Oid {
         unsafe { Binding::from_raw(raw::git_commit_id(&*self.raw)) }
     }
 
+    /// Create an independently-owned handle to this commit.
+    ///
+    /// The new handle refers to the same underlying commit and remains valid
+    /// after this one is dropped.
+    pub fn dup(&self) -> Result<Commit<'repo>, Error> {
+        let mut raw = ptr::null_mut();
+        unsafe {
+            try_call!(raw::git_commit_dup(&mut raw, self.raw));
+            Ok(Binding::from_raw(raw))
+        }
+    }
+
     /// Get the id of the tree pointed to by this commit.
     ///
     /// No attempts are made to fetch an object from the ODB.
@@ -74,14 +95,16 @@ impl<'repo> Commit<'repo> {
     }
 
     /// Get the encoding for the message of a commit, as a string representing a
-    /// standard encoding name.
+    /// standard encoding name such as `ISO-8859-1`.
     ///
-    /// `None` will be returned if the encoding is not known
+    /// This is the value of the commit's `encoding` header. `None` will be
+    /// returned if there is no such header, in which case the message is
+    /// assumed to be UTF-8, or if the encoding name is not valid utf-8.
     pub fn message_encoding(&self) -> Option<&str> {
         let bytes = unsafe {
-            ::opt_bytes(self, raw::git_commit_message(&*self.raw))
+            ::opt_bytes(self, raw::git_commit_message_encoding(&*self.raw))
         };
-        bytes.map(|b| str::from_utf8(b).unwrap())
+        bytes.and_then(|b| str::from_utf8(b).ok())
     }
 
     /// Get the full raw message of a commit.
@@ -112,6 +135,43 @@ impl<'repo> Commit<'repo> {
         }
     }
 
+    /// Get the raw value of an arbitrary header field, such as `gpgsig`.
+    ///
+    /// Returns an error with the code `NotFound` if there is no such field in
+    /// the commit header.
+    pub fn header_field(&self, field: &str) -> Result<Buf, Error> {
+        let buf = Buf::new();
+        let field = try!(CString::new(field));
+        unsafe {
+            try_call!(raw::git_commit_header_field(buf.raw(), &*self.raw,
+                                                   field));
+        }
+        Ok(buf)
+    }
+
+    /// Extract the signature of this commit, along with the data which was
+    /// signed.
+    ///
+    /// The signature is looked up in the header field `signature_field`, or
+    /// `gpgsig` if `None`. Returns an error with the code `NotFound` if the
+    /// commit is not signed.
+    pub fn extract_signature(&self, signature_field: Option<&str>)
+                             -> Result<(Buf, Buf), Error> {
+        let signature_field = try!(::opt_cstr(signature_field));
+        let signature = Buf::new();
+        let signed_data = Buf::new();
+        let id = self.id();
+        unsafe {
+            let repo = raw::git_commit_owner(&*self.raw);
+            try_call!(raw::git_commit_extract_signature(signature.raw(),
+                                                        signed_data.raw(),
+                                                        repo,
+                                                        id.raw() as *mut _,
+                                                        signature_field));
+        }
+        Ok((signature, signed_data))
+    }
+
     /// Get the short "summary" of the git commit message.
     ///
     /// The returned message is the summary of the commit, comprising the first
@@ -173,6 +233,33 @@ impl<'repo> Commit<'repo> {
         }
     }
 
+    /// Get the author of this commit, using the mailmap to map names and email
+    /// addresses to canonical real names and email addresses.
+    #[cfg(feature = "libgit2-0-28")]
+    pub fn author_with_mailmap(&self, mailmap: &Mailmap)
+                               -> Result<Signature<'static>, Error> {
+        let mut ret = ptr::null_mut();
+        unsafe {
+            try_call!(raw::git_commit_author_with_mailmap(&mut ret, &*self.raw,
+                                                          &*mailmap.raw()));
+            Ok(Binding::from_raw(ret))
+        }
+    }
+
+    /// Get the committer of this commit, using the mailmap to map names and
+    /// email addresses to canonical real names and email addresses.
+    #[cfg(feature = "libgit2-0-28")]
+    pub fn committer_with_mailmap(&self, mailmap: &Mailmap)
+                                  -> Result<Signature<'static>, Error> {
+        let mut ret = ptr::null_mut();
+        unsafe {
+            try_call!(raw::git_commit_committer_with_mailmap(&mut ret,
+                                                             &*self.raw,
+                                                             &*mailmap.raw()));
+            Ok(Binding::from_raw(ret))
+        }
+    }
+
     /// Amend this existing commit with all non-`None` values
     ///
     /// This creates a new commit that is exactly the same as the old commit,
@@ -235,6 +322,33 @@ impl<'repo> Commit<'repo> {
         }
     }
 
+    /// Get the commit object that is the `n`th generation ancestor of this
+    /// commit, following only the first parent of each commit.
+    ///
+    /// This is the equivalent of the `~n` revspec suffix, so passing `0`
+    /// returns a handle to this commit itself. An error with the code
+    /// `NotFound` is returned if there are fewer than `n` ancestors.
+    pub fn nth_gen_ancestor(&self, n: u32) -> Result<Commit<'repo>, Error> {
+        let mut raw = ptr::null_mut();
+        unsafe {
+            try_call!(raw::git_commit_nth_gen_ancestor(&mut raw, &*self.raw,
+                                                       n as libc::c_uint));
+            Ok(Binding::from_raw(raw))
+        }
+    }
+
+    /// Creates a new iterator over this commit and its ancestors, following
+    /// only the first parent of each commit.
+    ///
+    /// This commit is yielded first and iteration ends after a root commit.
+    /// Commits brought in by the other parents of a merge are not visited,
+    /// which makes this suitable for listing the merges into a mainline.
+    /// `Revwalk::simplify_first_parent` offers the same traversal in a
+    /// revwalk.
+    pub fn ancestors_first_parent(&self) -> FirstParentAncestors<'repo> {
+        FirstParentAncestors { next: Some(Ok(self.clone())) }
+    }
+
     /// Casts this Commit to be usable as an `Object`
     pub fn as_object(&self) -> &Object<'repo> {
         unsafe {
@@ -305,6 +419,20 @@ impl<'commit> DoubleEndedIterator for ParentIds<'commit> {
 
 impl<'commit> ExactSizeIterator for ParentIds<'commit> {}
 
+impl<'repo> Iterator for FirstParentAncestors<'repo> {
+    type Item = Result<Commit<'repo>, Error>;
+    fn next(&mut self) -> Option<Result<Commit<'repo>, Error>> {
+        let commit = match self.next.take() {
+            Some(Ok(commit)) => commit,
+            other => return other,
+        };
+        if commit.parent_ids().len() > 0 {
+            self.next = Some(commit.parent(0));
+        }
+        Some(Ok(commit))
+    }
+}
+
 impl<'repo> Clone for Commit<'repo> {
     fn clone(&self) -> Self {
         self.as_object().clone().into_commit().ok().unwrap()
@@ -329,6 +457,13 @@ mod tests {
         assert_eq!(commit.id(), target);
         commit.message_raw().unwrap();
         commit.raw_header().unwrap();
+        let author = commit.header_field("author").unwrap();
+        assert!(author.as_str().unwrap().starts_with("name <email> "));
+        assert!(commit.extract_signature(None).is_err());
+        match commit.header_field("gpgsig") {
+            Err(e) => assert_eq!(e.code(), ::ErrorCode::NotFound),
+            Ok(..) => panic!("commit unexpectedly has a gpgsig header"),
+        }
         commit.message_encoding();
         commit.summary().unwrap();
         commit.tree_id();
@@ -350,10 +485,160 @@ mod tests {
                                   Some("new message"), None).unwrap();
         let new_head = repo.find_commit(new_head).unwrap();
         assert_eq!(new_head.message(), Some("new message"));
+        assert_eq!(new_head.author().name(), head.author().name());
+        assert_eq!(new_head.tree_id(), head.tree_id());
+        assert_eq!(new_head.parent_ids().collect::<Vec<_>>(), vec![commit.id()]);
+        assert_eq!(repo.head().unwrap().target(), Some(new_head.id()));
         new_head.into_object();
 
+        let content = repo.commit_create_buffer(&sig, &sig, "signed", &tree,
+                                                &[&commit]).unwrap();
+        let content = content.as_str().unwrap();
+        let signature = "-----BEGIN PGP SIGNATURE-----\n\
+                         fake\n\
+                         -----END PGP SIGNATURE-----";
+        let id = repo.commit_signed(content, signature, None).unwrap();
+        let signed = repo.find_commit(id).unwrap();
+        assert_eq!(signed.message(), Some("signed"));
+        let (sig_buf, data_buf) = signed.extract_signature(None).unwrap();
+        assert_eq!(sig_buf.as_str(), Some(signature));
+        assert_eq!(data_buf.as_str(), Some(content));
+
         repo.find_object(target, None).unwrap().as_commit().unwrap();
         repo.find_object(target, None).unwrap().into_commit().ok().unwrap();
     }
+
+    #[test]
+    fn raw_bytes() {
+        use std::io::prelude::*;
+        use ObjectType;
+
+        let (_td, repo) = ::test::repo_init();
+        let parent = repo.head().unwrap().target().unwrap();
+        let tree = repo.find_commit(parent).unwrap().tree_id();
+        let header = format!("tree {}\n\
+                              parent {}\n\
+                              author a <a@example.com> 1 +0000\n\
+                              committer c <c@example.com> 2 +0100\n\
+                              x-extra some value\n", tree, parent);
+        let mut data = header.clone().into_bytes();
+        data.extend_from_slice(b"\ncaf\xe9\n");
+
+        let odb = repo.odb().unwrap();
+        let mut writer = odb.writer(data.len(), ObjectType::Commit).unwrap();
+        writer.write_all(&data).unwrap();
+        let id = writer.finalize().unwrap();
+
+        let commit = repo.find_commit(id).unwrap();
+        assert_eq!(commit.raw_header(), Some(&*header));
+        assert_eq!(commit.raw_header_bytes(), header.as_bytes());
+        assert_eq!(commit.message_bytes(), b"caf\xe9\n");
+        assert_eq!(commit.message(), None);
+        assert_eq!(commit.message_encoding(), None);
+        assert_eq!(commit.parent_id(0).unwrap(), parent);
+    }
+
+    #[test]
+    fn message_encoding() {
+        use std::io::prelude::*;
+        use ObjectType;
+
+        let (_td, repo) = ::test::repo_init();
+        let head = repo.head().unwrap().target().unwrap();
+        let commit = repo.find_commit(head).unwrap();
+        assert_eq!(commit.message_encoding(), None);
+
+        let data = format!("tree {}\n\
+                            author a <a@example.com> 1 +0000\n\
+                            committer c <c@example.com> 2 +0000\n\
+                            encoding ISO-8859-1\n\
+                            \n\
+                            message\n", commit.tree_id());
+        let odb = repo.odb().unwrap();
+        let mut writer = odb.writer(data.len(), ObjectType::Commit).unwrap();
+        writer.write_all(data.as_bytes()).unwrap();
+        let id = writer.finalize().unwrap();
+        let commit = repo.find_commit(id).unwrap();
+        assert_eq!(commit.message_encoding(), Some("ISO-8859-1"));
+        assert_eq!(commit.message(), Some("message\n"));
+    }
+
+    #[test]
+    fn dup() {
+        let (_td, repo) = ::test::repo_init();
+        let target = repo.head().unwrap().target().unwrap();
+        let mut commits = Vec::new();
+        let tree = {
+            let commit = repo.find_commit(target).unwrap();
+            commits.push(commit.dup().unwrap());
+            commit.tree().unwrap().dup().unwrap()
+        };
+        assert_eq!(commits[0].id(), target);
+        assert_eq!(commits[0].tree_id(), tree.id());
+
+        let blob = repo.find_blob(repo.blob(b"foo").unwrap()).unwrap();
+        let copy = blob.dup().unwrap();
+        drop(blob);
+        assert_eq!(copy.content(), b"foo");
+
+        let object = repo.find_object(target, None).unwrap().dup().unwrap();
+        assert_eq!(object.id(), target);
+    }
+
+    #[test]
+    fn nth_gen_ancestor() {
+        let (_td, repo) = ::test::repo_init();
+        let sig = repo.signature().unwrap();
+        let root = repo.find_commit(repo.head().unwrap().target().unwrap())
+                       .unwrap();
+        let tree = root.tree().unwrap();
+        let side = repo.commit(None, &sig, &sig, "side", &tree,
+                               &[&root]).unwrap();
+        let side = repo.find_commit(side).unwrap();
+        let second = repo.commit(None, &sig, &sig, "second", &tree,
+                                 &[&root]).unwrap();
+        let second = repo.find_commit(second).unwrap();
+        let merge = repo.commit(None, &sig, &sig, "merge", &tree,
+                                &[&second, &side]).unwrap();
+        let merge = repo.find_commit(merge).unwrap();
+
+        assert_eq!(merge.nth_gen_ancestor(0).unwrap().id(), merge.id());
+        assert_eq!(merge.nth_gen_ancestor(1).unwrap().id(), second.id());
+        assert_eq!(merge.nth_gen_ancestor(2).unwrap().id(), root.id());
+        let rev = repo.revparse_single(&format!("{}~2", merge.id())).unwrap();
+        assert_eq!(rev.id(), root.id());
+        match merge.nth_gen_ancestor(3) {
+            Ok(..) => panic!("found an ancestor past the root commit"),
+            Err(e) => assert_eq!(e.code(), ::ErrorCode::NotFound),
+        };
+    }
+
+    #[test]
+    fn ancestors_first_parent() {
+        let (_td, repo) = ::test::repo_init();
+        let sig = repo.signature().unwrap();
+        let root = repo.find_commit(repo.head().unwrap().target().unwrap())
+                       .unwrap();
+        let tree = root.tree().unwrap();
+        let side = repo.commit(None, &sig, &sig, "side", &tree,
+                               &[&root]).unwrap();
+        let side = repo.find_commit(side).unwrap();
+        let second = repo.commit(None, &sig, &sig, "second", &tree,
+                                 &[&root]).unwrap();
+        let second = repo.find_commit(second).unwrap();
+        let merge = repo.commit(None, &sig, &sig, "merge", &tree,
+                                &[&second, &side]).unwrap();
+        let merge = repo.find_commit(merge).unwrap();
+
+        let ids = merge.ancestors_first_parent().map(|c| c.unwrap().id())
+                       .collect::<Vec<_>>();
+        assert_eq!(ids, vec![merge.id(), second.id(), root.id()]);
+        assert_eq!(root.ancestors_first_parent().count(), 1);
+
+        let mut walk = repo.revwalk().unwrap();
+        walk.simplify_first_parent();
+        walk.push(merge.id()).unwrap();
+        assert_eq!(walk.collect::<Result<Vec<_>, _>>().unwrap(), ids);
+    }
 }
 
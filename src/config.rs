@@ -1,5 +1,6 @@
 use std::ffi::CString;
 use std::marker;
+use std::ops::{Deref, DerefMut};
 use std::path::{Path, PathBuf};
 use std::ptr;
 use std::str;
@@ -28,6 +29,16 @@ pub struct ConfigEntries<'cfg> {
     _marker: marker::PhantomData<&'cfg Config>,
 }
 
+/// A lock held on a `Config`, created by `Config::lock`.
+///
+/// Changes made through the transaction, which dereferences to the locked
+/// `Config`, are buffered and only written to disk by `commit`. Dropping the
+/// transaction without committing discards them and releases the lock.
+pub struct ConfigTransaction<'cfg> {
+    raw: *mut raw::git_transaction,
+    config: &'cfg mut Config,
+}
+
 impl Config {
     /// Allocate a new configuration object
     ///
@@ -226,6 +237,10 @@ impl Config {
     }
 
     /// Get the ConfigEntry for a config variable.
+    ///
+    /// The `level` of the entry is the level of the file the value was read
+    /// from. Values pulled in by `[include]` or a matching `[includeIf]`
+    /// section report the level of the file containing that directive.
     pub fn get_entry(&self, name: &str) -> Result<ConfigEntry, Error> {
         let mut ret = ptr::null_mut();
         let name = try!(CString::new(name));
@@ -363,6 +378,19 @@ impl Config {
         }
     }
 
+    /// Lock the configuration file with the highest priority for writing.
+    ///
+    /// Other writers are locked out until the returned transaction is
+    /// committed or dropped, and any changes made through it are written
+    /// atomically on commit.
+    pub fn lock(&mut self) -> Result<ConfigTransaction, Error> {
+        let mut raw = ptr::null_mut();
+        unsafe {
+            try_call!(raw::git_config_lock(&mut raw, self.raw));
+        }
+        Ok(ConfigTransaction { raw: raw, config: self })
+    }
+
     /// Parse a string as a bool.
     /// Interprets "true", "yes", "on", 1, or any non-zero number as true.
     /// Interprets "false", "no", "off", 0, or an empty string as false.
@@ -415,6 +443,29 @@ impl Drop for Config {
     }
 }
 
+impl<'cfg> ConfigTransaction<'cfg> {
+    /// Write the changes made while the lock was held and release it.
+    pub fn commit(self) -> Result<(), Error> {
+        unsafe { try_call!(raw::git_transaction_commit(self.raw)); }
+        Ok(())
+    }
+}
+
+impl<'cfg> Deref for ConfigTransaction<'cfg> {
+    type Target = Config;
+    fn deref(&self) -> &Config { self.config }
+}
+
+impl<'cfg> DerefMut for ConfigTransaction<'cfg> {
+    fn deref_mut(&mut self) -> &mut Config { self.config }
+}
+
+impl<'cfg> Drop for ConfigTransaction<'cfg> {
+    fn drop(&mut self) {
+        unsafe { raw::git_transaction_free(self.raw) }
+    }
+}
+
 impl<'cfg> ConfigEntry<'cfg> {
     /// Gets the name of this entry.
     ///
@@ -505,10 +556,11 @@ impl<'cfg> Drop for ConfigEntry<'cfg> {
 
 #[cfg(test)]
 mod tests {
-    use std::fs::File;
+    use std::fs::{File, OpenOptions};
+    use std::io::prelude::*;
     use tempdir::TempDir;
 
-    use Config;
+    use {Config, ConfigLevel, Repository};
 
     #[test]
     fn smoke() {
@@ -547,6 +599,71 @@ mod tests {
         }
     }
 
+    #[test]
+    fn includes() {
+        let (_td, repo) = ::test::repo_init();
+        let dir = repo.path();
+        File::create(dir.join("extra")).unwrap()
+            .write_all(b"[foo]\n\tplain = included\n").unwrap();
+        File::create(dir.join("cond")).unwrap()
+            .write_all(b"[foo]\n\tcond = matched\n").unwrap();
+        File::create(dir.join("other")).unwrap()
+            .write_all(b"[foo]\n\tother = unexpected\n").unwrap();
+        let mut config = OpenOptions::new().append(true)
+                                           .open(dir.join("config")).unwrap();
+        write!(config, "[include]\n\tpath = extra\n\
+                        [includeIf \"gitdir:{}\"]\n\tpath = cond\n\
+                        [includeIf \"gitdir:/does/not/exist/\"]\n\
+                        \tpath = other\n", dir.display()).unwrap();
+        drop(config);
+
+        let repo = Repository::open(dir).unwrap();
+        let cfg = repo.config().unwrap().snapshot().unwrap();
+        assert_eq!(cfg.get_str("foo.plain").unwrap(), "included");
+        assert_eq!(cfg.get_str("foo.cond").unwrap(), "matched");
+        assert!(cfg.get_str("foo.other").is_err());
+
+        let entry = cfg.get_entry("foo.cond").unwrap();
+        assert_eq!(entry.value(), Some("matched"));
+        assert_eq!(entry.level(), ConfigLevel::Local);
+    }
+
+    #[test]
+    fn lock() {
+        let td = TempDir::new("test").unwrap();
+        let path = td.path().join("foo");
+        File::create(&path).unwrap();
+
+        let mut cfg = Config::open(&path).unwrap();
+        {
+            let mut tx = cfg.lock().unwrap();
+            tx.set_str("user.name", "name").unwrap();
+            tx.set_str("user.email", "email").unwrap();
+
+            let mut other = Config::open(&path).unwrap();
+            let err = other.set_str("foo.bar", "baz").unwrap_err();
+            assert_eq!(err.code(), ::ErrorCode::Locked);
+            let mut other = Config::open(&path).unwrap();
+            assert!(other.snapshot().unwrap().get_str("user.name").is_err());
+
+            tx.commit().unwrap();
+        }
+        let mut other = Config::open(&path).unwrap();
+        let snapshot = other.snapshot().unwrap();
+        assert_eq!(snapshot.get_str("user.name").unwrap(), "name");
+        assert_eq!(snapshot.get_str("user.email").unwrap(), "email");
+
+        // dropping the transaction rolls the changes back
+        {
+            let mut tx = cfg.lock().unwrap();
+            tx.set_str("user.name", "other").unwrap();
+        }
+        let mut other = Config::open(&path).unwrap();
+        assert_eq!(other.snapshot().unwrap().get_str("user.name").unwrap(),
+                   "name");
+        cfg.set_str("foo.bar", "baz").unwrap();
+    }
+
     #[test]
     fn multivar() {
         let td = TempDir::new("test").unwrap();
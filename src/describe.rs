@@ -147,7 +147,10 @@ impl DescribeOptions {
 
     /// If no matching tag or reference is found whether a describe option would
     /// normally fail. This option indicates, however, that it will instead fall
-    /// back to showing the full id of the commit.
+    /// back to showing the abbreviated id of the commit.
+    ///
+    /// This behaves like the `--always` option to git-describe. The length of
+    /// the id is controlled by `DescribeFormatOptions::abbreviated_size`.
     pub fn show_commit_oid_as_fallback(&mut self, show: bool) -> &mut Self {
         self.raw.show_commit_oid_as_fallback = show as c_int;
         self
@@ -175,7 +178,7 @@ impl Binding for DescribeOptions {
 
 #[cfg(test)]
 mod tests {
-    use DescribeOptions;
+    use {DescribeFormatOptions, DescribeOptions, ErrorCode};
 
     #[test]
     fn smoke() {
@@ -196,4 +199,24 @@ mod tests {
         let d = t!(obj.describe(&DescribeOptions::new()));
         assert_eq!(t!(d.format(None)), "foo");
     }
+
+    #[test]
+    fn fallback() {
+        let (_td, repo) = ::test::repo_init();
+        let id = t!(repo.head()).target().unwrap().to_string();
+
+        match repo.describe(&DescribeOptions::new()) {
+            Ok(..) => panic!("described a commit with no tags"),
+            Err(e) => assert_eq!(e.code(), ErrorCode::NotFound),
+        }
+
+        let d = t!(repo.describe(DescribeOptions::new()
+                                        .show_commit_oid_as_fallback(true)));
+        let mut fmt = DescribeFormatOptions::new();
+        assert_eq!(t!(d.format(Some(&fmt))), &id[..7]);
+        fmt.abbreviated_size(12);
+        assert_eq!(t!(d.format(Some(&fmt))), &id[..12]);
+        fmt.dirty_suffix("-dirty");
+        assert_eq!(t!(d.format(Some(&fmt))), &id[..12]);
+    }
 }
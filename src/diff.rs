@@ -116,6 +116,27 @@ struct ForeachCallbacks<'a, 'b: 'a, 'c, 'd: 'c, 'e, 'f: 'e, 'g, 'h: 'g> {
     line: Option<&'g mut LineCb<'h>>,
 }
 
+#[cfg(feature = "libgit2-0-28")]
+impl Diff<'static> {
+    /// Read the contents of a git patch file, such as the output of
+    /// `git diff` or `git format-patch`, into a diff.
+    ///
+    /// Any text before the first `diff --git` header, e.g. the headers and
+    /// message of a mailed patch, is ignored. The resulting diff is not
+    /// associated with any repository, so operations which need to look up
+    /// objects, such as finding renames, are not available.
+    pub fn from_buffer(buffer: &[u8]) -> Result<Diff<'static>, Error> {
+        ::init();
+        let mut diff = ptr::null_mut();
+        unsafe {
+            try_call!(raw::git_diff_from_buffer(&mut diff,
+                                                buffer.as_ptr() as *const c_char,
+                                                buffer.len()));
+            Ok(Diff::from_raw(diff))
+        }
+    }
+}
+
 impl<'repo> Diff<'repo> {
     /// Merge one diff into another.
     ///
@@ -137,6 +158,11 @@ impl<'repo> Diff<'repo> {
     }
 
     /// Return the diff delta for an entry in the diff list.
+    ///
+    /// Deltas are sorted by file path (for a rename, by the old path), so an
+    /// index obtained from a binary search over the paths can be passed here.
+    /// The comparison is case insensitive if `is_sorted_icase` returns true.
+    /// Returns `None` if `i` is out of bounds.
     pub fn get_delta(&self, i: usize) -> Option<DiffDelta> {
         unsafe {
             let ptr = raw::git_diff_get_delta(&*self.raw, i as size_t);
@@ -145,6 +171,9 @@ impl<'repo> Diff<'repo> {
     }
 
     /// Check if deltas are sorted case sensitively or insensitively.
+    ///
+    /// Deltas are sorted case insensitively if the diff was generated with
+    /// `DiffOptions::ignore_case` set.
     pub fn is_sorted_icase(&self) -> bool {
         unsafe { raw::git_diff_is_sorted_icase(&*self.raw) == 1 }
     }
@@ -1155,6 +1184,83 @@ mod tests {
         assert_eq!(stats.files_changed(), 0);
     }
 
+    #[test]
+    #[cfg(feature = "libgit2-0-28")]
+    fn from_buffer() {
+        let patch = b"Subject: [PATCH] ignored\n\
+                      \n\
+                      diff --git a/foo b/foo\n\
+                      new file mode 100644\n\
+                      --- /dev/null\n\
+                      +++ b/foo\n\
+                      @@ -0,0 +1,2 @@\n\
+                      +a\n\
+                      +b\n";
+        let diff = t!(::Diff::from_buffer(patch));
+        assert_eq!(diff.deltas().len(), 1);
+        let delta = diff.get_delta(0).unwrap();
+        assert_eq!(delta.status(), ::Delta::Added);
+        assert_eq!(delta.new_file().path(), Some(Path::new("foo")));
+        let stats = t!(diff.stats());
+        assert_eq!(stats.insertions(), 2);
+
+        assert!(::Diff::from_buffer(b"@@ -1 +1 @@\n-a\n+b\n").is_err());
+    }
+
+    #[test]
+    fn index_to_index() {
+        let (_td, repo) = ::test::repo_init();
+        let a = t!(repo.blob(b"a\n"));
+        let b = t!(repo.blob(b"b\n"));
+        let mut old = t!(::Index::new());
+        t!(old.add(&::IndexEntry::new(a, ::FileMode::Blob, b"foo")));
+        t!(old.add(&::IndexEntry::new(a, ::FileMode::Blob, b"gone")));
+        let mut new = t!(::Index::new());
+        t!(new.add(&::IndexEntry::new(b, ::FileMode::Blob, b"foo")));
+        t!(new.add(&::IndexEntry::new(b, ::FileMode::Blob, b"new")));
+
+        let diff = t!(repo.diff_index_to_index(&old, &new, None));
+        let deltas = diff.deltas().map(|d| {
+            (d.status(), d.new_file().path().unwrap().to_path_buf())
+        }).collect::<Vec<_>>();
+        assert_eq!(deltas, vec![
+            (::Delta::Modified, Path::new("foo").to_path_buf()),
+            (::Delta::Deleted, Path::new("gone").to_path_buf()),
+            (::Delta::Added, Path::new("new").to_path_buf()),
+        ]);
+
+        let diff = t!(repo.diff_index_to_index(&old, &old, None));
+        assert_eq!(diff.deltas().len(), 0);
+    }
+
+    #[test]
+    fn delta_order() {
+        let (td, repo) = ::test::repo_init();
+        for name in &["c", "B", "a", "d"] {
+            t!(t!(File::create(&td.path().join(name))).write_all(b"x"));
+        }
+        let mut opts = DiffOptions::new();
+        opts.include_untracked(true);
+        let diff = t!(repo.diff_tree_to_workdir(None, Some(&mut opts)));
+        assert!(!diff.is_sorted_icase());
+        let paths = diff.deltas().map(|d| {
+            d.new_file().path().unwrap().to_str().unwrap().to_string()
+        }).collect::<Vec<_>>();
+        assert_eq!(paths, ["B", "a", "c", "d"]);
+        let idx = paths.binary_search_by(|p| p[..].cmp("c")).unwrap();
+        let delta = diff.get_delta(idx).unwrap();
+        assert_eq!(delta.new_file().path(), Some(Path::new("c")));
+        assert!(diff.get_delta(paths.len()).is_none());
+
+        opts.ignore_case(true);
+        let diff = t!(repo.diff_tree_to_workdir(None, Some(&mut opts)));
+        assert!(diff.is_sorted_icase());
+        let paths = diff.deltas().map(|d| {
+            d.new_file().path().unwrap().to_str().unwrap().to_string()
+        }).collect::<Vec<_>>();
+        assert_eq!(paths, ["a", "B", "c", "d"]);
+    }
+
     #[test]
     fn foreach_smoke() {
         let (_td, repo) = ::test::repo_init();
@@ -1247,4 +1353,75 @@ mod tests {
         assert_eq!(new_lines, 1);
         assert_eq!(line_content, Some("bar\n".to_string()));
     }
+
+    #[test]
+    fn merge() {
+        let (td, repo) = ::test::repo_init();
+        t!(t!(File::create(&td.path().join("foo"))).write_all(b"foo\n"));
+        t!(t!(File::create(&td.path().join("bar"))).write_all(b"bar\n"));
+        let mut index = t!(repo.index());
+        t!(index.add_path(Path::new("foo")));
+        t!(index.add_path(Path::new("bar")));
+        t!(index.write());
+        t!(t!(File::create(&td.path().join("bar"))).write_all(b"bar\nbaz\n"));
+
+        let head = t!(t!(repo.head()).peel_to_tree());
+        let mut staged = t!(repo.diff_tree_to_index(Some(&head), None, None));
+        let unstaged = t!(repo.diff_index_to_workdir(None, None));
+        assert_eq!(staged.deltas().len(), 2);
+        assert_eq!(unstaged.deltas().len(), 1);
+        assert_eq!(unstaged.deltas().next().unwrap().status(),
+                   ::Delta::Modified);
+
+        t!(staged.merge(&unstaged));
+        assert_eq!(staged.deltas().len(), 2);
+        let paths = staged.deltas().map(|d| {
+            assert_eq!(d.status(), ::Delta::Added);
+            d.new_file().path().unwrap().to_path_buf()
+        }).collect::<Vec<_>>();
+        assert_eq!(paths, [Path::new("bar"), Path::new("foo")]);
+        let stats = t!(staged.stats());
+        assert_eq!(stats.files_changed(), 2);
+        assert_eq!(stats.insertions(), 3);
+    }
+
+    #[test]
+    fn print_line_numbers() {
+        let path = Path::new("foo");
+        let (td, repo) = ::test::repo_init();
+        t!(t!(File::create(&td.path().join(path))).write_all(b"a\nb\nc\n"));
+        let mut index = t!(repo.index());
+        t!(index.add_path(path));
+        t!(index.write());
+        t!(t!(File::create(&td.path().join(path))).write_all(b"a\nB\nc\nd\n"));
+
+        let diff = t!(repo.diff_index_to_workdir(None, None));
+        let mut hunks = Vec::new();
+        let mut lines = Vec::new();
+        t!(diff.print(::DiffFormat::Patch, |_delta, hunk, line| {
+            match line.origin() {
+                'H' => {
+                    let hunk = hunk.unwrap();
+                    hunks.push((hunk.old_start(), hunk.old_lines(),
+                                hunk.new_start(), hunk.new_lines(),
+                                hunk.header().to_vec()));
+                }
+                origin @ ' ' | origin @ '+' | origin @ '-' => {
+                    assert_eq!(line.num_lines(), 1);
+                    lines.push((origin, line.old_lineno(), line.new_lineno(),
+                                line.content().to_vec()));
+                }
+                _ => {}
+            }
+            true
+        }));
+        assert_eq!(hunks, vec![(1, 3, 1, 4, b"@@ -1,3 +1,4 @@\n".to_vec())]);
+        assert_eq!(lines, vec![
+            (' ', Some(1), Some(1), b"a\n".to_vec()),
+            ('-', Some(2), None, b"b\n".to_vec()),
+            ('+', None, Some(2), b"B\n".to_vec()),
+            (' ', Some(3), Some(3), b"c\n".to_vec()),
+            ('+', None, Some(4), b"d\n".to_vec()),
+        ]);
+    }
 }
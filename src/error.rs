@@ -35,6 +35,76 @@ impl Error {
         Error { code: code, klass: (*ptr).klass, message: msg.to_string() }
     }
 
+    /// Creates a new error with the given code, class and message.
+    ///
+    /// This is mostly useful when implementing an extension point such as an
+    /// `OdbBackend`, where libgit2 acts on the code of the error returned,
+    /// e.g. moving on to the next backend for `NotFound`.
+    pub fn new(code: ErrorCode, class: ErrorClass, message: &str) -> Error {
+        let code = match code {
+            ErrorCode::GenericError => raw::GIT_ERROR,
+            ErrorCode::NotFound => raw::GIT_ENOTFOUND,
+            ErrorCode::Exists => raw::GIT_EEXISTS,
+            ErrorCode::Ambiguous => raw::GIT_EAMBIGUOUS,
+            ErrorCode::BufSize => raw::GIT_EBUFS,
+            ErrorCode::User => raw::GIT_EUSER,
+            ErrorCode::BareRepo => raw::GIT_EBAREREPO,
+            ErrorCode::UnbornBranch => raw::GIT_EUNBORNBRANCH,
+            ErrorCode::Unmerged => raw::GIT_EUNMERGED,
+            ErrorCode::NotFastForward => raw::GIT_ENONFASTFORWARD,
+            ErrorCode::InvalidSpec => raw::GIT_EINVALIDSPEC,
+            ErrorCode::Conflict => raw::GIT_ECONFLICT,
+            ErrorCode::Locked => raw::GIT_ELOCKED,
+            ErrorCode::Modified => raw::GIT_EMODIFIED,
+            ErrorCode::Auth => raw::GIT_EAUTH,
+            ErrorCode::Certificate => raw::GIT_ECERTIFICATE,
+            ErrorCode::Applied => raw::GIT_EAPPLIED,
+            ErrorCode::Peel => raw::GIT_EPEEL,
+            ErrorCode::Eof => raw::GIT_EEOF,
+            ErrorCode::Invalid => raw::GIT_EINVALID,
+            ErrorCode::Uncommitted => raw::GIT_EUNCOMMITTED,
+            ErrorCode::Directory => raw::GIT_EDIRECTORY,
+        };
+        let klass = match class {
+            ErrorClass::None => raw::GITERR_NONE,
+            ErrorClass::NoMemory => raw::GITERR_NOMEMORY,
+            ErrorClass::Os => raw::GITERR_OS,
+            ErrorClass::Invalid => raw::GITERR_INVALID,
+            ErrorClass::Reference => raw::GITERR_REFERENCE,
+            ErrorClass::Zlib => raw::GITERR_ZLIB,
+            ErrorClass::Repository => raw::GITERR_REPOSITORY,
+            ErrorClass::Config => raw::GITERR_CONFIG,
+            ErrorClass::Regex => raw::GITERR_REGEX,
+            ErrorClass::Odb => raw::GITERR_ODB,
+            ErrorClass::Index => raw::GITERR_INDEX,
+            ErrorClass::Object => raw::GITERR_OBJECT,
+            ErrorClass::Net => raw::GITERR_NET,
+            ErrorClass::Tag => raw::GITERR_TAG,
+            ErrorClass::Tree => raw::GITERR_TREE,
+            ErrorClass::Indexer => raw::GITERR_INDEXER,
+            ErrorClass::Ssl => raw::GITERR_SSL,
+            ErrorClass::Submodule => raw::GITERR_SUBMODULE,
+            ErrorClass::Thread => raw::GITERR_THREAD,
+            ErrorClass::Stash => raw::GITERR_STASH,
+            ErrorClass::Checkout => raw::GITERR_CHECKOUT,
+            ErrorClass::FetchHead => raw::GITERR_FETCHHEAD,
+            ErrorClass::Merge => raw::GITERR_MERGE,
+            ErrorClass::Ssh => raw::GITERR_SSH,
+            ErrorClass::Filter => raw::GITERR_FILTER,
+            ErrorClass::Revert => raw::GITERR_REVERT,
+            ErrorClass::Callback => raw::GITERR_CALLBACK,
+            ErrorClass::CherryPick => raw::GITERR_CHERRYPICK,
+            ErrorClass::Describe => raw::GITERR_DESCRIBE,
+            ErrorClass::Rebase => raw::GITERR_REBASE,
+            ErrorClass::Filesystem => raw::GITERR_FILESYSTEM,
+        };
+        Error {
+            code: code as c_int,
+            klass: klass as c_int,
+            message: message.to_string(),
+        }
+    }
+
     /// Creates a new error from the given string as the error.
     pub fn from_str(s: &str) -> Error {
         Error {
@@ -197,6 +267,16 @@ impl Error {
     pub fn message(&self) -> &str { &self.message }
 }
 
+/// Creates an error for a negative return code from libgit2 for which no error
+/// message was set, preserving the code.
+pub fn unknown(code: c_int) -> Error {
+    Error {
+        code: code,
+        klass: raw::GITERR_NONE as c_int,
+        message: "an unknown error occurred".to_string(),
+    }
+}
+
 impl error::Error for Error {
     fn description(&self) -> &str { &self.message }
 }
@@ -234,4 +314,12 @@ mod tests {
         assert_eq!(err.code(), ErrorCode::NotFound);
         assert_eq!(err.class(), ErrorClass::Submodule);
     }
+
+    #[test]
+    fn new() {
+        let err = ::Error::new(ErrorCode::NotFound, ErrorClass::Odb, "missing");
+        assert_eq!(err.code(), ErrorCode::NotFound);
+        assert_eq!(err.class(), ErrorClass::Odb);
+        assert_eq!(err.message(), "missing");
+    }
 }
@@ -0,0 +1,109 @@
+use std::ffi::CString;
+use std::marker;
+
+use libc::{c_char, size_t};
+
+use {raw, Blob, Buf, Error, Repository};
+use util::Binding;
+
+/// A list of filters, such as CRLF conversion or smudge/clean drivers, to be
+/// applied to some content.
+///
+/// This structure corresponds to a `git_filter_list` in libgit2 and is created
+/// with `Repository::filter_list_load`.
+pub struct FilterList<'repo> {
+    raw: *mut raw::git_filter_list,
+    _marker: marker::PhantomData<&'repo Repository>,
+}
+
+impl<'repo> FilterList<'repo> {
+    /// Check whether this list contains the filter named `name`, e.g. `crlf`
+    /// or `ident`.
+    ///
+    /// A name containing a nul byte cannot name any filter, so it is never
+    /// contained.
+    pub fn contains(&self, name: &str) -> bool {
+        let name = match CString::new(name) {
+            Ok(name) => name,
+            Err(..) => return false,
+        };
+        unsafe { raw::git_filter_list_contains(self.raw, name.as_ptr()) != 0 }
+    }
+
+    /// Apply the filters in this list to a buffer of data.
+    pub fn apply_to_buffer(&self, data: &[u8]) -> Result<Buf, Error> {
+        let mut input = raw::git_buf {
+            ptr: data.as_ptr() as *mut c_char,
+            asize: 0,
+            size: data.len() as size_t,
+        };
+        let buf = Buf::new();
+        unsafe {
+            try_call!(raw::git_filter_list_apply_to_data(buf.raw(), self.raw,
+                                                         &mut input));
+        }
+        Ok(buf)
+    }
+
+    /// Apply the filters in this list to the contents of a blob.
+    pub fn apply_to_blob(&self, blob: &Blob) -> Result<Buf, Error> {
+        let buf = Buf::new();
+        unsafe {
+            try_call!(raw::git_filter_list_apply_to_blob(buf.raw(), self.raw,
+                                                         blob.raw()));
+        }
+        Ok(buf)
+    }
+}
+
+impl<'repo> Binding for FilterList<'repo> {
+    type Raw = *mut raw::git_filter_list;
+
+    unsafe fn from_raw(raw: *mut raw::git_filter_list) -> FilterList<'repo> {
+        FilterList { raw: raw, _marker: marker::PhantomData }
+    }
+    fn raw(&self) -> *mut raw::git_filter_list { self.raw }
+}
+
+impl<'repo> Drop for FilterList<'repo> {
+    fn drop(&mut self) {
+        unsafe { raw::git_filter_list_free(self.raw) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use std::path::Path;
+
+    use {FilterMode, FILTER_DEFAULT};
+
+    #[test]
+    fn smoke() {
+        let (td, repo) = ::test::repo_init();
+        File::create(td.path().join(".gitattributes")).unwrap()
+            .write_all(b"*.txt text eol=crlf\n").unwrap();
+
+        let filters = repo.filter_list_load(None, Path::new("foo.txt"),
+                                            FilterMode::ToWorktree,
+                                            FILTER_DEFAULT).unwrap().unwrap();
+        assert!(filters.contains("crlf"));
+        assert!(!filters.contains("ident"));
+        assert!(!filters.contains("cr\0lf"));
+        let buf = filters.apply_to_buffer(b"a\nb\n").unwrap();
+        assert_eq!(&*buf, b"a\r\nb\r\n");
+        let blob = repo.find_blob(repo.blob(b"c\n").unwrap()).unwrap();
+        assert_eq!(&*filters.apply_to_blob(&blob).unwrap(), b"c\r\n");
+
+        let filters = repo.filter_list_load(None, Path::new("foo.txt"),
+                                            FilterMode::ToOdb,
+                                            FILTER_DEFAULT).unwrap().unwrap();
+        let buf = filters.apply_to_buffer(b"a\r\nb\r\n").unwrap();
+        assert_eq!(&*buf, b"a\nb\n");
+
+        assert!(repo.filter_list_load(None, Path::new("foo.bin"),
+                                      FilterMode::ToWorktree,
+                                      FILTER_DEFAULT).unwrap().is_none());
+    }
+}
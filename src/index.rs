@@ -6,7 +6,8 @@ use std::slice;
 
 use libc::{c_int, c_uint, size_t, c_void, c_char};
 
-use {raw, panic, Repository, Error, Tree, Oid, IndexAddOption, IndexTime};
+use {raw, panic, Repository, Error, FileMode, Tree, Oid, IndexAddOption, IndexCaps};
+use IndexTime;
 use IntoCString;
 use util::{self, Binding};
 
@@ -32,8 +33,8 @@ pub type IndexMatchedPath<'a> = FnMut(&Path, &[u8]) -> i32 + 'a;
 
 /// A structure to represent an entry or a file inside of an index.
 ///
-/// All fields of an entry are public for modification and inspection. This is
-/// also how a new index entry is created.
+/// All fields of an entry are public for modification and inspection. A new
+/// entry may be created with `IndexEntry::new` or by filling in every field.
 #[allow(missing_docs)]
 pub struct IndexEntry {
     pub ctime: IndexTime,
@@ -50,6 +51,34 @@ pub struct IndexEntry {
     pub path: Vec<u8>,
 }
 
+impl IndexEntry {
+    /// Create a new entry for the object `id` at `path` with the given mode.
+    ///
+    /// All other fields are zeroed and may be filled in afterwards, for example
+    /// `file_size` or the stage bits of `flags`.
+    pub fn new(id: Oid, mode: FileMode, path: &[u8]) -> IndexEntry {
+        IndexEntry {
+            ctime: IndexTime::new(0, 0),
+            mtime: IndexTime::new(0, 0),
+            dev: 0,
+            ino: 0,
+            mode: mode.raw(),
+            uid: 0,
+            gid: 0,
+            file_size: 0,
+            id: id,
+            flags: 0,
+            flags_extended: 0,
+            path: path.to_vec(),
+        }
+    }
+
+    /// Get the mode of this entry, if it is one of the standard git modes.
+    pub fn file_mode(&self) -> Option<FileMode> {
+        FileMode::from_raw(self.mode)
+    }
+}
+
 impl Index {
     /// Creates a new in-memory index.
     ///
@@ -89,39 +118,8 @@ impl Index {
     /// will be added.
     pub fn add(&mut self, entry: &IndexEntry) -> Result<(), Error> {
         let path = try!(CString::new(&entry.path[..]));
-
-        // libgit2 encodes the length of the path in the lower bits of the
-        // `flags` entry, so mask those out and recalculate here to ensure we
-        // don't corrupt anything.
-        let mut flags = entry.flags & !raw::GIT_IDXENTRY_NAMEMASK;
-
-        if entry.path.len() < raw::GIT_IDXENTRY_NAMEMASK as usize {
-            flags |= entry.path.len() as u16;
-        } else {
-            flags |= raw::GIT_IDXENTRY_NAMEMASK;
-        }
-
+        let raw = raw_entry(entry, &path);
         unsafe {
-            let raw = raw::git_index_entry {
-                dev: entry.dev,
-                ino: entry.ino,
-                mode: entry.mode,
-                uid: entry.uid,
-                gid: entry.gid,
-                file_size: entry.file_size,
-                id: *entry.id.raw(),
-                flags: flags,
-                flags_extended: entry.flags_extended,
-                path: path.as_ptr(),
-                mtime: raw::git_index_time {
-                    seconds: entry.mtime.seconds(),
-                    nanoseconds: entry.mtime.nanoseconds(),
-                },
-                ctime: raw::git_index_time {
-                    seconds: entry.ctime.seconds(),
-                    nanoseconds: entry.ctime.nanoseconds(),
-                },
-            };
             try_call!(raw::git_index_add(self.raw, &raw));
             Ok(())
         }
@@ -244,6 +242,10 @@ impl Index {
     }
 
     /// Get one of the entries in the index by its path.
+    ///
+    /// The `stage` selects which version of a conflicted path to look up:
+    /// `0` for a normal, resolved entry, or `1`, `2` and `3` for the common
+    /// ancestor, "ours" and "theirs" sides of a conflict respectively.
     pub fn get_path(&self, path: &Path, stage: i32) -> Option<IndexEntry> {
         let path = path.into_c_string().unwrap();
         unsafe {
@@ -262,6 +264,23 @@ impl Index {
         }
     }
 
+    /// Get the capabilities of this index.
+    pub fn caps(&self) -> IndexCaps {
+        let caps = unsafe { raw::git_index_caps(&*self.raw) };
+        IndexCaps::from_bits_truncate(caps as u32)
+    }
+
+    /// Set the capabilities of this index.
+    ///
+    /// `INDEXCAP_FROM_OWNER` resets the capabilities to those implied by the
+    /// configuration of the owning repository.
+    pub fn set_caps(&mut self, caps: IndexCaps) -> Result<(), Error> {
+        unsafe {
+            try_call!(raw::git_index_set_caps(self.raw, caps.bits() as c_int));
+        }
+        Ok(())
+    }
+
     /// Get the full path to the index file on disk.
     ///
     /// Returns `None` if this is an in-memory index.
@@ -289,7 +308,9 @@ impl Index {
 
     /// Read a tree into the index file with stats
     ///
-    /// The current index contents will be replaced by the specified tree.
+    /// The current index contents will be replaced by the specified tree. This
+    /// is the inverse of `write_tree`, and only changes the in-memory index;
+    /// use `write` to save the result to disk.
     pub fn read_tree(&mut self, tree: &Tree) -> Result<(), Error> {
         unsafe { try_call!(raw::git_index_read_tree(self.raw, &*tree.raw())); }
         Ok(())
@@ -394,6 +415,8 @@ impl Index {
 
     /// Write an existing index object from memory back to disk using an atomic
     /// file lock.
+    ///
+    /// This fails for an in-memory index, as it has no file to write to.
     pub fn write(&mut self) -> Result<(), Error> {
         unsafe { try_call!(raw::git_index_write(self.raw)); }
         Ok(())
@@ -421,7 +444,8 @@ impl Index {
     /// Write the index as a tree to the given repository
     ///
     /// This is the same as `write_tree` except that the destination repository
-    /// can be chosen.
+    /// can be chosen. Only tree objects are written to `repo`; the blobs the
+    /// index refers to are expected to already exist there.
     pub fn write_tree_to(&mut self, repo: &Repository) -> Result<Oid, Error> {
         let mut raw = raw::git_oid { id: [0; raw::GIT_OID_RAWSZ] };
         unsafe {
@@ -467,6 +491,42 @@ impl<'index> Iterator for IndexEntries<'index> {
     }
 }
 
+/// Convert `entry` to a `git_index_entry` which borrows `path`, the entry's
+/// path as a C string.
+pub fn raw_entry(entry: &IndexEntry, path: &CString) -> raw::git_index_entry {
+    // libgit2 encodes the length of the path in the lower bits of the
+    // `flags` entry, so mask those out and recalculate here to ensure we
+    // don't corrupt anything.
+    let mut flags = entry.flags & !raw::GIT_IDXENTRY_NAMEMASK;
+
+    if entry.path.len() < raw::GIT_IDXENTRY_NAMEMASK as usize {
+        flags |= entry.path.len() as u16;
+    } else {
+        flags |= raw::GIT_IDXENTRY_NAMEMASK;
+    }
+
+    raw::git_index_entry {
+        dev: entry.dev,
+        ino: entry.ino,
+        mode: entry.mode,
+        uid: entry.uid,
+        gid: entry.gid,
+        file_size: entry.file_size,
+        id: unsafe { *entry.id.raw() },
+        flags: flags,
+        flags_extended: entry.flags_extended,
+        path: path.as_ptr(),
+        mtime: raw::git_index_time {
+            seconds: entry.mtime.seconds(),
+            nanoseconds: entry.mtime.nanoseconds(),
+        },
+        ctime: raw::git_index_time {
+            seconds: entry.ctime.seconds(),
+            nanoseconds: entry.ctime.nanoseconds(),
+        },
+    }
+}
+
 impl Binding for IndexEntry {
     type Raw = raw::git_index_entry;
 
@@ -514,7 +574,7 @@ mod tests {
     use std::path::Path;
     use tempdir::TempDir;
 
-    use {Index, IndexEntry, Repository, ResetType, Oid, IndexTime};
+    use {FileMode, Index, IndexEntry, Repository, ResetType, Oid, IndexTime};
 
     #[test]
     fn smoke() {
@@ -525,6 +585,26 @@ mod tests {
         assert!(index.get(0).is_none());
         assert!(index.path().is_none());
         assert!(index.read(true).is_err());
+        assert!(index.write().is_err());
+    }
+
+    #[test]
+    fn caps() {
+        let mut index = Index::new().unwrap();
+        index.set_caps(::INDEXCAP_IGNORE_CASE | ::INDEXCAP_NO_SYMLINKS).unwrap();
+        assert_eq!(index.caps(), ::INDEXCAP_IGNORE_CASE | ::INDEXCAP_NO_SYMLINKS);
+
+        let mut e = entry();
+        e.path = b"Foo".to_vec();
+        index.add(&e).unwrap();
+        assert!(index.get_path(Path::new("foo"), 0).is_some());
+
+        let (_td, repo) = ::test::repo_init();
+        let mut index = repo.index().unwrap();
+        index.set_caps(::INDEXCAP_NO_FILEMODE).unwrap();
+        assert!(index.caps().contains(::INDEXCAP_NO_FILEMODE));
+        index.set_caps(::INDEXCAP_FROM_OWNER).unwrap();
+        assert!(!index.caps().contains(::INDEXCAP_IGNORE_CASE));
     }
 
     #[test]
@@ -615,6 +695,89 @@ mod tests {
         assert_eq!(e.path.len(), 6);
     }
 
+    #[test]
+    fn stages() {
+        let mut index = Index::new().unwrap();
+        for stage in 1..4 {
+            let mut e = entry();
+            e.path = b"foo".to_vec();
+            e.id = Oid::from_bytes(&[stage as u8; 20]).unwrap();
+            e.flags = stage << ::raw::GIT_IDXENTRY_STAGESHIFT;
+            index.add(&e).unwrap();
+        }
+        assert!(index.has_conflicts());
+        assert!(index.get_path(Path::new("foo"), 0).is_none());
+        let ours = index.get_path(Path::new("foo"), 2).unwrap();
+        assert_eq!(ours.id, Oid::from_bytes(&[2; 20]).unwrap());
+        let theirs = index.get_path(Path::new("foo"), 3).unwrap();
+        assert_eq!(theirs.id, Oid::from_bytes(&[3; 20]).unwrap());
+
+        // resolve the conflict in favor of "theirs"
+        for stage in 1..4 {
+            index.remove(Path::new("foo"), stage).unwrap();
+        }
+        let mut resolved = theirs;
+        resolved.flags &= !::raw::GIT_IDXENTRY_STAGEMASK;
+        index.add(&resolved).unwrap();
+        assert!(!index.has_conflicts());
+        assert_eq!(index.len(), 1);
+        let e = index.get_path(Path::new("foo"), 0).unwrap();
+        assert_eq!(e.id, Oid::from_bytes(&[3; 20]).unwrap());
+    }
+
+    #[test]
+    fn add_with_mode() {
+        let (_td, repo) = ::test::repo_init();
+        let id = repo.blob(b"#!/bin/sh\n").unwrap();
+        let mut index = Index::new().unwrap();
+        let mut e = IndexEntry::new(id, FileMode::BlobExecutable, b"run.sh");
+        e.file_size = 10;
+        index.add(&e).unwrap();
+        index.add(&IndexEntry::new(id, FileMode::Blob, b"plain")).unwrap();
+        index.add(&IndexEntry::new(id, FileMode::Link, b"link")).unwrap();
+
+        let e = index.get_path(Path::new("run.sh"), 0).unwrap();
+        assert_eq!(e.file_mode(), Some(FileMode::BlobExecutable));
+        assert_eq!(e.mode, 0o100755);
+        assert_eq!(e.file_size, 10);
+
+        let tree = index.write_tree_to(&repo).unwrap();
+        let tree = repo.find_tree(tree).unwrap();
+        let mode = |name| tree.get_name(name).unwrap().filemode();
+        assert_eq!(mode("run.sh"), FileMode::BlobExecutable.into());
+        assert_eq!(mode("plain"), i32::from(FileMode::Blob));
+        assert_eq!(mode("link"), i32::from(FileMode::Link));
+        assert_eq!(FileMode::from_raw(0o100644), Some(FileMode::Blob));
+        assert_eq!(FileMode::from_raw(0o100664), None);
+    }
+
+    #[test]
+    fn read_tree() {
+        let (_td, repo) = ::test::repo_init();
+        let a = repo.blob(b"a").unwrap();
+        let b = repo.blob(b"b").unwrap();
+        let mut built = Index::new().unwrap();
+        built.add(&IndexEntry::new(a, FileMode::Blob, b"a")).unwrap();
+        built.add(&IndexEntry::new(b, FileMode::Blob, b"dir/b")).unwrap();
+        let tree = built.write_tree_to(&repo).unwrap();
+        let tree = repo.find_tree(tree).unwrap();
+
+        let mut index = repo.index().unwrap();
+        index.add(&IndexEntry::new(a, FileMode::Blob, b"staged")).unwrap();
+        index.read_tree(&tree).unwrap();
+        let paths = index.iter().map(|e| e.path).collect::<Vec<_>>();
+        assert_eq!(paths, [b"a".to_vec(), b"dir/b".to_vec()]);
+        assert_eq!(index.write_tree().unwrap(), tree.id());
+
+        let td = TempDir::new("test").unwrap();
+        let other = Repository::init(td.path()).unwrap();
+        other.blob(b"a").unwrap();
+        other.blob(b"b").unwrap();
+        assert!(other.find_tree(tree.id()).is_err());
+        assert_eq!(index.write_tree_to(&other).unwrap(), tree.id());
+        assert_eq!(other.find_tree(tree.id()).unwrap().len(), 2);
+    }
+
     fn entry() -> IndexEntry {
         IndexEntry {
             ctime: IndexTime::new(0, 0),
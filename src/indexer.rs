@@ -0,0 +1,150 @@
+use std::marker;
+use std::mem;
+use std::path::Path;
+use std::ptr;
+use libc::{c_int, c_uint, c_void};
+
+use {raw, panic, Error, IntoCString, Odb, Oid, Progress, TransferProgress};
+use util::Binding;
+
+/// A structure to index a packfile received in chunks, for example over a
+/// custom transport.
+///
+/// Data is fed to the indexer with `append`, and once the whole pack has been
+/// received `commit` writes the pack and its `.idx` file to disk.
+pub struct Indexer<'odb> {
+    raw: *mut raw::git_indexer,
+    stats: raw::git_transfer_progress,
+    progress: Box<Option<Box<TransferProgress<'odb>>>>,
+    _marker: marker::PhantomData<&'odb Odb<'odb>>,
+}
+
+impl<'odb> Indexer<'odb> {
+    /// Create a new indexer writing its pack and index to the directory
+    /// `path`.
+    ///
+    /// The `mode` is the permissions to give the created files, where `0`
+    /// selects the default. The `odb` is used to resolve bases of thin packs
+    /// and may be `None` if the pack is known to be self-contained.
+    pub fn new(path: &Path, mode: u32, odb: Option<&'odb Odb<'odb>>)
+               -> Result<Indexer<'odb>, Error> {
+        ::init();
+        let path = try!(path.into_c_string());
+        let mut progress = Box::new(None);
+        let cb = Some(progress_cb as raw::git_transfer_progress_cb);
+        let payload = &mut *progress as *mut _ as *mut c_void;
+        let odb = odb.map(|o| o.raw()).unwrap_or(ptr::null_mut());
+        let mut ret = ptr::null_mut();
+        unsafe {
+            try_call!(raw::git_indexer_new(&mut ret, path, mode as c_uint,
+                                           odb, cb, payload));
+            Ok(Indexer {
+                raw: ret,
+                stats: mem::zeroed(),
+                progress: progress,
+                _marker: marker::PhantomData,
+            })
+        }
+    }
+
+    /// Set the callback through which indexing progress is reported.
+    ///
+    /// The return value indicates whether indexing should continue. A return
+    /// value of `false` will abort the current `append` or `commit`.
+    pub fn progress<F>(&mut self, cb: F) -> &mut Indexer<'odb>
+                       where F: FnMut(Progress) -> bool + 'odb {
+        *self.progress = Some(Box::new(cb) as Box<TransferProgress<'odb>>);
+        self
+    }
+
+    /// Add a chunk of packfile data to the indexer.
+    ///
+    /// The pack may be split into chunks of any size.
+    pub fn append(&mut self, data: &[u8]) -> Result<(), Error> {
+        unsafe {
+            try_call!(raw::git_indexer_append(self.raw,
+                                              data.as_ptr() as *const c_void,
+                                              data.len(),
+                                              &mut self.stats));
+        }
+        Ok(())
+    }
+
+    /// Finalize the pack and index once all of its data has been appended,
+    /// returning the hash of the packfile.
+    ///
+    /// The hash is also the name of the pack, i.e. the pack is written to
+    /// `pack-<hash>.pack`.
+    pub fn commit(&mut self) -> Result<Oid, Error> {
+        unsafe {
+            try_call!(raw::git_indexer_commit(self.raw, &mut self.stats));
+            Ok(Binding::from_raw(raw::git_indexer_hash(self.raw)))
+        }
+    }
+
+    /// Get the statistics of the data indexed so far.
+    pub fn stats(&self) -> Progress {
+        unsafe { Binding::from_raw(&self.stats as *const _) }
+    }
+}
+
+impl<'odb> Drop for Indexer<'odb> {
+    fn drop(&mut self) {
+        unsafe { raw::git_indexer_free(self.raw) }
+    }
+}
+
+extern fn progress_cb(stats: *const raw::git_transfer_progress,
+                      data: *mut c_void) -> c_int {
+    let ok = panic::wrap(|| unsafe {
+        let data = &mut *(data as *mut Option<Box<TransferProgress>>);
+        let callback = match *data {
+            Some(ref mut c) => c,
+            None => return true,
+        };
+        callback(Binding::from_raw(stats))
+    });
+    if ok == Some(true) {0} else {-1}
+}
+
+#[cfg(test)]
+mod tests {
+    use tempdir::TempDir;
+    use {Buf, Indexer, Repository};
+
+    #[test]
+    fn smoke() {
+        let (_td, repo) = ::test::repo_init();
+        let head = repo.head().unwrap().target().unwrap();
+        let mut builder = repo.packbuilder().unwrap();
+        builder.insert_commit(head).unwrap();
+        let mut pack = Buf::new();
+        builder.write_buf(&mut pack).unwrap();
+
+        let td = TempDir::new("test").unwrap();
+        let other = Repository::init(td.path()).unwrap();
+        let pack_dir = other.path().join("objects/pack");
+        let mut progress_called = false;
+        let id = {
+            let odb = other.odb().unwrap();
+            let mut indexer = Indexer::new(&pack_dir, 0, Some(&odb)).unwrap();
+            indexer.progress(|_| {
+                progress_called = true;
+                true
+            });
+            let mid = pack.len() / 2;
+            indexer.append(&pack[..mid]).unwrap();
+            indexer.append(&pack[mid..]).unwrap();
+            let id = indexer.commit().unwrap();
+            assert_eq!(indexer.stats().total_objects(), 2);
+            assert_eq!(indexer.stats().indexed_objects(), 2);
+            id
+        };
+        assert!(progress_called);
+        assert_eq!(Some(id), builder.hash());
+        assert!(pack_dir.join(format!("pack-{}.idx", id)).exists());
+
+        let other = Repository::open(td.path()).unwrap();
+        other.find_commit(head).unwrap();
+    }
+}
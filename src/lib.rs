@@ -83,16 +83,21 @@ pub use blame::{Blame, BlameHunk, BlameIter, BlameOptions};
 pub use blob::{Blob, BlobWriter};
 pub use branch::{Branch, Branches};
 pub use buf::Buf;
-pub use commit::{Commit, Parents};
-pub use config::{Config, ConfigEntry, ConfigEntries};
+pub use commit::{Commit, FirstParentAncestors, Parents};
+pub use config::{Config, ConfigEntry, ConfigEntries, ConfigTransaction};
 pub use cred::{Cred, CredentialHelper};
 pub use describe::{Describe, DescribeFormatOptions, DescribeOptions};
 pub use diff::{Diff, DiffDelta, DiffFile, DiffOptions, Deltas};
 pub use diff::{DiffBinary, DiffBinaryFile, DiffBinaryKind};
 pub use diff::{DiffLine, DiffHunk, DiffStats, DiffFindOptions};
 pub use error::Error;
+pub use filter::FilterList;
 pub use index::{Index, IndexEntry, IndexEntries, IndexMatchedPath};
+pub use indexer::Indexer;
+#[cfg(feature = "libgit2-0-28")]
+pub use mailmap::Mailmap;
 pub use merge::{AnnotatedCommit, MergeOptions};
+pub use merge::{merge_file, MergeFileOptions, MergeFileResult};
 pub use message::{message_prettify, DEFAULT_COMMENT_CHAR};
 pub use note::{Note, Notes};
 pub use object::Object;
@@ -102,24 +107,32 @@ pub use pathspec::{Pathspec, PathspecMatchList, PathspecFailedEntries};
 pub use pathspec::{PathspecDiffEntries, PathspecEntries};
 pub use patch::Patch;
 pub use proxy_options::ProxyOptions;
+pub use rebase::{Rebase, RebaseOperation, RebaseOperationType, RebaseOptions};
+pub use refdb::{RefdbBackend, ReferenceTarget};
 pub use reference::{Reference, References, ReferenceNames};
 pub use reflog::{Reflog, ReflogEntry, ReflogIter};
 pub use refspec::Refspec;
 pub use remote::{Remote, RemoteConnection, Refspecs, RemoteHead, FetchOptions, PushOptions};
 pub use remote_callbacks::{RemoteCallbacks, Credentials, TransferProgress};
 pub use remote_callbacks::{TransportMessage, Progress, UpdateTips};
+pub use remote_callbacks::CertificateCheckStatus;
 pub use repo::{Repository, RepositoryInitOptions};
 pub use revspec::Revspec;
-pub use revwalk::Revwalk;
+pub use revwalk::{Revwalk, Commits};
 pub use signature::Signature;
 pub use status::{StatusOptions, Statuses, StatusIter, StatusEntry, StatusShow};
 pub use stash::{StashApplyOptions, StashCb, StashApplyProgressCb};
 pub use submodule::{Submodule, SubmoduleUpdateOptions};
 pub use tag::Tag;
 pub use time::{Time, IndexTime};
-pub use tree::{Tree, TreeEntry, TreeIter};
+pub use transaction::Transaction;
+pub use tree::{Tree, TreeEntry, TreeIter, TreeWalkMode, TreeWalkResult};
 pub use treebuilder::TreeBuilder;
-pub use odb::{Odb, OdbReader, OdbWriter};
+pub use odb::{Odb, OdbBackend, OdbReader, OdbWriter};
+#[cfg(feature = "libgit2-0-28")]
+pub use worktree::{Worktree, WorktreeAddOptions, WorktreeLockStatus};
+#[cfg(feature = "libgit2-0-28")]
+pub use worktree::WorktreePruneOptions;
 pub use util::IntoCString;
 
 /// An enumeration of possible errors that can happen when working with a git
@@ -258,6 +271,33 @@ pub enum RepositoryState {
     ApplyMailboxOrRebase,
 }
 
+impl RepositoryState {
+    /// Returns whether an operation such as a merge or rebase is in progress,
+    /// i.e. whether this state is anything other than `Clean`.
+    pub fn is_in_progress(&self) -> bool {
+        *self != RepositoryState::Clean
+    }
+
+    /// Returns the name of the operation in progress as git would print it,
+    /// e.g. `"rebase -i"`, or `None` for `Clean`.
+    pub fn operation_name(&self) -> Option<&'static str> {
+        match *self {
+            RepositoryState::Clean => None,
+            RepositoryState::Merge => Some("merge"),
+            RepositoryState::Revert |
+            RepositoryState::RevertSequence => Some("revert"),
+            RepositoryState::CherryPick |
+            RepositoryState::CherryPickSequence => Some("cherry-pick"),
+            RepositoryState::Bisect => Some("bisect"),
+            RepositoryState::Rebase => Some("rebase"),
+            RepositoryState::RebaseInteractive => Some("rebase -i"),
+            RepositoryState::RebaseMerge => Some("rebase -m"),
+            RepositoryState::ApplyMailbox => Some("am"),
+            RepositoryState::ApplyMailboxOrRebase => Some("am/rebase"),
+        }
+    }
+}
+
 /// An enumeration of the possible directions for a remote.
 #[derive(Copy, Clone)]
 pub enum Direction {
@@ -267,6 +307,17 @@ pub enum Direction {
     Push,
 }
 
+/// The direction in which content is filtered by a `FilterList`.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum FilterMode {
+    /// Filter content from the object database for the working directory,
+    /// also known as "smudge".
+    ToWorktree,
+    /// Filter content from the working directory for the object database,
+    /// also known as "clean".
+    ToOdb,
+}
+
 /// An enumeration of the operations that can be performed for the `reset`
 /// method on a `Repository`.
 #[derive(Copy, Clone)]
@@ -347,6 +398,23 @@ pub enum FileFavor {
     Union,
 }
 
+/// Valid modes for index and tree entries.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub enum FileMode {
+    /// Unreadable
+    Unreadable,
+    /// Tree
+    Tree,
+    /// Blob
+    Blob,
+    /// Blob executable
+    BlobExecutable,
+    /// Link
+    Link,
+    /// Commit
+    Commit,
+}
+
 bitflags! {
     /// Orderings that may be specified for Revwalk iteration.
     pub struct Sort: u32 {
@@ -439,6 +507,27 @@ bitflags! {
     }
 }
 
+bitflags! {
+    /// Options for `Reference::normalize_name`
+    pub struct ReferenceFormat: u32 {
+        /// No particular normalization.
+        const REFERENCE_FORMAT_NORMAL =
+                raw::GIT_REFERENCE_FORMAT_NORMAL as u32;
+        /// Allow names containing a single component, such as `HEAD` or
+        /// `foo`, rather than requiring e.g. `refs/foo`.
+        const REFERENCE_FORMAT_ALLOW_ONELEVEL =
+                raw::GIT_REFERENCE_FORMAT_ALLOW_ONELEVEL as u32;
+        /// Allow the name to contain a single `*` as part of a refspec
+        /// pattern, such as `refs/heads/*`.
+        const REFERENCE_FORMAT_REFSPEC_PATTERN =
+                raw::GIT_REFERENCE_FORMAT_REFSPEC_PATTERN as u32;
+        /// Interpret the name as part of a refspec in shorthand form, so that
+        /// names like `master` are accepted.
+        const REFERENCE_FORMAT_REFSPEC_SHORTHAND =
+                raw::GIT_REFERENCE_FORMAT_REFSPEC_SHORTHAND as u32;
+    }
+}
+
 bitflags! {
     /// Flags for APIs that add files matching pathspec
     pub struct IndexAddOption: u32 {
@@ -454,6 +543,22 @@ bitflags! {
     }
 }
 
+bitflags! {
+    /// Capabilities of an index, controlling how it compares entries against
+    /// the working directory.
+    pub struct IndexCaps: u32 {
+        /// Paths are compared case-insensitively.
+        const INDEXCAP_IGNORE_CASE = raw::GIT_INDEXCAP_IGNORE_CASE as u32;
+        /// File modes are not compared, as if `core.filemode` were false.
+        const INDEXCAP_NO_FILEMODE = raw::GIT_INDEXCAP_NO_FILEMODE as u32;
+        /// Symlinks are treated as regular files.
+        const INDEXCAP_NO_SYMLINKS = raw::GIT_INDEXCAP_NO_SYMLINKS as u32;
+        /// Infer the capabilities from the configuration of the owning
+        /// repository. Only meaningful when passed to `Index::set_caps`.
+        const INDEXCAP_FROM_OWNER = raw::GIT_INDEXCAP_FROM_OWNER as u32;
+    }
+}
+
 bitflags! {
     /// Flags for `Repository::open_ext`
     pub struct RepositoryOpenFlags: u32 {
@@ -528,6 +633,7 @@ pub mod build;
 pub mod cert;
 pub mod string_array;
 pub mod oid_array;
+pub mod opts;
 pub mod transport;
 
 mod blame;
@@ -540,7 +646,13 @@ mod cred;
 mod describe;
 mod diff;
 mod error;
+mod filter;
 mod index;
+mod indexer;
+#[cfg(feature = "libgit2-0-28")]
+mod mailinfo;
+#[cfg(feature = "libgit2-0-28")]
+mod mailmap;
 mod merge;
 mod message;
 mod note;
@@ -551,6 +663,8 @@ mod packbuilder;
 mod pathspec;
 mod patch;
 mod proxy_options;
+mod rebase;
+mod refdb;
 mod reference;
 mod reflog;
 mod refspec;
@@ -565,8 +679,11 @@ mod submodule;
 mod stash;
 mod tag;
 mod time;
+mod transaction;
 mod tree;
 mod treebuilder;
+#[cfg(feature = "libgit2-0-28")]
+mod worktree;
 
 fn init() {
     static INIT: Once = ONCE_INIT;
@@ -726,10 +843,22 @@ impl ObjectType {
     }
 
     /// Determine if the given git_otype is a valid loose object type.
+    ///
+    /// Only commits, trees, blobs and tags may be stored as loose objects;
+    /// `ObjectType::Any` is not a valid type for a stored object.
     pub fn is_loose(&self) -> bool {
         unsafe { (call!(raw::git_object_typeisloose(*self)) == 1) }
     }
 
+    /// Get the size in bytes of the structure libgit2 uses to represent an
+    /// object of this type in memory.
+    ///
+    /// This is not the size of any object's contents, and is `0` for
+    /// `ObjectType::Any`.
+    pub fn size(&self) -> usize {
+        unsafe { call!(raw::git_object__size(*self)) as usize }
+    }
+
     /// Convert a raw git_otype to an ObjectType
     pub fn from_raw(raw: raw::git_otype) -> Option<ObjectType> {
         match raw {
@@ -760,6 +889,43 @@ impl fmt::Display for ObjectType {
     }
 }
 
+impl FileMode {
+    /// Convert a raw file mode, as found in `IndexEntry::mode` or returned by
+    /// `TreeEntry::filemode`, to a `FileMode`.
+    pub fn from_raw(raw: u32) -> Option<FileMode> {
+        match raw {
+            0o000000 => Some(FileMode::Unreadable),
+            0o040000 => Some(FileMode::Tree),
+            0o100644 => Some(FileMode::Blob),
+            0o100755 => Some(FileMode::BlobExecutable),
+            0o120000 => Some(FileMode::Link),
+            0o160000 => Some(FileMode::Commit),
+            _ => None,
+        }
+    }
+
+    /// Convert this mode into its raw representation.
+    pub fn raw(&self) -> u32 {
+        let raw = match *self {
+            FileMode::Unreadable => raw::GIT_FILEMODE_UNREADABLE,
+            FileMode::Tree => raw::GIT_FILEMODE_TREE,
+            FileMode::Blob => raw::GIT_FILEMODE_BLOB,
+            FileMode::BlobExecutable => raw::GIT_FILEMODE_BLOB_EXECUTABLE,
+            FileMode::Link => raw::GIT_FILEMODE_LINK,
+            FileMode::Commit => raw::GIT_FILEMODE_COMMIT,
+        };
+        raw as u32
+    }
+}
+
+impl From<FileMode> for u32 {
+    fn from(mode: FileMode) -> u32 { mode.raw() }
+}
+
+impl From<FileMode> for i32 {
+    fn from(mode: FileMode) -> i32 { mode.raw() as i32 }
+}
+
 impl ConfigLevel {
     /// Converts a raw configuration level to a ConfigLevel
     pub fn from_raw(raw: raw::git_config_level_t) -> ConfigLevel {
@@ -818,6 +984,30 @@ bitflags! {
     }
 }
 
+macro_rules! is_bit_set {
+    ($name:ident, $flag:expr) => (
+        #[allow(missing_docs)]
+        pub fn $name(&self) -> bool {
+            self.intersects($flag)
+        }
+    )
+}
+
+impl Status {
+    is_bit_set!(is_index_new, STATUS_INDEX_NEW);
+    is_bit_set!(is_index_modified, STATUS_INDEX_MODIFIED);
+    is_bit_set!(is_index_deleted, STATUS_INDEX_DELETED);
+    is_bit_set!(is_index_renamed, STATUS_INDEX_RENAMED);
+    is_bit_set!(is_index_typechange, STATUS_INDEX_TYPECHANGE);
+    is_bit_set!(is_wt_new, STATUS_WT_NEW);
+    is_bit_set!(is_wt_modified, STATUS_WT_MODIFIED);
+    is_bit_set!(is_wt_deleted, STATUS_WT_DELETED);
+    is_bit_set!(is_wt_typechange, STATUS_WT_TYPECHANGE);
+    is_bit_set!(is_wt_renamed, STATUS_WT_RENAMED);
+    is_bit_set!(is_ignored, STATUS_IGNORED);
+    is_bit_set!(is_conflicted, STATUS_CONFLICTED);
+}
+
 bitflags! {
     /// Mode options for RepositoryInitOptions
     pub struct RepositoryInitMode: u32 {
@@ -949,6 +1139,39 @@ bitflags! {
 
 }
 
+impl SubmoduleStatus {
+    is_bit_set!(is_in_head, SUBMODULE_STATUS_IN_HEAD);
+    is_bit_set!(is_in_index, SUBMODULE_STATUS_IN_INDEX);
+    is_bit_set!(is_in_config, SUBMODULE_STATUS_IN_CONFIG);
+    is_bit_set!(is_in_wd, SUBMODULE_STATUS_IN_WD);
+    is_bit_set!(is_index_added, SUBMODULE_STATUS_INDEX_ADDED);
+    is_bit_set!(is_index_deleted, SUBMODULE_STATUS_INDEX_DELETED);
+    is_bit_set!(is_index_modified, SUBMODULE_STATUS_INDEX_MODIFIED);
+    is_bit_set!(is_wd_uninitialized, SUBMODULE_STATUS_WD_UNINITIALIZED);
+    is_bit_set!(is_wd_added, SUBMODULE_STATUS_WD_ADDED);
+    is_bit_set!(is_wd_deleted, SUBMODULE_STATUS_WD_DELETED);
+    is_bit_set!(is_wd_modified, SUBMODULE_STATUS_WD_MODIFIED);
+    is_bit_set!(is_wd_index_modified, SUBMODULE_STATUS_WD_INDEX_MODIFIED);
+    is_bit_set!(is_wd_wd_modified, SUBMODULE_STATUS_WD_WD_MODIFIED);
+    is_bit_set!(is_wd_untracked, SUBMODULE_STATUS_WD_UNTRACKED);
+
+    /// Returns whether the submodule is unmodified, i.e. only the `IN_*`
+    /// flags describing where it was found are set.
+    pub fn is_unmodified(&self) -> bool {
+        let in_flags = SUBMODULE_STATUS_IN_HEAD | SUBMODULE_STATUS_IN_INDEX |
+                       SUBMODULE_STATUS_IN_CONFIG | SUBMODULE_STATUS_IN_WD;
+        (*self - in_flags).is_empty()
+    }
+
+    /// Returns whether the submodule's working directory has uncommitted
+    /// changes: a dirty index, modified files or untracked files.
+    pub fn is_wd_dirty(&self) -> bool {
+        self.intersects(SUBMODULE_STATUS_WD_INDEX_MODIFIED |
+                        SUBMODULE_STATUS_WD_WD_MODIFIED |
+                        SUBMODULE_STATUS_WD_UNTRACKED)
+    }
+}
+
 /// Submodule ignore values
 ///
 /// These values represent settings for the `submodule.$name.ignore`
@@ -1068,7 +1291,7 @@ pub enum FetchPrune {
 }
 
 #[allow(missing_docs)]
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
 pub enum StashApplyProgress {
     /// None
     None,
@@ -1116,6 +1339,17 @@ bitflags! {
     }
 }
 
+bitflags! {
+    /// Flags controlling how a `FilterList` is loaded.
+    pub struct FilterFlags: u32 {
+        #[allow(missing_docs)]
+        const FILTER_DEFAULT = raw::GIT_FILTER_DEFAULT as u32;
+        /// Don't error for `safecrlf` violations, allowing the filters to
+        /// proceed.
+        const FILTER_ALLOW_UNSAFE = raw::GIT_FILTER_ALLOW_UNSAFE as u32;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::ObjectType;
@@ -1127,4 +1361,15 @@ mod tests {
         assert!(ObjectType::Blob.is_loose());
     }
 
+    #[test]
+    fn is_loose() {
+        for kind in &[ObjectType::Commit, ObjectType::Tree, ObjectType::Blob,
+                      ObjectType::Tag] {
+            assert!(kind.is_loose());
+            assert!(kind.size() > 0);
+        }
+        assert!(!ObjectType::Any.is_loose());
+        assert_eq!(ObjectType::Any.size(), 0);
+    }
+
 }
@@ -0,0 +1,372 @@
+use std::str;
+
+use {message_prettify, Error, Signature, Time};
+
+/// The author and commit message of an emailed patch, as produced by
+/// `git format-patch`.
+pub struct MailInfo {
+    pub author: Signature<'static>,
+    pub message: String,
+}
+
+/// Parse the `From`, `Date` and `Subject` headers and the message body of an
+/// emailed patch.
+///
+/// The message body ends at the `---` line separating it from the diffstat,
+/// or at the first `diff --git` header. A leading `[PATCH ...]` tag is
+/// stripped from the subject. RFC 2047 encoded words in the headers are
+/// decoded, and the body is decoded with the charset of its `Content-Type`
+/// header, defaulting to utf-8. Only utf-8, us-ascii and iso-8859-1 are
+/// supported, anything else is an error rather than being copied verbatim.
+pub fn parse(patch: &[u8]) -> Result<MailInfo, Error> {
+    let mut lines = patch.split(|b| *b == b'\n').map(|line| {
+        if line.ends_with(b"\r") { &line[..line.len() - 1] } else { line }
+    }).peekable();
+
+    // skip the mbox separator line, e.g. `From <sha> Mon Sep 17 00:00:00 2001`
+    if lines.peek().map_or(false, |l| l.starts_with(b"From ")) {
+        lines.next();
+    }
+
+    let mut headers: Vec<(String, Vec<u8>)> = Vec::new();
+    while let Some(line) = lines.next() {
+        if line.is_empty() {
+            break
+        }
+        if line.starts_with(b" ") || line.starts_with(b"\t") {
+            if let Some(&mut (_, ref mut value)) = headers.last_mut() {
+                value.push(b' ');
+                value.extend_from_slice(trim(line));
+            }
+            continue
+        }
+        if let Some(idx) = line.iter().position(|b| *b == b':') {
+            let name = String::from_utf8_lossy(trim(&line[..idx]));
+            let value = trim(&line[idx + 1..]).to_vec();
+            headers.push((name.to_lowercase(), value));
+        }
+    }
+    let header = |name: &str| -> Result<Option<String>, Error> {
+        match headers.iter().find(|h| h.0 == name) {
+            Some(h) => decode_header(&h.1).map(Some),
+            None => Ok(None),
+        }
+    };
+
+    let from = match try!(header("from")) {
+        Some(from) => from,
+        None => return Err(Error::from_str("patch has no From header")),
+    };
+    let (name, email) = parse_from(&from);
+    let author = match try!(header("date")) {
+        Some(date) => match parse_date(&date) {
+            Some(time) => try!(Signature::new(name, email, &time)),
+            None => return Err(Error::from_str("invalid Date header in patch")),
+        },
+        None => try!(Signature::now(name, email)),
+    };
+    let charset = try!(header("content-type")).and_then(|t| content_charset(&t))
+                                              .unwrap_or("utf-8".to_string());
+
+    let subject = try!(header("subject")).unwrap_or(String::new());
+    let mut body = Vec::new();
+    for line in lines {
+        if line == b"---" || line.starts_with(b"diff --git ") {
+            break
+        }
+        body.extend_from_slice(line);
+        body.push(b'\n');
+    }
+    let mut message = strip_subject(&subject).to_string();
+    message.push_str("\n\n");
+    message.push_str(&try!(decode(&body, &charset)));
+    let message = try!(message_prettify(message, None));
+    Ok(MailInfo { author: author, message: message })
+}
+
+fn trim(mut s: &[u8]) -> &[u8] {
+    while s.first().map_or(false, |b| *b == b' ' || *b == b'\t') {
+        s = &s[1..];
+    }
+    while s.last().map_or(false, |b| *b == b' ' || *b == b'\t') {
+        s = &s[..s.len() - 1];
+    }
+    s
+}
+
+/// Decode `bytes` from the named charset.
+fn decode(bytes: &[u8], charset: &str) -> Result<String, Error> {
+    match &charset.to_lowercase()[..] {
+        "utf-8" | "utf8" | "us-ascii" => match str::from_utf8(bytes) {
+            Ok(s) => Ok(s.to_string()),
+            Err(..) => Err(Error::from_str(&format!("patch is not valid {}",
+                                                    charset))),
+        },
+        "iso-8859-1" | "latin1" => Ok(bytes.iter().map(|b| *b as char)
+                                           .collect()),
+        _ => Err(Error::from_str(&format!("unsupported charset {} in patch",
+                                          charset))),
+    }
+}
+
+/// Find the `charset` parameter of a `Content-Type` header.
+fn content_charset(content_type: &str) -> Option<String> {
+    content_type.split(';').skip(1).filter_map(|param| {
+        let mut kv = param.splitn(2, '=');
+        match (kv.next(), kv.next()) {
+            (Some(k), Some(v)) if k.trim().to_lowercase() == "charset" => {
+                Some(v.trim().trim_matches('"').to_string())
+            }
+            _ => None,
+        }
+    }).next()
+}
+
+/// Decode a header value, including any RFC 2047 encoded words such as
+/// `=?UTF-8?q?J=C3=B6rg?=`.
+///
+/// Whitespace between two adjacent encoded words is dropped, as the RFC
+/// requires.
+fn decode_header(value: &[u8]) -> Result<String, Error> {
+    let value = try!(decode(value, "utf-8"));
+    let mut out = String::new();
+    let mut rest = &value[..];
+    let mut after_word = false;
+    while let Some(start) = rest.find("=?") {
+        let (before, word) = rest.split_at(start);
+        let mut parts = word[2..].splitn(3, '?');
+        let (charset, encoding, text) = match (parts.next(), parts.next(),
+                                               parts.next()) {
+            (Some(c), Some(e), Some(t)) => (c, e, t),
+            _ => break,
+        };
+        let end = match text.find("?=") {
+            Some(end) => end,
+            None => break,
+        };
+        if !after_word || !before.trim().is_empty() {
+            out.push_str(before);
+        }
+        let bytes = match &encoding.to_lowercase()[..] {
+            "q" => decode_q(&text[..end]),
+            "b" => decode_base64(&text[..end]),
+            _ => None,
+        };
+        let bytes = match bytes {
+            Some(bytes) => bytes,
+            None => return Err(Error::from_str("invalid encoded word in \
+                                                patch header")),
+        };
+        // drop any RFC 2231 language suffix, e.g. `UTF-8*en`
+        let charset = charset.split('*').next().unwrap_or(charset);
+        out.push_str(&try!(decode(&bytes, charset)));
+        rest = &text[end + 2..];
+        after_word = true;
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Decode the "Q" encoding of RFC 2047, a variant of quoted-printable.
+fn decode_q(text: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::new();
+    let mut bytes = text.bytes();
+    while let Some(b) = bytes.next() {
+        match b {
+            b'_' => out.push(b' '),
+            b'=' => {
+                let hex = [bytes.next().unwrap_or(b'x'),
+                           bytes.next().unwrap_or(b'x')];
+                let hex = match str::from_utf8(&hex) {
+                    Ok(hex) => hex,
+                    Err(..) => return None,
+                };
+                match u8::from_str_radix(hex, 16) {
+                    Ok(b) => out.push(b),
+                    Err(..) => return None,
+                }
+            }
+            b => out.push(b),
+        }
+    }
+    Some(out)
+}
+
+/// Decode the "B" encoding of RFC 2047, which is base64.
+fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    const ALPHABET: &'static [u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ\
+                                      abcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = Vec::new();
+    let mut acc = 0u32;
+    let mut bits = 0;
+    for b in text.bytes().take_while(|b| *b != b'=') {
+        let value = match ALPHABET.iter().position(|a| *a == b) {
+            Some(value) => value as u32,
+            None => return None,
+        };
+        acc = (acc << 6) | value;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((acc >> bits) as u8);
+            acc &= (1 << bits) - 1;
+        }
+    }
+    Some(out)
+}
+
+/// Split a `From` header of the form `Name <email>` into its parts.
+fn parse_from(from: &str) -> (&str, &str) {
+    match (from.find('<'), from.rfind('>')) {
+        (Some(start), Some(end)) if start < end => {
+            let email = &from[start + 1..end];
+            let name = from[..start].trim().trim_matches('"').trim();
+            (if name.is_empty() { email } else { name }, email)
+        }
+        _ => (from, from),
+    }
+}
+
+/// Remove any leading bracketed tags, such as `[PATCH 1/2]`, from a subject.
+fn strip_subject(mut subject: &str) -> &str {
+    while subject.starts_with('[') {
+        match subject.find(']') {
+            Some(idx) => subject = subject[idx + 1..].trim(),
+            None => break,
+        }
+    }
+    subject
+}
+
+/// Parse an RFC 2822 date such as `Tue, 13 Oct 2026 10:00:00 +0200`.
+///
+/// Comments in parentheses, e.g. a trailing `(UTC)`, are ignored.
+fn parse_date(date: &str) -> Option<Time> {
+    let mut stripped = String::new();
+    let mut depth = 0;
+    for c in date.chars() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth > 0 => depth -= 1,
+            c if depth == 0 => stripped.push(c),
+            _ => {}
+        }
+    }
+    let mut parts = stripped.split_whitespace().peekable();
+    if parts.peek().map_or(false, |p| p.ends_with(',')) {
+        parts.next();
+    }
+    let parts = parts.collect::<Vec<_>>();
+    if parts.len() != 5 {
+        return None
+    }
+    const MONTHS: [&'static str; 12] = ["jan", "feb", "mar", "apr", "may",
+                                        "jun", "jul", "aug", "sep", "oct",
+                                        "nov", "dec"];
+    let day = match parts[0].parse::<i64>() { Ok(d) => d, Err(..) => return None };
+    let month = match MONTHS.iter().position(|m| parts[1].to_lowercase() == *m) {
+        Some(m) => m as i64 + 1,
+        None => return None,
+    };
+    let year = match parts[2].parse::<i64>() { Ok(y) => y, Err(..) => return None };
+    let hms = parts[3].split(':').map(|p| p.parse::<i64>())
+                      .collect::<Result<Vec<_>, _>>();
+    let (hour, min, sec) = match hms {
+        Ok(ref v) if v.len() == 3 => (v[0], v[1], v[2]),
+        Ok(ref v) if v.len() == 2 => (v[0], v[1], 0),
+        _ => return None,
+    };
+    let zone = parts[4];
+    if zone.len() != 5 || !(zone.starts_with('+') || zone.starts_with('-')) {
+        return None
+    }
+    let offset = match zone[1..].parse::<i32>() {
+        Ok(z) => (z / 100) * 60 + z % 100,
+        Err(..) => return None,
+    };
+    let offset = if zone.starts_with('-') { -offset } else { offset };
+
+    // days since the epoch of the civil date, from Howard Hinnant's algorithm
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = if y >= 0 { y } else { y - 399 } / 400;
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146097 + doe - 719468;
+
+    let local = days * 86400 + hour * 3600 + min * 60 + sec;
+    Some(Time::new(local - offset as i64 * 60, offset))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, parse_date};
+
+    #[test]
+    fn smoke() {
+        let info = parse(b"From 1234 Mon Sep 17 00:00:00 2001\n\
+                           From: \"A U Thor\" <author@example.com>\n\
+                           Date: Fri, 13 Feb 2009 23:31:30 +0000\n\
+                           Subject: [PATCH 1/2] Fix the\n \
+                           frobnicator\n\
+                           \n\
+                           It was broken.\n\
+                           ---\n \
+                           foo | 1 +\n").unwrap();
+        assert_eq!(info.author.name(), Some("A U Thor"));
+        assert_eq!(info.author.email(), Some("author@example.com"));
+        assert_eq!(info.author.when().seconds(), 1234567890);
+        assert_eq!(info.message, "Fix the frobnicator\n\nIt was broken.\n");
+
+        let info = parse(b"From: x@y.z\nSubject: one line\n\n").unwrap();
+        assert_eq!(info.author.name(), Some("x@y.z"));
+        assert_eq!(info.message, "one line\n");
+
+        assert!(parse(b"Subject: no author\n\n").is_err());
+        assert!(parse(b"From: a <b>\nDate: yesterday\n\n").is_err());
+    }
+
+    #[test]
+    fn encodings() {
+        let info = parse(b"From: =?UTF-8?q?J=C3=B6rg_Thor?= <j@example.com>\n\
+                           Subject: [PATCH] =?UTF-8?B?w5xuw69jb2RlIHN1YmplY3Q=?=\n \
+                           =?ISO-8859-1?Q?_=E9?=\n\
+                           \n\
+                           Body\n\
+                           ---\n\
+                           diff --git a/foo b/foo\n\
+                           +\xff\xfe\n").unwrap();
+        assert_eq!(info.author.name(), Some("J\u{f6}rg Thor"));
+        assert_eq!(info.author.email(), Some("j@example.com"));
+        assert_eq!(info.message, "\u{dc}n\u{ef}code subject \u{e9}\n\nBody\n");
+
+        let info = parse(b"From: a <b@c>\n\
+                           Content-Type: text/plain; charset=\"ISO-8859-1\"\n\
+                           Subject: latin1\n\
+                           \n\
+                           caf\xe9\n").unwrap();
+        assert_eq!(info.message, "latin1\n\ncaf\u{e9}\n");
+
+        assert!(parse(b"From: a <b@c>\nSubject: x\n\n\xff\n").is_err());
+        assert!(parse(b"From: =?KOI8-R?Q?=E1?= <b@c>\n\n").is_err());
+        assert!(parse(b"From: a <b@c>\n\
+                        Content-Type: text/plain; charset=KOI8-R\n\
+                        \n\
+                        x\n").is_err());
+    }
+
+    #[test]
+    fn date() {
+        let t = parse_date("Tue, 13 Oct 2026 10:00:00 +0200").unwrap();
+        assert_eq!(t.seconds(), 1791878400);
+        assert_eq!(t.offset_minutes(), 120);
+        let t = parse_date("Fri, 13 Feb 2009 23:31:30 +0000 (UTC)").unwrap();
+        assert_eq!(t.seconds(), 1234567890);
+        let t = parse_date("1 Jan 1970 00:00 -0130").unwrap();
+        assert_eq!(t.seconds(), 5400);
+        assert_eq!(t.offset_minutes(), -90);
+        assert!(parse_date("13 Foo 2026 10:00:00 +0200").is_none());
+        assert!(parse_date("13 Oct 2026 10:00:00").is_none());
+    }
+}
@@ -0,0 +1,147 @@
+use std::ffi::{CStr, CString};
+use std::ptr;
+use libc::c_char;
+
+use {raw, Error, Signature};
+use util::Binding;
+
+/// A structure to represent a repository's .mailmap file.
+///
+/// The representation cannot be written to disk.
+pub struct Mailmap {
+    raw: *mut raw::git_mailmap,
+}
+
+impl Binding for Mailmap {
+    type Raw = *mut raw::git_mailmap;
+
+    unsafe fn from_raw(ptr: *mut raw::git_mailmap) -> Mailmap {
+        Mailmap { raw: ptr }
+    }
+
+    fn raw(&self) -> *mut raw::git_mailmap {
+        self.raw
+    }
+}
+
+impl Drop for Mailmap {
+    fn drop(&mut self) {
+        unsafe {
+            raw::git_mailmap_free(self.raw);
+        }
+    }
+}
+
+impl Mailmap {
+    /// Creates an empty, in-memory mailmap object.
+    pub fn new() -> Result<Mailmap, Error> {
+        ::init();
+        let mut ret = ptr::null_mut();
+        unsafe {
+            try_call!(raw::git_mailmap_new(&mut ret));
+            Ok(Binding::from_raw(ret))
+        }
+    }
+
+    /// Creates an in-memory mailmap object representing the given buffer,
+    /// which is parsed in the format of a `.mailmap` file.
+    pub fn from_buffer(buf: &str) -> Result<Mailmap, Error> {
+        ::init();
+        let mut ret = ptr::null_mut();
+        let len = buf.len();
+        let buf = try!(CString::new(buf));
+        unsafe {
+            try_call!(raw::git_mailmap_from_buffer(&mut ret, buf, len));
+            Ok(Binding::from_raw(ret))
+        }
+    }
+
+    /// Adds a new entry to this in-memory mailmap object.
+    ///
+    /// Commits by `replace_name` (if given) with `replace_email` will be
+    /// attributed to `real_name` and `real_email`, each of which retains the
+    /// original value when `None`.
+    pub fn add_entry(&mut self,
+                     real_name: Option<&str>,
+                     real_email: Option<&str>,
+                     replace_name: Option<&str>,
+                     replace_email: &str) -> Result<(), Error> {
+        let real_name = try!(::opt_cstr(real_name));
+        let real_email = try!(::opt_cstr(real_email));
+        let replace_name = try!(::opt_cstr(replace_name));
+        let replace_email = try!(CString::new(replace_email));
+        unsafe {
+            try_call!(raw::git_mailmap_add_entry(self.raw, real_name,
+                                                 real_email, replace_name,
+                                                 replace_email));
+            Ok(())
+        }
+    }
+
+    /// Resolves a name and email to the canonical name and email listed in
+    /// this mailmap.
+    ///
+    /// If no entry matches, the given name and email are returned unchanged.
+    pub fn resolve(&self, name: &str, email: &str)
+                   -> Result<(String, String), Error> {
+        let name = try!(CString::new(name));
+        let email = try!(CString::new(email));
+        let mut real_name: *const c_char = ptr::null();
+        let mut real_email: *const c_char = ptr::null();
+        unsafe {
+            try_call!(raw::git_mailmap_resolve(&mut real_name, &mut real_email,
+                                               &*self.raw, name, email));
+            let real_name = CStr::from_ptr(real_name).to_string_lossy();
+            let real_email = CStr::from_ptr(real_email).to_string_lossy();
+            Ok((real_name.into_owned(), real_email.into_owned()))
+        }
+    }
+
+    /// Resolves a signature to use the canonical name and email listed in
+    /// this mailmap.
+    pub fn resolve_signature(&self, sig: &Signature)
+                             -> Result<Signature<'static>, Error> {
+        let mut ret = ptr::null_mut();
+        unsafe {
+            try_call!(raw::git_mailmap_resolve_signature(&mut ret, &*self.raw,
+                                                         sig.raw()));
+            Ok(Binding::from_raw(ret))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {Mailmap, Signature};
+
+    #[test]
+    fn smoke() {
+        let mut mm = Mailmap::from_buffer("\
+            Real Name <real@example.com> <old@example.com>\n").unwrap();
+        mm.add_entry(Some("Other"), None, None, "other@example.com").unwrap();
+
+        let (name, email) = mm.resolve("Old", "old@example.com").unwrap();
+        assert_eq!(name, "Real Name");
+        assert_eq!(email, "real@example.com");
+        let (name, email) = mm.resolve("Nobody", "nobody@example.com").unwrap();
+        assert_eq!(name, "Nobody");
+        assert_eq!(email, "nobody@example.com");
+
+        let sig = Signature::now("Some One", "other@example.com").unwrap();
+        let sig = mm.resolve_signature(&sig).unwrap();
+        assert_eq!(sig.name(), Some("Other"));
+        assert_eq!(sig.email(), Some("other@example.com"));
+    }
+
+    #[test]
+    fn from_repository() {
+        let (_td, repo) = ::test::repo_init();
+        let head = repo.head().unwrap().target().unwrap();
+        let commit = repo.find_commit(head).unwrap();
+        let mm = repo.mailmap().unwrap();
+        let author = commit.author_with_mailmap(&mm).unwrap();
+        assert_eq!(author.name(), commit.author().name());
+        let committer = commit.committer_with_mailmap(&mm).unwrap();
+        assert_eq!(committer.email(), commit.committer().email());
+    }
+}
@@ -1,8 +1,14 @@
+use std::ffi::CString;
 use std::marker;
 use std::mem;
-use libc::c_uint;
+use std::ptr;
+use std::slice;
+use std::str;
+use libc::{c_char, c_uint, size_t};
+#[cfg(feature = "libgit2-0-28")]
+use libc::c_ushort;
 
-use {raw, Oid, Commit, FileFavor};
+use {raw, Error, Oid, Commit, FileFavor};
 use util::Binding;
 use call::Convert;
 
@@ -21,11 +27,41 @@ pub struct MergeOptions {
     raw: raw::git_merge_options,
 }
 
+/// Options to specify when merging the contents of a file with `merge_file`.
+pub struct MergeFileOptions {
+    raw: raw::git_merge_file_options,
+    ancestor_label: Option<CString>,
+    our_label: Option<CString>,
+    their_label: Option<CString>,
+}
+
+/// The result of a three-way merge of file contents with `merge_file`.
+pub struct MergeFileResult {
+    raw: raw::git_merge_file_result,
+}
+
 impl<'repo> AnnotatedCommit<'repo> {
     /// Gets the commit ID that the given git_annotated_commit refers to
     pub fn id(&self) -> Oid {
         unsafe { Binding::from_raw(raw::git_annotated_commit_id(self.raw)) }
     }
+
+    /// Get the name of the reference this annotated commit was looked up
+    /// from.
+    ///
+    /// Returns `None` if it was not created from a reference or if the name
+    /// is not valid utf-8.
+    pub fn refname(&self) -> Option<&str> {
+        self.refname_bytes().and_then(|s| str::from_utf8(s).ok())
+    }
+
+    /// Get the name of the reference this annotated commit was looked up
+    /// from, as a byte slice.
+    ///
+    /// Returns `None` if it was not created from a reference.
+    pub fn refname_bytes(&self) -> Option<&[u8]> {
+        unsafe { ::opt_bytes(self, raw::git_annotated_commit_ref(&*self.raw)) }
+    }
 }
 
 impl Default for MergeOptions {
@@ -141,6 +177,201 @@ impl MergeOptions {
     }
 }
 
+/// Merge three versions of a file's contents in memory, using the same
+/// algorithm as a merge of the index.
+///
+/// `ancestor` is the common base of `ours` and `theirs`. If the two sides
+/// conflict the result contains conflict markers and isn't automergeable,
+/// unless a side is favored with `MergeFileOptions::file_favor`.
+pub fn merge_file(ancestor: &[u8], ours: &[u8], theirs: &[u8],
+                  opts: Option<&MergeFileOptions>)
+                  -> Result<MergeFileResult, Error> {
+    ::init();
+    unsafe {
+        let ancestor = try!(file_input(ancestor));
+        let ours = try!(file_input(ours));
+        let theirs = try!(file_input(theirs));
+        let mut result = mem::zeroed();
+        try_call!(raw::git_merge_file(&mut result, &ancestor, &ours, &theirs,
+                                      opts.map(|o| o.raw())));
+        Ok(Binding::from_raw(&result as *const _))
+    }
+}
+
+unsafe fn file_input(data: &[u8]) -> Result<raw::git_merge_file_input, Error> {
+    let mut input = mem::zeroed();
+    try_call!(raw::git_merge_file_init_input(&mut input,
+                                             raw::GIT_MERGE_FILE_INPUT_VERSION));
+    input.ptr = data.as_ptr() as *const c_char;
+    input.size = data.len() as size_t;
+    Ok(input)
+}
+
+impl Default for MergeFileOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MergeFileOptions {
+    /// Creates a default set of file merge options.
+    pub fn new() -> MergeFileOptions {
+        let mut opts = MergeFileOptions {
+            raw: unsafe { mem::zeroed() },
+            ancestor_label: None,
+            our_label: None,
+            their_label: None,
+        };
+        assert_eq!(unsafe {
+            raw::git_merge_file_init_options(&mut opts.raw,
+                                             raw::GIT_MERGE_FILE_OPTIONS_VERSION)
+        }, 0);
+        opts
+    }
+
+    /// Label for the ancestor side of diff3-style conflicts.
+    pub fn ancestor_label(&mut self, label: &str) -> &mut MergeFileOptions {
+        let label = CString::new(label).unwrap();
+        self.raw.ancestor_label = label.as_ptr();
+        self.ancestor_label = Some(label);
+        self
+    }
+
+    /// Label for our side of conflicts, shown after `<<<<<<<`.
+    pub fn our_label(&mut self, label: &str) -> &mut MergeFileOptions {
+        let label = CString::new(label).unwrap();
+        self.raw.our_label = label.as_ptr();
+        self.our_label = Some(label);
+        self
+    }
+
+    /// Label for their side of conflicts, shown after `>>>>>>>`.
+    pub fn their_label(&mut self, label: &str) -> &mut MergeFileOptions {
+        let label = CString::new(label).unwrap();
+        self.raw.their_label = label.as_ptr();
+        self.their_label = Some(label);
+        self
+    }
+
+    /// Specify a side to favor for resolving conflicts
+    pub fn file_favor(&mut self, favor: FileFavor) -> &mut MergeFileOptions {
+        self.raw.favor = favor.convert();
+        self
+    }
+
+    fn flag(&mut self, opt: raw::git_merge_file_flag_t, val: bool)
+            -> &mut MergeFileOptions {
+        if val {
+            self.raw.flags |= opt;
+        } else {
+            self.raw.flags &= !opt;
+        }
+        self
+    }
+
+    /// Create standard conflicted merge files
+    pub fn standard_style(&mut self, standard: bool) -> &mut MergeFileOptions {
+        self.flag(raw::GIT_MERGE_FILE_STYLE_MERGE, standard)
+    }
+
+    /// Create diff3-style file
+    pub fn diff3_style(&mut self, diff3: bool) -> &mut MergeFileOptions {
+        self.flag(raw::GIT_MERGE_FILE_STYLE_DIFF3, diff3)
+    }
+
+    /// Condense non-alphanumeric regions for simplified diff file
+    pub fn simplify_alnum(&mut self, simplify: bool) -> &mut MergeFileOptions {
+        self.flag(raw::GIT_MERGE_FILE_SIMPLIFY_ALNUM, simplify)
+    }
+
+    /// Ignore all whitespace
+    pub fn ignore_whitespace(&mut self, ignore: bool) -> &mut MergeFileOptions {
+        self.flag(raw::GIT_MERGE_FILE_IGNORE_WHITESPACE, ignore)
+    }
+
+    /// Ignore changes in amount of whitespace
+    pub fn ignore_whitespace_change(&mut self, ignore: bool)
+                                    -> &mut MergeFileOptions {
+        self.flag(raw::GIT_MERGE_FILE_IGNORE_WHITESPACE_CHANGE, ignore)
+    }
+
+    /// Ignore whitespace at end of line
+    pub fn ignore_whitespace_eol(&mut self, ignore: bool)
+                                 -> &mut MergeFileOptions {
+        self.flag(raw::GIT_MERGE_FILE_IGNORE_WHITESPACE_EOL, ignore)
+    }
+
+    /// Use the "patience diff" algorithm
+    pub fn patience(&mut self, patience: bool) -> &mut MergeFileOptions {
+        self.flag(raw::GIT_MERGE_FILE_DIFF_PATIENCE, patience)
+    }
+
+    /// Take extra time to find minimal diff
+    pub fn minimal(&mut self, minimal: bool) -> &mut MergeFileOptions {
+        self.flag(raw::GIT_MERGE_FILE_DIFF_MINIMAL, minimal)
+    }
+
+    /// The size of conflict markers (default 7)
+    #[cfg(feature = "libgit2-0-28")]
+    pub fn marker_size(&mut self, size: u16) -> &mut MergeFileOptions {
+        self.raw.marker_size = size as c_ushort;
+        self
+    }
+
+    /// Acquire a pointer to the underlying raw options.
+    pub unsafe fn raw(&self) -> *const raw::git_merge_file_options {
+        &self.raw as *const _
+    }
+}
+
+impl MergeFileResult {
+    /// Whether the merge completed without conflicts.
+    pub fn automergeable(&self) -> bool {
+        self.raw.automergeable != 0
+    }
+
+    /// The path of the merged file, if the inputs had one.
+    ///
+    /// This is `None` for the results of `merge_file`, which merges contents
+    /// alone, and is taken from the entries by
+    /// `Repository::merge_file_from_index`. Returns `None` too if the path is
+    /// not valid utf-8.
+    pub fn path(&self) -> Option<&str> {
+        self.path_bytes().and_then(|s| str::from_utf8(s).ok())
+    }
+
+    /// Gets the path of the merged file, in bytes.
+    pub fn path_bytes(&self) -> Option<&[u8]> {
+        unsafe { ::opt_bytes(self, self.raw.path) }
+    }
+
+    /// The merged contents, including any conflict markers.
+    pub fn content(&self) -> &[u8] {
+        if self.raw.ptr.is_null() {
+            return &[]
+        }
+        unsafe {
+            slice::from_raw_parts(self.raw.ptr as *const u8,
+                                  self.raw.len as usize)
+        }
+    }
+}
+
+impl Binding for MergeFileResult {
+    type Raw = *const raw::git_merge_file_result;
+    unsafe fn from_raw(raw: *const raw::git_merge_file_result)
+                       -> MergeFileResult {
+        MergeFileResult { raw: ptr::read(raw) }
+    }
+    fn raw(&self) -> *const raw::git_merge_file_result { &self.raw }
+}
+
+impl Drop for MergeFileResult {
+    fn drop(&mut self) {
+        unsafe { raw::git_merge_file_result_free(&mut self.raw) }
+    }
+}
+
 impl<'repo> Binding for AnnotatedCommit<'repo> {
     type Raw = *mut raw::git_annotated_commit;
     unsafe fn from_raw(raw: *mut raw::git_annotated_commit)
@@ -158,3 +389,96 @@ impl<'repo> Drop for AnnotatedCommit<'repo> {
         unsafe { raw::git_annotated_commit_free(self.raw) }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use {merge_file, FileFavor, MergeFileOptions, MergeOptions, Oid};
+    use Repository;
+
+    fn commit(repo: &Repository, parent: Oid, content: &[u8]) -> Oid {
+        let blob = repo.blob(content).unwrap();
+        let mut builder = repo.treebuilder(None).unwrap();
+        builder.insert("foo", blob, 0o100644).unwrap();
+        let tree = repo.find_tree(builder.write().unwrap()).unwrap();
+        let sig = repo.signature().unwrap();
+        let parent = repo.find_commit(parent).unwrap();
+        repo.commit(None, &sig, &sig, "foo", &tree, &[&parent]).unwrap()
+    }
+
+    #[test]
+    fn file_favor() {
+        let (_td, repo) = ::test::repo_init();
+        let base = repo.head().unwrap().target().unwrap();
+        let base = commit(&repo, base, b"base\n");
+        let ours = repo.find_commit(commit(&repo, base, b"ours\n")).unwrap();
+        let theirs = repo.find_commit(commit(&repo, base, b"theirs\n")).unwrap();
+
+        let index = repo.merge_commits(&ours, &theirs, None).unwrap();
+        assert!(index.has_conflicts());
+
+        let mut opts = MergeOptions::new();
+        opts.file_favor(FileFavor::Ours);
+        let index = repo.merge_commits(&ours, &theirs, Some(&opts)).unwrap();
+        assert!(!index.has_conflicts());
+        let entry = index.get_path(Path::new("foo"), 0).unwrap();
+        assert_eq!(repo.find_blob(entry.id).unwrap().content(), b"ours\n");
+    }
+
+    #[test]
+    fn merge_file_contents() {
+        let result = merge_file(b"a\nb\nc\n", b"A\nb\nc\n", b"a\nb\nC\n",
+                                None).unwrap();
+        assert!(result.automergeable());
+        assert_eq!(result.content(), b"A\nb\nC\n");
+        assert_eq!(result.path(), None);
+
+        let mut opts = MergeFileOptions::new();
+        opts.our_label("ours").their_label("theirs");
+        let result = merge_file(b"base\n", b"ours\n", b"theirs\n",
+                                Some(&opts)).unwrap();
+        assert!(!result.automergeable());
+        assert_eq!(result.content(),
+                   &b"<<<<<<< ours\nours\n=======\ntheirs\n>>>>>>> theirs\n"[..]);
+
+        opts.diff3_style(true).ancestor_label("base");
+        let result = merge_file(b"base\n", b"ours\n", b"theirs\n",
+                                Some(&opts)).unwrap();
+        assert_eq!(result.content(),
+                   &b"<<<<<<< ours\nours\n||||||| base\nbase\n=======\n\
+                      theirs\n>>>>>>> theirs\n"[..]);
+
+        opts.file_favor(FileFavor::Theirs);
+        let result = merge_file(b"base\n", b"ours\n", b"theirs\n",
+                                Some(&opts)).unwrap();
+        assert!(result.automergeable());
+        assert_eq!(result.content(), b"theirs\n");
+    }
+
+    #[test]
+    fn merge_file_from_index() {
+        let (_td, repo) = ::test::repo_init();
+        let base = repo.head().unwrap().target().unwrap();
+        let base = commit(&repo, base, b"base\n");
+        let ours = repo.find_commit(commit(&repo, base, b"ours\n")).unwrap();
+        let theirs = repo.find_commit(commit(&repo, base, b"theirs\n")).unwrap();
+        let index = repo.merge_commits(&ours, &theirs, None).unwrap();
+
+        let stage = |n| index.get_path(Path::new("foo"), n).unwrap();
+        let (ancestor, ours, theirs) = (stage(1), stage(2), stage(3));
+        let result = repo.merge_file_from_index(Some(&ancestor), &ours,
+                                                &theirs, None).unwrap();
+        assert!(!result.automergeable());
+        assert_eq!(result.path(), Some("foo"));
+        assert_eq!(result.content(),
+                   &b"<<<<<<< foo\nours\n=======\ntheirs\n>>>>>>> foo\n"[..]);
+
+        let mut opts = MergeFileOptions::new();
+        opts.file_favor(FileFavor::Ours);
+        let result = repo.merge_file_from_index(None, &ours, &theirs,
+                                                Some(&opts)).unwrap();
+        assert!(result.automergeable());
+        assert_eq!(result.content(), b"ours\n");
+    }
+}
@@ -127,4 +127,24 @@ mod tests {
         assert_eq!(sig.name(), note_obj.committer().name());
         assert!(sig.when() == note_obj.committer().when());
     }
+
+    #[test]
+    fn default_ref_from_config() {
+        let (_td, repo) = ::test::repo_init();
+        repo.config().unwrap().set_str("core.notesRef", "refs/notes/review")
+            .unwrap();
+        assert_eq!(repo.note_default_ref().unwrap(), "refs/notes/review");
+
+        let sig = repo.signature().unwrap();
+        let head = repo.head().unwrap().target().unwrap();
+        repo.note(&sig, &sig, None, head, "lgtm", false).unwrap();
+        assert!(repo.find_reference("refs/notes/review").is_ok());
+        assert!(repo.find_reference("refs/notes/commits").is_err());
+
+        let default_ref = repo.note_default_ref().unwrap();
+        let note = repo.find_note(Some(&default_ref), head).unwrap();
+        assert_eq!(note.message(), Some("lgtm"));
+        assert_eq!(repo.notes(Some(&default_ref)).unwrap().count(), 1);
+        assert!(repo.notes(Some("refs/notes/commits")).is_err());
+    }
 }
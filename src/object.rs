@@ -22,6 +22,18 @@ impl<'repo> Object<'repo> {
         }
     }
 
+    /// Create an independently-owned handle to this object.
+    ///
+    /// The new handle refers to the same underlying object and remains valid
+    /// after this one is dropped.
+    pub fn dup(&self) -> Result<Object<'repo>, Error> {
+        let mut raw = ptr::null_mut();
+        unsafe {
+            try_call!(raw::git_object_dup(&mut raw, self.raw));
+            Ok(Binding::from_raw(raw))
+        }
+    }
+
     /// Get the object type of an object.
     ///
     /// If the type is unknown, then `None` is returned.
@@ -43,22 +55,22 @@ impl<'repo> Object<'repo> {
     }
 
     /// Recursively peel an object until a blob is found
-    pub fn peel_to_blob(&self) -> Result<Blob, Error> {
+    pub fn peel_to_blob(&self) -> Result<Blob<'repo>, Error> {
         self.peel(ObjectType::Blob).map(|o| o.cast_or_panic(ObjectType::Blob))
     }
 
     /// Recursively peel an object until a commit is found
-    pub fn peel_to_commit(&self) -> Result<Commit, Error> {
+    pub fn peel_to_commit(&self) -> Result<Commit<'repo>, Error> {
         self.peel(ObjectType::Commit).map(|o| o.cast_or_panic(ObjectType::Commit))
     }
 
     /// Recursively peel an object until a tag is found
-    pub fn peel_to_tag(&self) -> Result<Tag, Error> {
+    pub fn peel_to_tag(&self) -> Result<Tag<'repo>, Error> {
         self.peel(ObjectType::Tag).map(|o| o.cast_or_panic(ObjectType::Tag))
     }
 
     /// Recursively peel an object until a tree is found
-    pub fn peel_to_tree(&self) -> Result<Tree, Error> {
+    pub fn peel_to_tree(&self) -> Result<Tree<'repo>, Error> {
         self.peel(ObjectType::Tree).map(|o| o.cast_or_panic(ObjectType::Tree))
     }
 
@@ -144,6 +156,23 @@ impl<'repo> Object<'repo> {
         }
     }
 
+    /// Create an `Object` from a raw `git_object` pointer.
+    ///
+    /// This is unsafe as the pointer must be valid and must not outlive the
+    /// repository it was looked up in. Ownership of the pointer is transferred
+    /// to the returned `Object`, which will free it when dropped.
+    pub unsafe fn from_raw(raw: *mut raw::git_object) -> Object<'repo> {
+        Binding::from_raw(raw)
+    }
+
+    /// Get the raw `git_object` pointer underlying this object.
+    ///
+    /// The pointer is still owned by this `Object` and is only valid for as
+    /// long as it is alive.
+    pub fn raw(&self) -> *mut raw::git_object {
+        self.raw
+    }
+
     fn cast<T>(&self, kind: ObjectType) -> Option<&T> {
         assert_eq!(mem::size_of::<Object>(), mem::size_of::<T>());
         if self.kind() == Some(kind) {
@@ -232,3 +261,27 @@ impl<'repo> Drop for Object<'repo> {
         unsafe { raw::git_object_free(self.raw) }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use ObjectType;
+
+    #[test]
+    fn peel() {
+        let (_td, repo) = ::test::repo_init();
+        let head = repo.head().unwrap().target().unwrap();
+        let commit = repo.find_commit(head).unwrap();
+        let sig = repo.signature().unwrap();
+        let tag_id = repo.tag("v1", commit.as_object(), &sig, "msg",
+                              false).unwrap();
+        let tag = repo.find_object(tag_id, None).unwrap();
+
+        assert_eq!(tag.peel_to_tag().unwrap().id(), tag_id);
+        assert_eq!(tag.peel_to_commit().unwrap().id(), head);
+        assert_eq!(tag.peel_to_tree().unwrap().id(), commit.tree_id());
+        assert_eq!(commit.as_object().peel_to_tree().unwrap().id(),
+                   commit.tree_id());
+        assert_eq!(tag.peel(ObjectType::Any).unwrap().id(), head);
+        assert!(commit.as_object().peel_to_blob().is_err());
+    }
+}
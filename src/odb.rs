@@ -1,9 +1,13 @@
+use std::ffi::CString;
 use std::marker;
 use std::io;
+use std::mem;
+use std::path::Path;
 use std::ptr;
-use libc::{c_char, c_int, c_void};
+use std::slice;
+use libc::{c_char, c_int, c_void, size_t};
 
-use {raw, Oid, Object, ObjectType, Error};
+use {raw, Oid, Object, ObjectType, Error, IntoCString};
 use panic;
 use util::Binding;
 
@@ -31,15 +35,84 @@ impl<'repo> Drop for Odb<'repo> {
     }
 }
 
+impl Odb<'static> {
+    /// Create a new object database with no backends.
+    ///
+    /// Backends must be added with `add_backend` or `add_disk_alternate`
+    /// before any objects can be read or written, and the database can be
+    /// used by a repository with `Repository::set_odb`.
+    pub fn new() -> Result<Odb<'static>, Error> {
+        ::init();
+        let mut out = ptr::null_mut();
+        unsafe {
+            try_call!(raw::git_odb_new(&mut out));
+            Ok(Binding::from_raw(out))
+        }
+    }
+}
+
 impl<'repo> Odb<'repo> {
+    /// Determine the object id of a buffer of data as if it were written to
+    /// an object database with the given type.
+    ///
+    /// No object database is required and nothing is written.
+    pub fn hash(data: &[u8], kind: ObjectType) -> Result<Oid, Error> {
+        ::init();
+        let mut raw = raw::git_oid { id: [0; raw::GIT_OID_RAWSZ] };
+        unsafe {
+            try_call!(raw::git_odb_hash(&mut raw,
+                                        data.as_ptr() as *const c_void,
+                                        data.len(),
+                                        kind));
+            Ok(Binding::from_raw(&raw as *const _))
+        }
+    }
+
+    /// Determine the object id of the contents of a file as if it were
+    /// written to an object database with the given type.
+    ///
+    /// Unlike `Repository::hashfile` no filters are applied to the contents.
+    pub fn hashfile(path: &Path, kind: ObjectType) -> Result<Oid, Error> {
+        ::init();
+        let path = try!(path.into_c_string());
+        let mut raw = raw::git_oid { id: [0; raw::GIT_OID_RAWSZ] };
+        unsafe {
+            try_call!(raw::git_odb_hashfile(&mut raw, path, kind));
+            Ok(Binding::from_raw(&raw as *const _))
+        }
+    }
+
     /// Create object database reading stream
     ///
+    /// Along with the stream, the size and type of the object are returned,
+    /// as read by `read_header`.
+    ///
     /// Note that most backends do not support streaming reads because they store their objects as compressed/delta'ed blobs.
-    pub fn reader(&self, oid: Oid) -> Result<OdbReader, Error> {
+    pub fn reader(&self, oid: Oid)
+                  -> Result<(OdbReader, usize, ObjectType), Error> {
+        let (size, otype) = try!(self.read_header(oid));
         let mut out = ptr::null_mut();
         unsafe {
             try_call!(raw::git_odb_open_rstream(&mut out, self.raw, oid.raw()));
-            Ok(OdbReader::from_raw(out))
+            Ok((OdbReader::from_raw(out), size, otype))
+        }
+    }
+
+    /// Read the size and type of an object without reading its contents.
+    ///
+    /// This is cheap for loose and packed objects, as only the object header
+    /// is decoded. Backends which cannot read headers on their own fall back
+    /// to reading the whole object.
+    pub fn read_header(&self, oid: Oid) -> Result<(usize, ObjectType), Error> {
+        let mut size: size_t = 0;
+        let mut otype: raw::git_otype = ObjectType::Any.raw();
+        unsafe {
+            try_call!(raw::git_odb_read_header(&mut size, &mut otype, self.raw,
+                                               oid.raw()));
+            match ObjectType::from_raw(otype) {
+                Some(otype) => Ok((size as usize, otype)),
+                None => Err(Error::from_str("unknown object type")),
+            }
         }
     }
 
@@ -54,6 +127,66 @@ impl<'repo> Odb<'repo> {
         }
     }
 
+    /// Checks whether an object with the given id exists in the object
+    /// database, without reading the object itself.
+    pub fn exists(&self, oid: Oid) -> bool {
+        unsafe { raw::git_odb_exists(self.raw, oid.raw()) == 1 }
+    }
+
+    /// Get the number of backends, including alternates, configured for this
+    /// object database.
+    pub fn num_backends(&self) -> usize {
+        unsafe { raw::git_odb_num_backends(self.raw) as usize }
+    }
+
+    /// Add an on-disk alternate to this object database.
+    ///
+    /// The `path` is the `objects` directory of another repository, whose
+    /// loose objects and packfiles are then searched for objects not found in
+    /// this database. Alternates are only used for reading; new objects are
+    /// never written to them. The alternate is not recorded in
+    /// `objects/info/alternates` and only lasts as long as this database.
+    pub fn add_disk_alternate(&self, path: &Path) -> Result<(), Error> {
+        let path = try!(path.into_c_string());
+        unsafe {
+            try_call!(raw::git_odb_add_disk_alternate(self.raw, path));
+        }
+        Ok(())
+    }
+
+    /// Add a custom backend to this object database.
+    ///
+    /// Backends are searched for objects in order of decreasing `priority`,
+    /// and new objects are written to the first backend that accepts them.
+    /// libgit2's own loose and packed backends have priorities 1 and 2. The
+    /// backend is owned by the database from then on, and is dropped when
+    /// the database and every repository using it are freed.
+    pub fn add_backend<B>(&self, backend: B, priority: i32) -> Result<(), Error>
+        where B: OdbBackend
+    {
+        let mut raw = Box::new(RawOdbBackend {
+            raw: unsafe { mem::zeroed() },
+            obj: Box::new(backend),
+        });
+        unsafe {
+            try_call!(raw::git_odb_init_backend(&mut raw.raw,
+                                                raw::GIT_ODB_BACKEND_VERSION));
+            raw.raw.read = Some(backend_read);
+            raw.raw.read_prefix = Some(backend_read_prefix);
+            raw.raw.read_header = Some(backend_read_header);
+            raw.raw.write = Some(backend_write);
+            raw.raw.exists = Some(backend_exists);
+            raw.raw.refresh = Some(backend_refresh);
+            raw.raw.foreach = Some(backend_foreach);
+            raw.raw.free = Some(backend_free);
+            let ptr = &mut raw.raw as *mut raw::git_odb_backend;
+            try_call!(raw::git_odb_add_backend(self.raw, ptr,
+                                               priority as c_int));
+            mem::forget(raw); // ownership transferred to the odb
+        }
+        Ok(())
+    }
+
     /// Iterate over all objects in the object database
     pub fn foreach<C>(&self, mut callback: C) -> Result<(), Error>
         where C: FnMut(&Oid) -> bool
@@ -101,7 +234,7 @@ impl<'repo> io::Read for OdbReader<'repo> {
             if res < 0 {
                 Err(io::Error::new(io::ErrorKind::Other, "Read error"))
             } else {
-                Ok(len)
+                Ok(res as usize)
             }
         }
     }
@@ -184,11 +317,260 @@ extern fn foreach_cb(id: *const raw::git_oid,
     }).unwrap_or(1)
 }
 
+/// A custom storage backend for an object database, added with
+/// `Odb::add_backend`.
+///
+/// Errors returned by a backend are passed on to libgit2, which acts on their
+/// codes: a `NotFound` error from `read` makes libgit2 try the next backend,
+/// so it must be used for missing objects. Errors can be created with
+/// `Error::new`.
+pub trait OdbBackend: Send + 'static {
+    /// Read the type and contents of the object `id`.
+    fn read(&self, id: Oid) -> Result<(ObjectType, Vec<u8>), Error>;
+
+    /// Read the object whose id starts with the first `len` hexadecimal
+    /// digits of `short_id`, returning its full id, type and contents.
+    ///
+    /// An `Ambiguous` error should be returned if several objects match.
+    fn read_prefix(&self, short_id: Oid, len: usize)
+                   -> Result<(Oid, ObjectType, Vec<u8>), Error>;
+
+    /// Read the type and size of the object `id`.
+    ///
+    /// The default implementation reads the whole object.
+    fn read_header(&self, id: Oid) -> Result<(ObjectType, usize), Error> {
+        self.read(id).map(|(kind, data)| (kind, data.len()))
+    }
+
+    /// Store an object, whose id has already been computed from its type and
+    /// contents.
+    fn write(&self, id: Oid, kind: ObjectType, data: &[u8])
+             -> Result<(), Error>;
+
+    /// Check whether the object `id` is stored in this backend.
+    fn exists(&self, id: Oid) -> bool;
+
+    /// Refresh any cached state, e.g. a listing of the objects available,
+    /// after an object was not found.
+    ///
+    /// The default implementation does nothing.
+    fn refresh(&self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Call `cb` with the id of every object stored in this backend, stopping
+    /// early if it returns `false`.
+    fn foreach(&self, cb: &mut FnMut(Oid) -> bool) -> Result<(), Error>;
+}
+
+/// Instance of a `git_odb_backend`, must use `#[repr(C)]` to ensure that the
+/// C fields come first.
+#[repr(C)]
+struct RawOdbBackend {
+    raw: raw::git_odb_backend,
+    obj: Box<OdbBackend>,
+}
+
+unsafe fn backend<'a>(raw: *mut raw::git_odb_backend) -> &'a OdbBackend {
+    &*(*(raw as *mut RawOdbBackend)).obj
+}
+
+// Report a backend's error to libgit2, returning the code to hand back.
+fn backend_result(ret: Option<Result<(), Error>>) -> c_int {
+    match ret {
+        Some(Ok(())) => 0,
+        Some(Err(e)) => unsafe {
+            let msg = CString::new(e.message()).unwrap_or_else(|_| {
+                CString::new("odb backend error").unwrap()
+            });
+            raw::giterr_set_str(e.raw_class() as c_int, msg.as_ptr());
+            e.raw_code() as c_int
+        },
+        None => -1,
+    }
+}
+
+// Copy object data into memory that libgit2 frees once it's done with it.
+unsafe fn backend_data(raw: *mut raw::git_odb_backend, data: &[u8],
+                       out: *mut *mut c_void, len: *mut size_t)
+                       -> Result<(), Error> {
+    let buf = raw::git_odb_backend_malloc(raw, data.len() as size_t);
+    if buf.is_null() {
+        return Err(Error::from_str("failed to allocate object data"))
+    }
+    ptr::copy_nonoverlapping(data.as_ptr(), buf as *mut u8, data.len());
+    *out = buf;
+    *len = data.len() as size_t;
+    Ok(())
+}
+
+extern fn backend_read(data: *mut *mut c_void,
+                       len: *mut size_t,
+                       kind: *mut raw::git_otype,
+                       raw: *mut raw::git_odb_backend,
+                       id: *const raw::git_oid) -> c_int {
+    backend_result(panic::wrap(|| -> Result<(), Error> {
+        unsafe {
+            let (k, contents) = try!(backend(raw).read(Binding::from_raw(id)));
+            try!(backend_data(raw, &contents, data, len));
+            *kind = k.raw();
+            Ok(())
+        }
+    }))
+}
+
+extern fn backend_read_prefix(out: *mut raw::git_oid,
+                              data: *mut *mut c_void,
+                              len: *mut size_t,
+                              kind: *mut raw::git_otype,
+                              raw: *mut raw::git_odb_backend,
+                              short_id: *const raw::git_oid,
+                              short_len: size_t) -> c_int {
+    backend_result(panic::wrap(|| -> Result<(), Error> {
+        unsafe {
+            let (id, k, contents) = try!(backend(raw).read_prefix(
+                Binding::from_raw(short_id), short_len as usize));
+            try!(backend_data(raw, &contents, data, len));
+            *out = *id.raw();
+            *kind = k.raw();
+            Ok(())
+        }
+    }))
+}
+
+extern fn backend_read_header(len: *mut size_t,
+                              kind: *mut raw::git_otype,
+                              raw: *mut raw::git_odb_backend,
+                              id: *const raw::git_oid) -> c_int {
+    backend_result(panic::wrap(|| -> Result<(), Error> {
+        unsafe {
+            let (k, size) = try!(backend(raw).read_header(
+                Binding::from_raw(id)));
+            *len = size as size_t;
+            *kind = k.raw();
+            Ok(())
+        }
+    }))
+}
+
+extern fn backend_write(raw: *mut raw::git_odb_backend,
+                        id: *const raw::git_oid,
+                        data: *const c_void,
+                        len: size_t,
+                        kind: raw::git_otype) -> c_int {
+    backend_result(panic::wrap(|| -> Result<(), Error> {
+        unsafe {
+            let kind = match ObjectType::from_raw(kind) {
+                Some(kind) => kind,
+                None => return Err(Error::from_str("invalid object type")),
+            };
+            let data = if len == 0 {
+                &[][..]
+            } else {
+                slice::from_raw_parts(data as *const u8, len as usize)
+            };
+            backend(raw).write(Binding::from_raw(id), kind, data)
+        }
+    }))
+}
+
+extern fn backend_exists(raw: *mut raw::git_odb_backend,
+                         id: *const raw::git_oid) -> c_int {
+    panic::wrap(|| unsafe {
+        backend(raw).exists(Binding::from_raw(id)) as c_int
+    }).unwrap_or(0)
+}
+
+extern fn backend_refresh(raw: *mut raw::git_odb_backend) -> c_int {
+    backend_result(panic::wrap(|| unsafe { backend(raw).refresh() }))
+}
+
+extern fn backend_foreach(raw: *mut raw::git_odb_backend,
+                          cb: raw::git_odb_foreach_cb,
+                          payload: *mut c_void) -> c_int {
+    let ret = panic::wrap(|| unsafe {
+        let mut code = 0;
+        let res = backend(raw).foreach(&mut |id| {
+            code = cb(id.raw(), payload);
+            code == 0
+        });
+        res.map(|()| code)
+    });
+    match ret {
+        // zero, or the code the callback stopped the iteration with
+        Some(Ok(code)) => code,
+        ret => backend_result(ret.map(|res| res.map(|_| ()))),
+    }
+}
+
+extern fn backend_free(raw: *mut raw::git_odb_backend) {
+    let _ = panic::wrap(|| unsafe {
+        drop(Box::from_raw(raw as *mut RawOdbBackend));
+    });
+}
+
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
+    use std::fs::File;
     use std::io::prelude::*;
+    use std::sync::{Arc, Mutex};
     use tempdir::TempDir;
-    use {Repository, ObjectType};
+    use {Repository, ObjectType, Odb, OdbBackend, Oid};
+    use {Error, ErrorClass, ErrorCode};
+
+    type Objects = Arc<Mutex<HashMap<Oid, (ObjectType, Vec<u8>)>>>;
+
+    struct Memory(Objects);
+
+    fn not_found() -> Error {
+        Error::new(ErrorCode::NotFound, ErrorClass::Odb, "object not found")
+    }
+
+    impl OdbBackend for Memory {
+        fn read(&self, id: Oid) -> Result<(ObjectType, Vec<u8>), Error> {
+            self.0.lock().unwrap().get(&id).cloned().ok_or_else(not_found)
+        }
+
+        fn read_prefix(&self, short_id: Oid, len: usize)
+                       -> Result<(Oid, ObjectType, Vec<u8>), Error> {
+            let prefix = short_id.to_string()[..len].to_string();
+            let objects = self.0.lock().unwrap();
+            let mut matches = objects.iter().filter(|&(id, _)| {
+                id.to_string().starts_with(&prefix)
+            });
+            match (matches.next(), matches.next()) {
+                (Some((id, &(kind, ref data))), None) => {
+                    Ok((*id, kind, data.clone()))
+                }
+                (Some(..), Some(..)) => {
+                    Err(Error::new(ErrorCode::Ambiguous, ErrorClass::Odb,
+                                   "ambiguous prefix"))
+                }
+                (None, _) => Err(not_found()),
+            }
+        }
+
+        fn write(&self, id: Oid, kind: ObjectType, data: &[u8])
+                 -> Result<(), Error> {
+            self.0.lock().unwrap().insert(id, (kind, data.to_vec()));
+            Ok(())
+        }
+
+        fn exists(&self, id: Oid) -> bool {
+            self.0.lock().unwrap().contains_key(&id)
+        }
+
+        fn foreach(&self, cb: &mut FnMut(Oid) -> bool) -> Result<(), Error> {
+            let ids = self.0.lock().unwrap().keys().cloned().collect::<Vec<_>>();
+            for id in ids {
+                if !cb(id) {
+                    break
+                }
+            }
+            Ok(())
+        }
+    }
 
     #[test]
     #[ignore]
@@ -198,8 +580,10 @@ mod tests {
         let dat = [4, 3, 5, 6, 9];
         let id = repo.blob(&dat).unwrap();
         let db = repo.odb().unwrap();
-        let mut rs = db.reader(id).unwrap();
-        let mut buf = [3];
+        let (mut rs, size, otype) = db.reader(id).unwrap();
+        assert_eq!(size, dat.len());
+        assert_eq!(otype, ObjectType::Blob);
+        let mut buf = [0; 3];
         let rl = rs.read(&mut buf).unwrap();
         assert_eq!(rl, 3);
         assert_eq!(buf, &dat[0..3]);
@@ -208,6 +592,19 @@ mod tests {
         assert_eq!(buf, &dat[3..5]);
     }
 
+    #[test]
+    fn hash() {
+        let td = TempDir::new("test").unwrap();
+        let repo = Repository::init(td.path()).unwrap();
+        let dat = [4, 3, 5, 6, 9];
+        let id = Odb::hash(&dat, ObjectType::Blob).unwrap();
+        assert_eq!(repo.blob(&dat).unwrap(), id);
+
+        let path = td.path().join("foo");
+        File::create(&path).unwrap().write_all(&dat).unwrap();
+        assert_eq!(Odb::hashfile(&path, ObjectType::Blob).unwrap(), id);
+    }
+
     #[test]
     fn writer() {
         let td = TempDir::new("test").unwrap();
@@ -223,4 +620,105 @@ mod tests {
         let blob = repo.find_blob(id).unwrap();
         assert_eq!(blob.content(), dat);
     }
+
+    #[test]
+    fn exists() {
+        let td = TempDir::new("test").unwrap();
+        let repo = Repository::init(td.path()).unwrap();
+        let dat = [4, 3, 5, 6, 9];
+        let db = repo.odb().unwrap();
+        let id = Odb::hash(&dat, ObjectType::Blob).unwrap();
+        assert!(!db.exists(id));
+        assert!(!repo.contains(id));
+        assert_eq!(repo.blob(&dat).unwrap(), id);
+        assert!(db.exists(id));
+        assert!(repo.contains(id));
+    }
+
+    #[test]
+    fn read_header() {
+        let (_td, repo) = ::test::repo_init();
+        let db = repo.odb().unwrap();
+        let id = repo.blob(&[1; 100]).unwrap();
+        assert_eq!(db.read_header(id).unwrap(), (100, ObjectType::Blob));
+        let head = repo.head().unwrap().target().unwrap();
+        let (_, size, kind) = db.reader(head).unwrap();
+        assert_eq!(db.read_header(head).unwrap(), (size, kind));
+        assert_eq!(kind, ObjectType::Commit);
+
+        let missing = Oid::from_bytes(&[1; 20]).unwrap();
+        match db.read_header(missing) {
+            Ok(..) => panic!("read the header of a missing object"),
+            Err(e) => assert_eq!(e.code(), ErrorCode::NotFound),
+        };
+    }
+
+    #[test]
+    fn add_disk_alternate() {
+        let td = TempDir::new("test").unwrap();
+        let repo = Repository::init(td.path()).unwrap();
+        let td2 = TempDir::new("test").unwrap();
+        let other = Repository::init(td2.path()).unwrap();
+        let id = other.blob(b"shared").unwrap();
+
+        let db = repo.odb().unwrap();
+        let backends = db.num_backends();
+        assert!(backends > 0);
+        assert!(!db.exists(id));
+        db.add_disk_alternate(&other.path().join("objects")).unwrap();
+        assert!(db.num_backends() > backends);
+        assert!(db.exists(id));
+        // the repository shares its object database, so it sees the alternate
+        assert_eq!(repo.find_blob(id).unwrap().content(), b"shared");
+        assert!(!repo.path().join("objects/info/alternates").exists());
+    }
+
+    #[test]
+    fn writer_size_mismatch() {
+        let td = TempDir::new("test").unwrap();
+        let repo = Repository::init(td.path()).unwrap();
+        let dat = [4, 3, 5, 6, 9];
+        let db = repo.odb().unwrap();
+
+        let mut ws = db.writer(dat.len(), ObjectType::Blob).unwrap();
+        ws.write_all(&dat[0..3]).unwrap();
+        assert!(ws.finalize().is_err());
+
+        let mut ws = db.writer(3, ObjectType::Blob).unwrap();
+        assert!(ws.write(&dat).is_err());
+    }
+
+    #[test]
+    fn custom_backend() {
+        let td = TempDir::new("test").unwrap();
+        let repo = Repository::init(td.path()).unwrap();
+        let objects = Objects::default();
+        let db = Odb::new().unwrap();
+        assert_eq!(db.num_backends(), 0);
+        db.add_backend(Memory(objects.clone()), 10).unwrap();
+        assert_eq!(db.num_backends(), 1);
+        repo.set_odb(&db);
+
+        let id = repo.blob(b"in memory").unwrap();
+        assert_eq!(id, Odb::hash(b"in memory", ObjectType::Blob).unwrap());
+        assert!(objects.lock().unwrap().contains_key(&id));
+        assert!(db.exists(id));
+        assert!(!td.path().join(".git/objects").join(&id.to_string()[..2])
+                   .exists());
+        assert_eq!(repo.find_blob(id).unwrap().content(), b"in memory");
+        assert_eq!(db.read_header(id).unwrap(), (9, ObjectType::Blob));
+
+        let short = &id.to_string()[..7];
+        assert_eq!(repo.revparse_single(short).unwrap().id(), id);
+
+        let mut ids = Vec::new();
+        db.foreach(|id| { ids.push(*id); true }).unwrap();
+        assert_eq!(ids, vec![id]);
+
+        let missing = Oid::from_bytes(&[1; 20]).unwrap();
+        match repo.find_blob(missing) {
+            Ok(..) => panic!("found a missing blob"),
+            Err(e) => assert_eq!(e.code(), ErrorCode::NotFound),
+        };
+    }
 }
@@ -0,0 +1,220 @@
+//! Bindings to libgit2's global options, set with `git_libgit2_opts`.
+//!
+//! These options are process-wide and affect every repository opened by
+//! libgit2, so they are usually set once at startup.
+
+use std::ffi::CString;
+use std::path::Path;
+use libc::{c_char, c_int, size_t, ssize_t};
+
+use {raw, call, Buf, ConfigLevel, Error, IntoCString, ObjectType};
+use util::Binding;
+
+/// Set the directories searched for the configuration files of `level`.
+///
+/// Several directories may be given by separating them with the platform's
+/// path list separator (`:` or `;`), and the string `$PATH` is replaced by the
+/// current search path. Only the `System`, `XDG`, `Global` and `ProgramData`
+/// levels have search paths. Repositories opened afterwards use the new path.
+pub fn set_search_path<P: IntoCString>(level: ConfigLevel, path: P)
+                                       -> Result<(), Error> {
+    ::init();
+    let path = try!(path.into_c_string());
+    unsafe {
+        try!(call::try(raw::git_libgit2_opts(
+            raw::GIT_OPT_SET_SEARCH_PATH as c_int,
+            call::convert::<raw::git_config_level_t, _>(&level) as c_int,
+            path.as_ptr())));
+    }
+    Ok(())
+}
+
+/// Reset the directories searched for the configuration files of `level` to
+/// their defaults, which are derived from the environment, e.g. `$HOME`.
+pub fn reset_search_path(level: ConfigLevel) -> Result<(), Error> {
+    ::init();
+    unsafe {
+        try!(call::try(raw::git_libgit2_opts(
+            raw::GIT_OPT_SET_SEARCH_PATH as c_int,
+            call::convert::<raw::git_config_level_t, _>(&level) as c_int,
+            0 as *const c_char)));
+    }
+    Ok(())
+}
+
+/// Get the directories searched for the configuration files of `level`,
+/// separated by the platform's path list separator.
+///
+/// This is where libgit2 looks for e.g. the `.gitconfig` of the `Global`
+/// level, which is useful when diagnosing why a file isn't being read. An
+/// error is returned for levels without a search path, or if the path isn't
+/// valid utf-8.
+pub fn get_search_path(level: ConfigLevel) -> Result<String, Error> {
+    ::init();
+    let buf = Buf::new();
+    unsafe {
+        try!(call::try(raw::git_libgit2_opts(
+            raw::GIT_OPT_GET_SEARCH_PATH as c_int,
+            call::convert::<raw::git_config_level_t, _>(&level) as c_int,
+            buf.raw())));
+    }
+    match buf.as_str() {
+        Some(path) => Ok(path.to_string()),
+        None => Err(Error::from_str("search path is not valid utf-8")),
+    }
+}
+
+/// Set the locations from which libgit2 loads the trusted certificates used
+/// to verify TLS connections.
+///
+/// `file` is a file of concatenated PEM certificates and `dir` a directory of
+/// certificates named by their hashes, as produced by OpenSSL's `c_rehash`.
+/// Either may be `None`, but not both. This fails if libgit2 was built
+/// without a TLS backend that supports certificate locations.
+pub fn set_ssl_cert_locations(file: Option<&Path>, dir: Option<&Path>)
+                              -> Result<(), Error> {
+    ::init();
+    let file = match file {
+        Some(file) => Some(try!(file.into_c_string())),
+        None => None,
+    };
+    let dir = match dir {
+        Some(dir) => Some(try!(dir.into_c_string())),
+        None => None,
+    };
+    unsafe {
+        try!(call::try(raw::git_libgit2_opts(
+            raw::GIT_OPT_SET_SSL_CERT_LOCATIONS as c_int,
+            call::convert::<*const c_char, _>(&file),
+            call::convert::<*const c_char, _>(&dir))));
+    }
+    Ok(())
+}
+
+/// Set the value libgit2 reports as its user agent in HTTP requests.
+///
+/// The given value is prefixed with `git/1.0 (compatible; ...)` by libgit2.
+pub fn set_user_agent(user_agent: &str) -> Result<(), Error> {
+    ::init();
+    let user_agent = try!(CString::new(user_agent));
+    unsafe {
+        try!(call::try(raw::git_libgit2_opts(
+            raw::GIT_OPT_SET_USER_AGENT as c_int, user_agent.as_ptr())));
+    }
+    Ok(())
+}
+
+/// Control whether objects are validated when they are created, e.g. that
+/// the tree and parents of a new commit exist in the object database.
+///
+/// Validation is enabled by default. Disabling it makes creating objects
+/// cheaper but allows corrupt objects to be written.
+pub fn enable_strict_object_creation(enabled: bool) -> Result<(), Error> {
+    ::init();
+    unsafe {
+        try!(call::try(raw::git_libgit2_opts(
+            raw::GIT_OPT_ENABLE_STRICT_OBJECT_CREATION as c_int,
+            enabled as c_int)));
+    }
+    Ok(())
+}
+
+/// Set the maximum amount of memory, in bytes, that may be used by libgit2's
+/// cache of parsed objects.
+///
+/// This is a soft limit: the cache is pruned when it is exceeded. The default
+/// is 256MB.
+pub fn set_cache_max_size(bytes: usize) -> Result<(), Error> {
+    ::init();
+    unsafe {
+        try!(call::try(raw::git_libgit2_opts(
+            raw::GIT_OPT_SET_CACHE_MAX_SIZE as c_int, bytes as ssize_t)));
+    }
+    Ok(())
+}
+
+/// Set the largest size, in bytes, of an object of type `kind` that will be
+/// kept in the object cache.
+///
+/// Objects larger than this are never cached, and a limit of `0` disables
+/// caching of the type altogether. By default only commits, trees and tags are
+/// cached.
+pub fn set_cache_object_limit(kind: ObjectType, size: usize)
+                              -> Result<(), Error> {
+    ::init();
+    unsafe {
+        try!(call::try(raw::git_libgit2_opts(
+            raw::GIT_OPT_SET_CACHE_OBJECT_LIMIT as c_int,
+            kind.raw() as c_int, size as size_t)));
+    }
+    Ok(())
+}
+
+/// Get the memory currently used by the object cache and the maximum it is
+/// allowed to use, both in bytes.
+pub fn cached_memory() -> Result<(usize, usize), Error> {
+    ::init();
+    let mut current: ssize_t = 0;
+    let mut allowed: ssize_t = 0;
+    unsafe {
+        try!(call::try(raw::git_libgit2_opts(
+            raw::GIT_OPT_GET_CACHED_MEMORY as c_int,
+            &mut current as *mut ssize_t, &mut allowed as *mut ssize_t)));
+    }
+    Ok((current as usize, allowed as usize))
+}
+
+#[cfg(test)]
+mod tests {
+    use tempdir::TempDir;
+    use {ConfigLevel, Oid, ObjectType};
+
+    #[test]
+    fn search_path() {
+        let td = TempDir::new("test").unwrap();
+        let old = super::get_search_path(ConfigLevel::System).unwrap();
+        super::set_search_path(ConfigLevel::System, td.path()).unwrap();
+        let new = super::get_search_path(ConfigLevel::System).unwrap();
+        assert_eq!(Some(&new[..]), td.path().to_str());
+        super::set_search_path(ConfigLevel::System, &old[..]).unwrap();
+        let restored = super::get_search_path(ConfigLevel::System).unwrap();
+        assert_eq!(restored, old);
+
+        for level in &[ConfigLevel::XDG, ConfigLevel::Global,
+                       ConfigLevel::ProgramData] {
+            assert!(super::get_search_path(*level).is_ok());
+        }
+        assert!(super::get_search_path(ConfigLevel::Local).is_err());
+        assert!(super::set_search_path(ConfigLevel::Local, td.path()).is_err());
+    }
+
+    #[test]
+    fn strict_object_creation() {
+        let (_td, repo) = ::test::repo_init();
+        // strict creation is the default, so this doesn't affect other tests
+        super::enable_strict_object_creation(true).unwrap();
+        let mut builder = repo.treebuilder(None).unwrap();
+        let missing = Oid::from_bytes(&[1; 20]).unwrap();
+        assert!(builder.insert("foo", missing, 0o100644).is_err());
+    }
+
+    #[test]
+    fn user_agent() {
+        super::set_user_agent("git2-rs tests").unwrap();
+        assert!(super::set_user_agent("nul\0").is_err());
+    }
+
+    #[test]
+    fn cache() {
+        let (_, allowed) = super::cached_memory().unwrap();
+        super::set_cache_max_size(allowed + 1024).unwrap();
+        let (current, new_allowed) = super::cached_memory().unwrap();
+        assert_eq!(new_allowed, allowed + 1024);
+        assert!(current <= new_allowed);
+        super::set_cache_max_size(allowed).unwrap();
+
+        // 4096 is the default limit for commits
+        super::set_cache_object_limit(ObjectType::Commit, 4096).unwrap();
+        assert!(super::set_cache_object_limit(ObjectType::Any, 0).is_err());
+    }
+}
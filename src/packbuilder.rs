@@ -349,6 +349,22 @@ mod tests {
         assert_eq!(&buf[0..12], &*pack_header(3));
     }
 
+    #[test]
+    fn insert_walk_write_buf() {
+        let (_td, repo) = ::test::repo_init();
+        let mut builder = t!(repo.packbuilder());
+        let mut buf = Buf::new();
+        commit(&repo);
+        let mut walk = t!(repo.revwalk());
+        t!(walk.push_head());
+        // will insert both commits, their trees and the blob, 5 objects
+        t!(builder.insert_walk(&mut walk));
+        assert_eq!(builder.object_count(), 5);
+        t!(builder.write_buf(&mut buf));
+        // Just check that the correct number of objects are written
+        assert_eq!(&buf[0..12], &*pack_header(5));
+    }
+
     #[test]
     fn progress_callback() {
         let mut progress_called = false;
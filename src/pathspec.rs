@@ -298,4 +298,29 @@ mod tests {
         assert_eq!(list.entries().len(), 1);
         assert_eq!(list.entries().next(), Some("a".as_bytes()));
     }
+
+    #[test]
+    fn match_tree() {
+        let (_td, repo) = ::test::repo_init();
+        let blob = repo.blob(b"foo").unwrap();
+        let mut dir = repo.treebuilder(None).unwrap();
+        dir.insert("b", blob, 0o100644).unwrap();
+        let dir = dir.write().unwrap();
+        let mut root = repo.treebuilder(None).unwrap();
+        root.insert("a", blob, 0o100644).unwrap();
+        root.insert("dir", dir, 0o040000).unwrap();
+        let tree = repo.find_tree(root.write().unwrap()).unwrap();
+
+        let ps = Pathspec::new(["dir", "missing"].iter()).unwrap();
+        let list = ps.match_tree(&tree, ::PATHSPEC_FIND_FAILURES).unwrap();
+        assert_eq!(list.entries().collect::<Vec<_>>(), [&b"dir/b"[..]]);
+        assert_eq!(list.failed_entries().collect::<Vec<_>>(), [&b"missing"[..]]);
+
+        let list = ps.match_tree(&tree, PATHSPEC_DEFAULT).unwrap();
+        assert_eq!(list.entries().len(), 1);
+        assert_eq!(list.failed_entries().len(), 0);
+
+        let ps = Pathspec::new(["missing"].iter()).unwrap();
+        assert!(ps.match_tree(&tree, ::PATHSPEC_NO_MATCH_ERROR).is_err());
+    }
 }
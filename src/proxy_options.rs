@@ -1,8 +1,10 @@
 use std::ffi::CString;
 use std::marker;
 use std::ptr;
+use libc::{c_char, c_int, c_uint, c_void};
 
-use raw;
+use {raw, Cred, CredentialType, Credentials, Error};
+use remote_callbacks;
 use util::Binding;
 
 /// Options which can be specified to various fetch operations.
@@ -10,6 +12,7 @@ use util::Binding;
 pub struct ProxyOptions<'a> {
     url: Option<CString>,
     proxy_kind: raw::git_proxy_t,
+    credentials: Option<Box<Box<Credentials<'a>>>>,
     _marker: marker::PhantomData<&'a i32>,
 }
 
@@ -35,6 +38,16 @@ impl<'a> ProxyOptions<'a> {
         self.url = Some(CString::new(url).unwrap());
         self
     }
+
+    /// The callback through which to fetch credentials if the proxy requires
+    /// authentication.
+    pub fn credentials<F>(&mut self, cb: F) -> &mut Self
+                          where F: FnMut(&str, Option<&str>, CredentialType)
+                                         -> Result<Cred, Error> + 'a
+    {
+        self.credentials = Some(Box::new(Box::new(cb)));
+        self
+    }
 }
 
 impl<'a> Binding for ProxyOptions<'a> {
@@ -48,9 +61,25 @@ impl<'a> Binding for ProxyOptions<'a> {
             version: raw::GIT_PROXY_OPTIONS_VERSION,
             kind: self.proxy_kind,
             url: self.url.as_ref().map(|s| s.as_ptr()).unwrap_or(ptr::null()),
-            credentials: None,
+            credentials: self.credentials.as_ref().map(|_| {
+                credentials_cb as raw::git_cred_acquire_cb
+            }),
             certificate_check: None,
-            payload: ptr::null_mut(),
+            payload: match self.credentials {
+                Some(ref c) => &**c as *const Box<Credentials> as *mut c_void,
+                None => ptr::null_mut(),
+            },
         }
     }
 }
+
+extern fn credentials_cb(ret: *mut *mut raw::git_cred,
+                         url: *const c_char,
+                         username_from_url: *const c_char,
+                         allowed_types: c_uint,
+                         payload: *mut c_void) -> c_int {
+    unsafe {
+        remote_callbacks::invoke_credentials(payload, ret, url,
+                                             username_from_url, allowed_types)
+    }
+}
@@ -0,0 +1,371 @@
+use std::ffi::CString;
+use std::marker;
+use std::mem;
+use std::ptr;
+use std::str;
+
+use {raw, Error, Index, MergeOptions, Oid, Signature};
+use build::CheckoutBuilder;
+use util::Binding;
+
+/// Representation of a rebase in progress.
+///
+/// A rebase is started with `Repository::rebase` or resumed with
+/// `Repository::open_rebase`, and its operations are then applied one by one
+/// with `next`.
+pub struct Rebase<'repo> {
+    raw: *mut raw::git_rebase,
+    _marker: marker::PhantomData<&'repo raw::git_rebase>,
+}
+
+/// A single operation of a rebase, i.e. a commit which is to be applied.
+pub struct RebaseOperation<'rebase> {
+    raw: *const raw::git_rebase_operation,
+    _marker: marker::PhantomData<Rebase<'rebase>>,
+}
+
+/// The kind of action a `RebaseOperation` performs.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RebaseOperationType {
+    /// The given commit is to be cherry-picked.
+    Pick,
+    /// The given commit is to be cherry-picked, with the user prompted to
+    /// edit the commit message.
+    Reword,
+    /// The given commit is to be cherry-picked, with the user given a chance
+    /// to amend the result.
+    Edit,
+    /// The given commit is to be squashed into the previous commit, with the
+    /// commit messages combined.
+    Squash,
+    /// The given commit is to be squashed into the previous commit, keeping
+    /// only the previous commit's message.
+    Fixup,
+    /// No commit is cherry-picked; the command given by `exec` is to be run.
+    Exec,
+}
+
+/// Options which can be used to configure a rebase.
+pub struct RebaseOptions<'cb> {
+    raw: raw::git_rebase_options,
+    rewrite_notes_ref: Option<CString>,
+    merge_options: Option<MergeOptions>,
+    checkout_options: Option<CheckoutBuilder<'cb>>,
+}
+
+impl<'cb> Default for RebaseOptions<'cb> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'cb> RebaseOptions<'cb> {
+    /// Creates a default set of rebase options.
+    pub fn new() -> RebaseOptions<'cb> {
+        let mut opts = RebaseOptions {
+            raw: unsafe { mem::zeroed() },
+            rewrite_notes_ref: None,
+            merge_options: None,
+            checkout_options: None,
+        };
+        assert_eq!(unsafe {
+            raw::git_rebase_init_options(&mut opts.raw,
+                                         raw::GIT_REBASE_OPTIONS_VERSION)
+        }, 0);
+        opts
+    }
+
+    /// Whether the rebase should be quiet.
+    ///
+    /// This is provided for interoperability with other git tools and has no
+    /// effect on libgit2 itself.
+    pub fn quiet(&mut self, quiet: bool) -> &mut RebaseOptions<'cb> {
+        self.raw.quiet = quiet as i32;
+        self
+    }
+
+    /// Whether to perform the rebase in memory, without touching the working
+    /// directory or writing any rebase state to disk.
+    ///
+    /// Conflicts must then be resolved through `Rebase::inmemory_index`.
+    pub fn inmemory(&mut self, inmemory: bool) -> &mut RebaseOptions<'cb> {
+        self.raw.inmemory = inmemory as i32;
+        self
+    }
+
+    /// The name of the notes reference used to rewrite notes for rebased
+    /// commits when finishing the rebase.
+    ///
+    /// If not set, the `notes.rewriteRef` configuration is used.
+    pub fn rewrite_notes_ref(&mut self, rewrite_notes_ref: &str)
+                             -> &mut RebaseOptions<'cb> {
+        self.rewrite_notes_ref = Some(CString::new(rewrite_notes_ref).unwrap());
+        self
+    }
+
+    /// Options to control how trees are merged during each operation.
+    pub fn merge_options(&mut self, opts: MergeOptions)
+                         -> &mut RebaseOptions<'cb> {
+        self.merge_options = Some(opts);
+        self
+    }
+
+    /// Options to control how files are written during `Repository::rebase`,
+    /// each operation and `Rebase::abort`.
+    pub fn checkout_options(&mut self, opts: CheckoutBuilder<'cb>)
+                            -> &mut RebaseOptions<'cb> {
+        self.checkout_options = Some(opts);
+        self
+    }
+
+    /// Acquire a pointer to the underlying raw options.
+    ///
+    /// This function is unsafe as the pointer is only valid so long as this
+    /// structure is not moved, modified, or used elsewhere.
+    pub unsafe fn raw(&mut self) -> *const raw::git_rebase_options {
+        self.raw.rewrite_notes_ref = ::call::convert(&self.rewrite_notes_ref);
+        if let Some(ref opts) = self.merge_options {
+            ptr::copy_nonoverlapping(opts.raw(), &mut self.raw.merge_options,
+                                     1);
+        }
+        if let Some(ref mut opts) = self.checkout_options {
+            opts.configure(&mut self.raw.checkout_options);
+        }
+        &self.raw as *const _
+    }
+}
+
+impl<'repo> Rebase<'repo> {
+    /// Get the number of operations in this rebase.
+    pub fn len(&self) -> usize {
+        unsafe { raw::git_rebase_operation_entrycount(self.raw) }
+    }
+
+    /// Return `true` if this rebase has no operations.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get the index of the operation currently being applied, or `None` if
+    /// no operation has been applied yet.
+    pub fn operation_current(&self) -> Option<usize> {
+        match unsafe { raw::git_rebase_operation_current(self.raw) } {
+            n if n == !0 => None,
+            n => Some(n),
+        }
+    }
+
+    /// Get the operation at the given index.
+    pub fn nth(&mut self, n: usize) -> Option<RebaseOperation> {
+        unsafe {
+            let op = raw::git_rebase_operation_byindex(self.raw, n);
+            if op.is_null() {
+                None
+            } else {
+                Some(Binding::from_raw(op as *const _))
+            }
+        }
+    }
+
+    /// Apply the next operation of the rebase, returning it.
+    ///
+    /// The result of the operation is written to the index and, unless the
+    /// rebase is in memory, the working directory. Any conflicts should be
+    /// resolved before calling `commit`. Returns `None` once all operations
+    /// have been applied.
+    ///
+    /// The operation is owned by the rebase, so it borrows it and must be
+    /// dropped before the rebase is used again.
+    pub fn next(&mut self) -> Option<Result<RebaseOperation, Error>> {
+        let mut out = ptr::null_mut();
+        unsafe {
+            try_call_iter!(raw::git_rebase_next(&mut out, self.raw));
+            Some(Ok(Binding::from_raw(out as *const _)))
+        }
+    }
+
+    /// Get the index produced by the last operation of an in-memory rebase.
+    ///
+    /// This is the result of the merge, which may contain conflicts to be
+    /// resolved before calling `commit`.
+    pub fn inmemory_index(&mut self) -> Result<Index, Error> {
+        let mut index = ptr::null_mut();
+        unsafe {
+            try_call!(raw::git_rebase_inmemory_index(&mut index, self.raw));
+            Ok(Binding::from_raw(index))
+        }
+    }
+
+    /// Commit the current operation, creating a new commit with the changes
+    /// that were applied.
+    ///
+    /// The author and message default to those of the original commit when
+    /// `None`. Returns an error with the code `Applied` if the current
+    /// operation's changes have already been applied upstream.
+    pub fn commit(&mut self, author: Option<&Signature>, committer: &Signature,
+                  message: Option<&str>) -> Result<Oid, Error> {
+        let message = try!(::opt_cstr(message));
+        let mut id = raw::git_oid { id: [0; raw::GIT_OID_RAWSZ] };
+        unsafe {
+            try_call!(raw::git_rebase_commit(&mut id,
+                                             self.raw,
+                                             author.map(|a| a.raw()),
+                                             committer.raw(),
+                                             ptr::null(),
+                                             message));
+            Ok(Binding::from_raw(&id as *const _))
+        }
+    }
+
+    /// Abort this rebase, resetting the repository and working directory to
+    /// their state before the rebase began.
+    pub fn abort(&mut self) -> Result<(), Error> {
+        unsafe { try_call!(raw::git_rebase_abort(self.raw)); }
+        Ok(())
+    }
+
+    /// Finish this rebase once all operations have been applied.
+    ///
+    /// The `signature` is used as the committer of rewritten notes, defaulting
+    /// to the repository's signature when `None`.
+    pub fn finish(&mut self, signature: Option<&Signature>)
+                  -> Result<(), Error> {
+        unsafe {
+            try_call!(raw::git_rebase_finish(self.raw,
+                                             signature.map(|s| s.raw())));
+        }
+        Ok(())
+    }
+}
+
+impl<'repo> Binding for Rebase<'repo> {
+    type Raw = *mut raw::git_rebase;
+    unsafe fn from_raw(raw: *mut raw::git_rebase) -> Rebase<'repo> {
+        Rebase { raw: raw, _marker: marker::PhantomData }
+    }
+    fn raw(&self) -> *mut raw::git_rebase { self.raw }
+}
+
+impl<'repo> Drop for Rebase<'repo> {
+    fn drop(&mut self) {
+        unsafe { raw::git_rebase_free(self.raw) }
+    }
+}
+
+impl<'rebase> RebaseOperation<'rebase> {
+    /// Get the kind of this operation.
+    ///
+    /// Returns `None` if the kind is not known to this library.
+    pub fn kind(&self) -> Option<RebaseOperationType> {
+        match unsafe { (*self.raw).kind } {
+            raw::GIT_REBASE_OPERATION_PICK => Some(RebaseOperationType::Pick),
+            raw::GIT_REBASE_OPERATION_REWORD => Some(RebaseOperationType::Reword),
+            raw::GIT_REBASE_OPERATION_EDIT => Some(RebaseOperationType::Edit),
+            raw::GIT_REBASE_OPERATION_SQUASH => Some(RebaseOperationType::Squash),
+            raw::GIT_REBASE_OPERATION_FIXUP => Some(RebaseOperationType::Fixup),
+            raw::GIT_REBASE_OPERATION_EXEC => Some(RebaseOperationType::Exec),
+            _ => None,
+        }
+    }
+
+    /// Get the id of the commit being cherry-picked.
+    ///
+    /// This is zero for `Exec` operations.
+    pub fn id(&self) -> Oid {
+        unsafe { Binding::from_raw(&(*self.raw).id as *const _) }
+    }
+
+    /// Get the command to run for an `Exec` operation.
+    ///
+    /// Returns `None` for other operations, or if the command is not valid
+    /// utf-8.
+    pub fn exec(&self) -> Option<&str> {
+        unsafe {
+            ::opt_bytes(self, (*self.raw).exec)
+                .and_then(|s| str::from_utf8(s).ok())
+        }
+    }
+}
+
+impl<'rebase> Binding for RebaseOperation<'rebase> {
+    type Raw = *const raw::git_rebase_operation;
+    unsafe fn from_raw(raw: *const raw::git_rebase_operation)
+                       -> RebaseOperation<'rebase> {
+        RebaseOperation { raw: raw, _marker: marker::PhantomData }
+    }
+    fn raw(&self) -> *const raw::git_rebase_operation { self.raw }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use std::path::Path;
+
+    use {RebaseOperationType, RebaseOptions, Repository};
+
+    fn commit(repo: &Repository, path: &str, update_ref: &str) {
+        let root = repo.workdir().unwrap();
+        File::create(root.join(path)).unwrap().write_all(b"foo").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new(path)).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = repo.signature().unwrap();
+        let parent = repo.head().unwrap().target().unwrap();
+        let parent = repo.find_commit(parent).unwrap();
+        repo.commit(Some(update_ref), &sig, &sig, path, &tree,
+                    &[&parent]).unwrap();
+    }
+
+    #[test]
+    fn smoke() {
+        let (_td, repo) = ::test::repo_init();
+        let head = repo.head().unwrap().target().unwrap();
+        let base = repo.find_commit(head).unwrap();
+        repo.branch("upstream", &base, false).unwrap();
+
+        // two commits on master, one on upstream
+        commit(&repo, "a", "HEAD");
+        commit(&repo, "b", "HEAD");
+        repo.set_head("refs/heads/upstream").unwrap();
+        repo.checkout_head(None).unwrap();
+        commit(&repo, "c", "HEAD");
+        repo.set_head("refs/heads/master").unwrap();
+        repo.checkout_head(None).unwrap();
+
+        let upstream = repo.find_reference("refs/heads/upstream").unwrap();
+        let upstream = repo.reference_to_annotated_commit(&upstream).unwrap();
+        let mut opts = RebaseOptions::new();
+        let mut rebase = repo.rebase(None, Some(&upstream), None,
+                                     Some(&mut opts)).unwrap();
+        assert_eq!(rebase.len(), 2);
+        assert_eq!(rebase.operation_current(), None);
+
+        let sig = repo.signature().unwrap();
+        let mut count = 0;
+        loop {
+            match rebase.next() {
+                Some(op) => {
+                    let op = op.unwrap();
+                    assert_eq!(op.kind(), Some(RebaseOperationType::Pick));
+                    assert_eq!(op.exec(), None);
+                }
+                None => break,
+            }
+            rebase.commit(None, &sig, None).unwrap();
+            count += 1;
+        }
+        assert_eq!(count, 2);
+        assert_eq!(rebase.operation_current(), Some(1));
+        rebase.finish(None).unwrap();
+
+        let head = repo.head().unwrap();
+        assert_eq!(head.name(), Some("refs/heads/master"));
+        let head = repo.find_commit(head.target().unwrap()).unwrap();
+        assert_eq!(head.message(), Some("b"));
+        let parent = head.parent(0).unwrap();
+        assert_eq!(parent.message(), Some("a"));
+        assert_eq!(parent.parent_id(0).unwrap(), upstream.id());
+    }
+}
@@ -0,0 +1,510 @@
+use std::ffi::{CStr, CString};
+use std::mem;
+use std::ptr;
+use std::str;
+use std::vec;
+use libc::{c_char, c_int, c_void};
+
+use {raw, panic, Error, Oid};
+use util::Binding;
+
+/// A custom storage backend for the references of a repository, installed
+/// with `Repository::set_refdb`.
+///
+/// The backend stores every reference of the repository, including `HEAD`,
+/// and libgit2 builds all higher-level operations such as branches, commits
+/// and reference renames on top of it. Reflogs and reference transactions
+/// are not supported by custom backends.
+///
+/// Errors returned by a backend are passed on to libgit2, which acts on their
+/// codes, so `lookup` must return a `NotFound` error for a missing reference.
+/// Errors can be created with `Error::new`.
+pub trait RefdbBackend: Send + 'static {
+    /// Check whether the reference `name` exists.
+    fn exists(&self, name: &str) -> Result<bool, Error>;
+
+    /// Look up the target of the reference `name`.
+    fn lookup(&self, name: &str) -> Result<ReferenceTarget, Error>;
+
+    /// List the names and targets of all references, or only those matching
+    /// `glob`, such as `refs/heads/*`, if one is given.
+    fn references(&self, glob: Option<&str>)
+                  -> Result<Vec<(String, ReferenceTarget)>, Error>;
+
+    /// Create or update the reference `name`.
+    ///
+    /// If `force` is `false` and the reference already exists an `Exists`
+    /// error should be returned. If `expected` is given the reference must
+    /// currently have that target, otherwise a `Modified` error should be
+    /// returned.
+    fn write(&self, name: &str, target: &ReferenceTarget, force: bool,
+             expected: Option<&ReferenceTarget>) -> Result<(), Error>;
+
+    /// Rename the reference `old_name` to `new_name`, returning its target.
+    ///
+    /// If `force` is `false` and `new_name` already exists an `Exists` error
+    /// should be returned.
+    fn rename(&self, old_name: &str, new_name: &str, force: bool)
+              -> Result<ReferenceTarget, Error>;
+
+    /// Delete the reference `name`.
+    ///
+    /// If `expected` is given the reference must currently have that target,
+    /// otherwise a `Modified` error should be returned.
+    fn delete(&self, name: &str, expected: Option<&ReferenceTarget>)
+              -> Result<(), Error>;
+}
+
+/// The target of a reference stored by a `RefdbBackend`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReferenceTarget {
+    /// A direct reference to an object.
+    Direct(Oid),
+    /// A symbolic reference to the named reference.
+    Symbolic(String),
+}
+
+/// Instance of a `git_refdb_backend`, must use `#[repr(C)]` to ensure that
+/// the C fields come first.
+#[repr(C)]
+struct RawRefdbBackend {
+    raw: raw::git_refdb_backend,
+    obj: Box<RefdbBackend>,
+}
+
+/// Instance of a `git_reference_iterator`, must use `#[repr(C)]` to ensure
+/// that the C fields come first.
+#[repr(C)]
+struct RawReferenceIterator {
+    raw: raw::git_reference_iterator,
+    refs: vec::IntoIter<(String, ReferenceTarget)>,
+    // the name last returned by `next_name`, which must outlive the call
+    name: CString,
+}
+
+/// Wrap `backend` in a `git_refdb_backend`, whose ownership passes to the
+/// caller and is released through the backend's `free` hook.
+pub fn raw_backend<B: RefdbBackend>(backend: B)
+                                    -> Result<*mut raw::git_refdb_backend,
+                                              Error> {
+    let mut raw = Box::new(RawRefdbBackend {
+        raw: unsafe { mem::zeroed() },
+        obj: Box::new(backend),
+    });
+    unsafe {
+        try_call!(raw::git_refdb_init_backend(&mut raw.raw,
+                                              raw::GIT_REFDB_BACKEND_VERSION));
+    }
+    raw.raw.exists = Some(backend_exists);
+    raw.raw.lookup = Some(backend_lookup);
+    raw.raw.iterator = Some(backend_iterator);
+    raw.raw.write = Some(backend_write);
+    raw.raw.rename = Some(backend_rename);
+    raw.raw.del = Some(backend_delete);
+    raw.raw.has_log = Some(backend_has_log);
+    raw.raw.ensure_log = Some(backend_ensure_log);
+    raw.raw.free = Some(backend_free);
+    raw.raw.reflog_read = Some(backend_reflog_read);
+    raw.raw.reflog_write = Some(backend_reflog_write);
+    raw.raw.reflog_rename = Some(backend_reflog_rename);
+    raw.raw.reflog_delete = Some(backend_reflog_delete);
+    raw.raw.lock = Some(backend_lock);
+    raw.raw.unlock = Some(backend_unlock);
+    Ok(Box::into_raw(raw) as *mut raw::git_refdb_backend)
+}
+
+unsafe fn backend<'a>(raw: *mut raw::git_refdb_backend) -> &'a RefdbBackend {
+    &*(*(raw as *mut RawRefdbBackend)).obj
+}
+
+unsafe fn name<'a>(name: *const c_char) -> Result<&'a str, Error> {
+    str::from_utf8(CStr::from_ptr(name).to_bytes()).map_err(|_| {
+        Error::from_str("reference name is not valid utf-8")
+    })
+}
+
+// The target a reference is expected to have, which libgit2 passes as either
+// an id or a symbolic target.
+unsafe fn expected(id: *const raw::git_oid, target: *const c_char)
+                   -> Result<Option<ReferenceTarget>, Error> {
+    if !id.is_null() {
+        Ok(Some(ReferenceTarget::Direct(Binding::from_raw(id))))
+    } else if !target.is_null() {
+        Ok(Some(ReferenceTarget::Symbolic(try!(name(target)).to_string())))
+    } else {
+        Ok(None)
+    }
+}
+
+// Allocate a reference which libgit2 takes ownership of.
+fn alloc(name: &str, target: &ReferenceTarget)
+         -> Result<*mut raw::git_reference, Error> {
+    let name = try!(CString::new(name));
+    let ptr = match *target {
+        ReferenceTarget::Direct(id) => unsafe {
+            raw::git_reference__alloc(name.as_ptr(), id.raw(), ptr::null())
+        },
+        ReferenceTarget::Symbolic(ref target) => {
+            let target = try!(CString::new(&target[..]));
+            unsafe {
+                raw::git_reference__alloc_symbolic(name.as_ptr(),
+                                                   target.as_ptr())
+            }
+        }
+    };
+    if ptr.is_null() {
+        Err(Error::from_str("failed to allocate reference"))
+    } else {
+        Ok(ptr)
+    }
+}
+
+// Report a backend's error to libgit2, returning the code to hand back.
+fn result_code(ret: Option<Result<(), Error>>) -> c_int {
+    match ret {
+        Some(Ok(())) => 0,
+        Some(Err(e)) => unsafe {
+            let msg = CString::new(e.message()).unwrap_or_else(|_| {
+                CString::new("refdb backend error").unwrap()
+            });
+            raw::giterr_set_str(e.raw_class() as c_int, msg.as_ptr());
+            e.raw_code() as c_int
+        },
+        None => -1,
+    }
+}
+
+fn unsupported(what: &str) -> c_int {
+    let err = format!("{} are not supported by this refdb backend", what);
+    result_code(Some(Err(Error::from_str(&err))))
+}
+
+extern fn backend_exists(out: *mut c_int,
+                         raw: *mut raw::git_refdb_backend,
+                         refname: *const c_char) -> c_int {
+    result_code(panic::wrap(|| -> Result<(), Error> {
+        unsafe {
+            let exists = try!(backend(raw).exists(try!(name(refname))));
+            *out = exists as c_int;
+            Ok(())
+        }
+    }))
+}
+
+extern fn backend_lookup(out: *mut *mut raw::git_reference,
+                         raw: *mut raw::git_refdb_backend,
+                         refname: *const c_char) -> c_int {
+    result_code(panic::wrap(|| -> Result<(), Error> {
+        unsafe {
+            let refname = try!(name(refname));
+            let target = try!(backend(raw).lookup(refname));
+            *out = try!(alloc(refname, &target));
+            Ok(())
+        }
+    }))
+}
+
+extern fn backend_iterator(out: *mut *mut raw::git_reference_iterator,
+                           raw: *mut raw::git_refdb_backend,
+                           glob: *const c_char) -> c_int {
+    result_code(panic::wrap(|| -> Result<(), Error> {
+        unsafe {
+            let glob = if glob.is_null() {
+                None
+            } else {
+                Some(try!(name(glob)))
+            };
+            let refs = try!(backend(raw).references(glob));
+            let iter = Box::new(RawReferenceIterator {
+                raw: raw::git_reference_iterator {
+                    db: ptr::null_mut(),
+                    next: Some(iterator_next),
+                    next_name: Some(iterator_next_name),
+                    free: Some(iterator_free),
+                },
+                refs: refs.into_iter(),
+                name: CString::new(Vec::new()).unwrap(),
+            });
+            *out = Box::into_raw(iter) as *mut raw::git_reference_iterator;
+            Ok(())
+        }
+    }))
+}
+
+extern fn backend_write(raw: *mut raw::git_refdb_backend,
+                        reference: *const raw::git_reference,
+                        force: c_int,
+                        _who: *const raw::git_signature,
+                        _message: *const c_char,
+                        old: *const raw::git_oid,
+                        old_target: *const c_char) -> c_int {
+    result_code(panic::wrap(|| -> Result<(), Error> {
+        unsafe {
+            let refname = try!(name(raw::git_reference_name(reference)));
+            let target = if raw::git_reference_type(reference) ==
+                            raw::GIT_REF_SYMBOLIC {
+                let target = raw::git_reference_symbolic_target(reference);
+                ReferenceTarget::Symbolic(try!(name(target)).to_string())
+            } else {
+                let id = raw::git_reference_target(reference);
+                ReferenceTarget::Direct(Binding::from_raw(id))
+            };
+            let expected = try!(expected(old, old_target));
+            backend(raw).write(refname, &target, force != 0,
+                               expected.as_ref())
+        }
+    }))
+}
+
+extern fn backend_rename(out: *mut *mut raw::git_reference,
+                         raw: *mut raw::git_refdb_backend,
+                         old_name: *const c_char,
+                         new_name: *const c_char,
+                         force: c_int,
+                         _who: *const raw::git_signature,
+                         _message: *const c_char) -> c_int {
+    result_code(panic::wrap(|| -> Result<(), Error> {
+        unsafe {
+            let new_name = try!(name(new_name));
+            let target = try!(backend(raw).rename(try!(name(old_name)),
+                                                  new_name, force != 0));
+            *out = try!(alloc(new_name, &target));
+            Ok(())
+        }
+    }))
+}
+
+extern fn backend_delete(raw: *mut raw::git_refdb_backend,
+                         refname: *const c_char,
+                         old_id: *const raw::git_oid,
+                         old_target: *const c_char) -> c_int {
+    result_code(panic::wrap(|| -> Result<(), Error> {
+        unsafe {
+            let expected = try!(expected(old_id, old_target));
+            backend(raw).delete(try!(name(refname)), expected.as_ref())
+        }
+    }))
+}
+
+extern fn backend_has_log(_raw: *mut raw::git_refdb_backend,
+                          _refname: *const c_char) -> c_int {
+    0
+}
+
+extern fn backend_ensure_log(_raw: *mut raw::git_refdb_backend,
+                             _refname: *const c_char) -> c_int {
+    unsupported("reflogs")
+}
+
+extern fn backend_reflog_read(_out: *mut *mut raw::git_reflog,
+                              _raw: *mut raw::git_refdb_backend,
+                              _refname: *const c_char) -> c_int {
+    unsupported("reflogs")
+}
+
+extern fn backend_reflog_write(_raw: *mut raw::git_refdb_backend,
+                               _reflog: *mut raw::git_reflog) -> c_int {
+    unsupported("reflogs")
+}
+
+extern fn backend_reflog_rename(_raw: *mut raw::git_refdb_backend,
+                                _old_name: *const c_char,
+                                _new_name: *const c_char) -> c_int {
+    // there's never a reflog to rename
+    0
+}
+
+extern fn backend_reflog_delete(_raw: *mut raw::git_refdb_backend,
+                                _refname: *const c_char) -> c_int {
+    // there's never a reflog to delete
+    0
+}
+
+extern fn backend_lock(_payload: *mut *mut c_void,
+                       _raw: *mut raw::git_refdb_backend,
+                       _refname: *const c_char) -> c_int {
+    unsupported("transactions")
+}
+
+extern fn backend_unlock(_raw: *mut raw::git_refdb_backend,
+                         _payload: *mut c_void,
+                         _success: c_int,
+                         _update_reflog: c_int,
+                         _reference: *const raw::git_reference,
+                         _who: *const raw::git_signature,
+                         _message: *const c_char) -> c_int {
+    unsupported("transactions")
+}
+
+extern fn backend_free(raw: *mut raw::git_refdb_backend) {
+    let _ = panic::wrap(|| unsafe {
+        drop(Box::from_raw(raw as *mut RawRefdbBackend));
+    });
+}
+
+extern fn iterator_next(out: *mut *mut raw::git_reference,
+                        iter: *mut raw::git_reference_iterator) -> c_int {
+    let ret = panic::wrap(|| unsafe {
+        let iter = &mut *(iter as *mut RawReferenceIterator);
+        match iter.refs.next() {
+            Some((name, target)) => alloc(&name, &target).map(|r| {
+                *out = r;
+                true
+            }),
+            None => Ok(false),
+        }
+    });
+    match ret {
+        Some(Ok(false)) => raw::GIT_ITEROVER as c_int,
+        ret => result_code(ret.map(|res| res.map(|_| ()))),
+    }
+}
+
+extern fn iterator_next_name(out: *mut *const c_char,
+                             iter: *mut raw::git_reference_iterator)
+                             -> c_int {
+    let ret = panic::wrap(|| unsafe {
+        let iter = &mut *(iter as *mut RawReferenceIterator);
+        match iter.refs.next() {
+            Some((name, _)) => CString::new(name).map_err(Error::from).map(|n| {
+                iter.name = n;
+                *out = iter.name.as_ptr();
+                true
+            }),
+            None => Ok(false),
+        }
+    });
+    match ret {
+        Some(Ok(false)) => raw::GIT_ITEROVER as c_int,
+        ret => result_code(ret.map(|res| res.map(|_| ()))),
+    }
+}
+
+extern fn iterator_free(iter: *mut raw::git_reference_iterator) {
+    let _ = panic::wrap(|| unsafe {
+        drop(Box::from_raw(iter as *mut RawReferenceIterator));
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+    use tempdir::TempDir;
+    use {Error, ErrorClass, ErrorCode, Repository};
+    use super::{RefdbBackend, ReferenceTarget};
+
+    type Refs = Arc<Mutex<HashMap<String, ReferenceTarget>>>;
+
+    struct Memory(Refs);
+
+    fn error(code: ErrorCode, msg: &str) -> Error {
+        Error::new(code, ErrorClass::Reference, msg)
+    }
+
+    fn check(refs: &HashMap<String, ReferenceTarget>, name: &str,
+             expected: Option<&ReferenceTarget>) -> Result<(), Error> {
+        match expected {
+            Some(e) if refs.get(name) != Some(e) => {
+                Err(error(ErrorCode::Modified, "reference was modified"))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    impl RefdbBackend for Memory {
+        fn exists(&self, name: &str) -> Result<bool, Error> {
+            Ok(self.0.lock().unwrap().contains_key(name))
+        }
+
+        fn lookup(&self, name: &str) -> Result<ReferenceTarget, Error> {
+            self.0.lock().unwrap().get(name).cloned().ok_or_else(|| {
+                error(ErrorCode::NotFound, "reference not found")
+            })
+        }
+
+        fn references(&self, glob: Option<&str>)
+                      -> Result<Vec<(String, ReferenceTarget)>, Error> {
+            // only trailing `*` globs are needed here
+            let prefix = glob.map_or("", |g| &g[..g.len() - 1]);
+            let refs = self.0.lock().unwrap();
+            let mut refs = refs.iter()
+                               .filter(|&(name, _)| name.starts_with(prefix))
+                               .map(|(name, t)| (name.clone(), t.clone()))
+                               .collect::<Vec<_>>();
+            refs.sort_by(|a, b| a.0.cmp(&b.0));
+            Ok(refs)
+        }
+
+        fn write(&self, name: &str, target: &ReferenceTarget, force: bool,
+                 expected: Option<&ReferenceTarget>) -> Result<(), Error> {
+            let mut refs = self.0.lock().unwrap();
+            if !force && refs.contains_key(name) {
+                return Err(error(ErrorCode::Exists, "reference exists"))
+            }
+            try!(check(&refs, name, expected));
+            refs.insert(name.to_string(), target.clone());
+            Ok(())
+        }
+
+        fn rename(&self, old_name: &str, new_name: &str, force: bool)
+                  -> Result<ReferenceTarget, Error> {
+            let mut refs = self.0.lock().unwrap();
+            if !force && refs.contains_key(new_name) {
+                return Err(error(ErrorCode::Exists, "reference exists"))
+            }
+            match refs.remove(old_name) {
+                Some(target) => {
+                    refs.insert(new_name.to_string(), target.clone());
+                    Ok(target)
+                }
+                None => Err(error(ErrorCode::NotFound, "reference not found")),
+            }
+        }
+
+        fn delete(&self, name: &str, expected: Option<&ReferenceTarget>)
+                  -> Result<(), Error> {
+            let mut refs = self.0.lock().unwrap();
+            try!(check(&refs, name, expected));
+            refs.remove(name);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn smoke() {
+        let td = TempDir::new("test").unwrap();
+        let repo = Repository::init(td.path()).unwrap();
+        let refs = Refs::default();
+        repo.set_refdb(Memory(refs.clone())).unwrap();
+        assert!(repo.head().is_err());
+
+        repo.reference_symbolic("HEAD", "refs/heads/master", true, "")
+            .unwrap();
+        let sig = repo.signature().unwrap();
+        let tree = repo.find_tree(repo.index().unwrap().write_tree().unwrap())
+                       .unwrap();
+        let id = repo.commit(Some("HEAD"), &sig, &sig, "initial", &tree, &[])
+                     .unwrap();
+        assert_eq!(refs.lock().unwrap().get("refs/heads/master"),
+                   Some(&ReferenceTarget::Direct(id)));
+        assert_eq!(repo.head().unwrap().target(), Some(id));
+        assert!(!td.path().join(".git/refs/heads/master").exists());
+
+        repo.reference("refs/heads/other", id, false, "").unwrap();
+        assert!(repo.reference("refs/heads/other", id, false, "").is_err());
+        let names = repo.references_glob("refs/heads/*").unwrap()
+                        .names().map(|n| n.unwrap().to_string())
+                        .collect::<Vec<_>>();
+        assert_eq!(names, ["refs/heads/master", "refs/heads/other"]);
+
+        let mut other = repo.find_reference("refs/heads/other").unwrap();
+        let renamed = other.rename("refs/heads/renamed", false, "").unwrap();
+        assert_eq!(renamed.target(), Some(id));
+        assert!(repo.find_reference("refs/heads/other").is_err());
+
+        repo.find_reference("refs/heads/renamed").unwrap().delete().unwrap();
+        assert_eq!(refs.lock().unwrap().len(), 2);
+        assert!(repo.transaction().unwrap().lock_ref("HEAD").is_err());
+    }
+}
@@ -1,11 +1,14 @@
 use std::cmp::Ordering;
-use std::ffi::CString;
+use std::ffi::{CStr, CString};
 use std::marker;
 use std::mem;
 use std::ptr;
 use std::str;
 
+use libc::{c_char, size_t};
+
 use {raw, Error, Oid, Repository, Object, ObjectType, Blob, Commit, Tree, Tag};
+use ReferenceFormat;
 use object::CastOrPanic;
 use util::Binding;
 
@@ -38,6 +41,25 @@ impl<'repo> Reference<'repo> {
         unsafe { raw::git_reference_is_valid_name(refname.as_ptr()) == 1 }
     }
 
+    /// Normalize a reference name, removing redundant slashes and validating
+    /// it against the rules selected by `flags`.
+    ///
+    /// Returns an error with the code `InvalidSpec` if the name is not valid.
+    pub fn normalize_name(refname: &str, flags: ReferenceFormat)
+                          -> Result<String, Error> {
+        ::init();
+        let mut dst = [0 as c_char; 1024];
+        let refname = try!(CString::new(refname));
+        unsafe {
+            try_call!(raw::git_reference_normalize_name(dst.as_mut_ptr(),
+                                                        dst.len() as size_t,
+                                                        refname,
+                                                        flags.bits()));
+            let s = CStr::from_ptr(dst.as_ptr()).to_bytes();
+            Ok(str::from_utf8(s).unwrap().to_owned())
+        }
+    }
+
     /// Get access to the underlying raw pointer.
     pub fn raw(&self) -> *mut raw::git_reference { self.raw }
 
@@ -114,6 +136,11 @@ impl<'repo> Reference<'repo> {
     ///
     /// This peeled OID only applies to direct references that point to a hard
     /// Tag object: it is the result of peeling such Tag.
+    ///
+    /// The value is the one cached in `packed-refs`, so no object is looked
+    /// up. It is therefore only available for packed references whose peeled
+    /// value was recorded there, and `None` otherwise, e.g. for loose
+    /// references; use `peel` to peel those.
     pub fn target_peel(&self) -> Option<Oid> {
         unsafe {
             Binding::from_raw_opt(raw::git_reference_target_peel(&*self.raw))
@@ -324,12 +351,60 @@ impl<'repo, 'references> Iterator for ReferenceNames<'repo, 'references> {
 
 #[cfg(test)]
 mod tests {
+    use std::fs::File;
+    use std::io::prelude::*;
+
     use {Reference, ObjectType};
 
     #[test]
     fn smoke() {
         assert!(Reference::is_valid_name("refs/foo"));
         assert!(!Reference::is_valid_name("foo"));
+        assert!(!Reference::is_valid_name("refs/heads/..bad"));
+    }
+
+    #[test]
+    fn normalize_name() {
+        assert_eq!(Reference::normalize_name("refs//heads///foo",
+                                             ::REFERENCE_FORMAT_NORMAL).unwrap(),
+                   "refs/heads/foo");
+        let err = Reference::normalize_name("refs/heads/..bad",
+                                            ::REFERENCE_FORMAT_NORMAL)
+                            .unwrap_err();
+        assert_eq!(err.code(), ::ErrorCode::InvalidSpec);
+
+        assert!(Reference::normalize_name("foo", ::REFERENCE_FORMAT_NORMAL)
+                          .is_err());
+        assert_eq!(Reference::normalize_name("foo",
+                            ::REFERENCE_FORMAT_ALLOW_ONELEVEL).unwrap(),
+                   "foo");
+        assert!(Reference::normalize_name("refs/heads/*",
+                                          ::REFERENCE_FORMAT_NORMAL).is_err());
+        assert_eq!(Reference::normalize_name("refs/heads/*",
+                            ::REFERENCE_FORMAT_REFSPEC_PATTERN).unwrap(),
+                   "refs/heads/*");
+    }
+
+    #[test]
+    fn target_peel_packed() {
+        let (_td, repo) = ::test::repo_init();
+        let head = repo.head().unwrap().target().unwrap();
+        let obj = repo.find_object(head, None).unwrap();
+        let sig = repo.signature().unwrap();
+        let tag = repo.tag("v1", &obj, &sig, "v1", false).unwrap();
+        let loose = repo.find_reference("refs/tags/v1").unwrap();
+        assert_eq!(loose.target(), Some(tag));
+        assert_eq!(loose.target_peel(), None);
+        drop(loose);
+
+        ::std::fs::remove_file(repo.path().join("refs/tags/v1")).unwrap();
+        File::create(repo.path().join("packed-refs")).unwrap()
+            .write_all(format!("# pack-refs with: peeled fully-peeled \n\
+                                {} refs/tags/v1\n\
+                                ^{}\n", tag, head).as_bytes()).unwrap();
+        let packed = repo.find_reference("refs/tags/v1").unwrap();
+        assert_eq!(packed.target(), Some(tag));
+        assert_eq!(packed.target_peel(), Some(head));
     }
 
     #[test]
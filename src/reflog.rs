@@ -14,6 +14,8 @@ pub struct Reflog {
 /// An entry inside the reflog of a repository
 pub struct ReflogEntry<'reflog> {
     raw: *const raw::git_reflog_entry,
+    // Owns the reflog for entries returned by `Repository::reflog_latest`
+    _reflog: Option<Reflog>,
     _marker: marker::PhantomData<&'reflog Reflog>,
 }
 
@@ -115,7 +117,7 @@ impl<'reflog> ReflogEntry<'reflog> {
 
     /// Get the old oid
     pub fn id_old(&self) -> Oid {
-        unsafe { Binding::from_raw(raw::git_reflog_entry_id_new(self.raw)) }
+        unsafe { Binding::from_raw(raw::git_reflog_entry_id_old(self.raw)) }
     }
 
     /// Get the log message, returning `None` on invalid UTF-8.
@@ -135,11 +137,28 @@ impl<'reflog> Binding for ReflogEntry<'reflog> {
     type Raw = *const raw::git_reflog_entry;
 
     unsafe fn from_raw(raw: *const raw::git_reflog_entry) -> ReflogEntry<'reflog> {
-        ReflogEntry { raw: raw, _marker: marker::PhantomData }
+        ReflogEntry { raw: raw, _reflog: None, _marker: marker::PhantomData }
     }
     fn raw(&self) -> *const raw::git_reflog_entry { self.raw }
 }
 
+/// Take the most recent entry out of `reflog`, keeping the reflog alive for
+/// as long as the entry.
+pub fn latest(reflog: Reflog) -> Option<ReflogEntry<'static>> {
+    unsafe {
+        let ptr = raw::git_reflog_entry_byindex(reflog.raw, 0);
+        if ptr.is_null() {
+            None
+        } else {
+            Some(ReflogEntry {
+                raw: ptr,
+                _reflog: Some(reflog),
+                _marker: marker::PhantomData,
+            })
+        }
+    }
+}
+
 impl<'reflog> Iterator for ReflogIter<'reflog> {
     type Item = ReflogEntry<'reflog>;
     fn next(&mut self) -> Option<ReflogEntry<'reflog>> {
@@ -169,4 +188,28 @@ mod tests {
         repo.reflog_rename("HEAD", "refs/heads/foo").unwrap();
         repo.reflog_delete("refs/heads/foo").unwrap();
     }
+
+    #[test]
+    fn latest() {
+        let (_td, repo) = ::test::repo_init();
+        let head = repo.head().unwrap().target().unwrap();
+        let next = {
+            let sig = repo.signature().unwrap();
+            let commit = repo.find_commit(head).unwrap();
+            let tree = commit.tree().unwrap();
+            repo.commit(Some("HEAD"), &sig, &sig, "next", &tree,
+                        &[&commit]).unwrap()
+        };
+
+        let entry = repo.reflog_latest("refs/heads/master").unwrap().unwrap();
+        assert_eq!(entry.id_old(), head);
+        assert_eq!(entry.id_new(), next);
+        assert_eq!(entry.message(), Some("commit: next"));
+        assert_eq!(entry.committer().name(), Some("name"));
+        drop(repo);
+        assert_eq!(entry.id_new(), next);
+
+        let (_td, repo) = ::test::repo_init();
+        assert!(repo.reflog_latest("refs/heads/missing").unwrap().is_none());
+    }
 }
@@ -1,20 +1,47 @@
 use std::ffi::CString;
 use std::marker;
+#[cfg(feature = "libgit2-0-28")]
+use std::ptr;
 use std::str;
 
-use {raw, Direction};
+use {raw, Buf, Direction, Error};
 use util::Binding;
 
 /// A structure to represent a git [refspec][1].
 ///
-/// Refspecs are currently mainly accessed/created through a `Remote`.
+/// Refspecs are mainly accessed through a `Remote`, but with the
+/// `libgit2-0-28` feature they may also be parsed from a string with
+/// `Refspec::parse`.
 ///
 /// [1]: http://git-scm.com/book/en/Git-Internals-The-Refspec
 pub struct Refspec<'remote> {
     raw: *const raw::git_refspec,
+    #[cfg(feature = "libgit2-0-28")]
+    owned: bool,
     _marker: marker::PhantomData<&'remote raw::git_remote>,
 }
 
+#[cfg(feature = "libgit2-0-28")]
+impl Refspec<'static> {
+    /// Parse a refspec from a string.
+    ///
+    /// `is_fetch` indicates whether the refspec is to be used for fetching or
+    /// pushing, which determines the rules it is validated against.
+    pub fn parse(input: &str, is_fetch: bool) -> Result<Refspec<'static>, Error> {
+        ::init();
+        let input = try!(CString::new(input));
+        let mut ret = ptr::null_mut();
+        unsafe {
+            try_call!(raw::git_refspec_parse(&mut ret, input, is_fetch));
+            Ok(Refspec {
+                raw: ret as *const _,
+                owned: true,
+                _marker: marker::PhantomData,
+            })
+        }
+    }
+}
+
 impl<'remote> Refspec<'remote> {
     /// Get the refspec's direction.
     pub fn direction(&self) -> Direction {
@@ -77,13 +104,88 @@ impl<'remote> Refspec<'remote> {
     pub fn bytes(&self) -> &[u8] {
         unsafe { ::opt_bytes(self, raw::git_refspec_string(self.raw)).unwrap() }
     }
+
+    /// Transform a reference to its target following the refspec's rules.
+    ///
+    /// The reference name is expected to match the source side of the
+    /// refspec.
+    pub fn transform(&self, name: &str) -> Result<Buf, Error> {
+        let name = try!(CString::new(name));
+        let buf = Buf::new();
+        unsafe {
+            try_call!(raw::git_refspec_transform(buf.raw(), self.raw, name));
+        }
+        Ok(buf)
+    }
+
+    /// Transform a target reference to its source reference following the
+    /// refspec's rules.
+    ///
+    /// The reference name is expected to match the destination side of the
+    /// refspec.
+    pub fn rtransform(&self, name: &str) -> Result<Buf, Error> {
+        let name = try!(CString::new(name));
+        let buf = Buf::new();
+        unsafe {
+            try_call!(raw::git_refspec_rtransform(buf.raw(), self.raw, name));
+        }
+        Ok(buf)
+    }
 }
 
 impl<'remote> Binding for Refspec<'remote> {
     type Raw = *const raw::git_refspec;
 
     unsafe fn from_raw(raw: *const raw::git_refspec) -> Refspec<'remote> {
-        Refspec { raw: raw, _marker: marker::PhantomData }
+        Refspec {
+            raw: raw,
+            #[cfg(feature = "libgit2-0-28")]
+            owned: false,
+            _marker: marker::PhantomData,
+        }
     }
     fn raw(&self) -> *const raw::git_refspec { self.raw }
 }
+
+#[cfg(feature = "libgit2-0-28")]
+impl<'remote> Drop for Refspec<'remote> {
+    fn drop(&mut self) {
+        if self.owned {
+            unsafe { raw::git_refspec_free(self.raw as *mut _) }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "libgit2-0-28"))]
+mod tests {
+    use {Direction, Refspec};
+
+    #[test]
+    fn parse() {
+        let spec = Refspec::parse("+refs/heads/*:refs/remotes/origin/*",
+                                  true).unwrap();
+        match spec.direction() {
+            Direction::Fetch => {}
+            Direction::Push => panic!("wrong direction"),
+        }
+        assert_eq!(spec.src(), Some("refs/heads/*"));
+        assert_eq!(spec.dst(), Some("refs/remotes/origin/*"));
+        assert!(spec.is_force());
+        assert!(spec.src_matches("refs/heads/master"));
+        assert!(spec.dst_matches("refs/remotes/origin/master"));
+
+        let dst = spec.transform("refs/heads/master").unwrap();
+        assert_eq!(dst.as_str(), Some("refs/remotes/origin/master"));
+        let src = spec.rtransform("refs/remotes/origin/master").unwrap();
+        assert_eq!(src.as_str(), Some("refs/heads/master"));
+
+        let spec = Refspec::parse("refs/heads/master", false).unwrap();
+        match spec.direction() {
+            Direction::Push => {}
+            Direction::Fetch => panic!("wrong direction"),
+        }
+        assert!(!spec.is_force());
+
+        assert!(Refspec::parse("refs/heads/ma..ster", true).is_err());
+    }
+}
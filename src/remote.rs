@@ -7,7 +7,7 @@ use std::slice;
 use std::str;
 use libc;
 
-use {raw, Direction, Error, Refspec, Oid, FetchPrune, ProxyOptions};
+use {raw, Buf, Direction, Error, Refspec, Oid, FetchPrune, ProxyOptions};
 use {RemoteCallbacks, Progress, Repository, AutotagOption};
 use string_array::StringArray;
 use util::Binding;
@@ -163,6 +163,11 @@ impl<'repo> Remote<'repo> {
     ///
     /// The `specs` argument is a list of refspecs to use for this negotiation
     /// and download. Use an empty array to use the base refspecs.
+    ///
+    /// No references are changed and `FETCH_HEAD` isn't written by a
+    /// download, but the downloaded objects can be looked up and the tips the
+    /// remote advertised are available from `list`. Call `update_tips` to
+    /// apply them, which together with `download` is what `fetch` does.
     pub fn download(&mut self, specs: &[&str], opts: Option<&mut FetchOptions>)
                     -> Result<(), Error> {
         let (_a, _b, arr) = try!(::util::iter2cstrs(specs.iter()));
@@ -221,6 +226,11 @@ impl<'repo> Remote<'repo> {
     }
 
     /// Update the tips to the new state
+    ///
+    /// The remote-tracking references are updated to the tips advertised by
+    /// the remote during the last `download`, and `FETCH_HEAD` is written if
+    /// `update_fetchhead` is true. The `update_tips` callback is invoked for
+    /// each updated reference.
     pub fn update_tips(&mut self,
                        callbacks: Option<&mut RemoteCallbacks>,
                        update_fetchhead: bool,
@@ -236,6 +246,24 @@ impl<'repo> Remote<'repo> {
         Ok(())
     }
 
+    /// Prune tracking refs that are no longer present on the remote.
+    ///
+    /// Every local reference matching the destination of one of the remote's
+    /// fetch refspecs, e.g. `refs/remotes/origin/*`, whose source no longer
+    /// exists on the remote is deleted. The remote must have connected to the
+    /// remote repository to know which references it has. To prune as part of
+    /// a fetch use `FetchOptions::prune` instead.
+    ///
+    /// The `update_tips` callback is invoked for each deleted reference.
+    pub fn prune(&mut self, callbacks: Option<&mut RemoteCallbacks>)
+                 -> Result<(), Error> {
+        let cbs = callbacks.map(|cb| cb.raw());
+        unsafe {
+            try_call!(raw::git_remote_prune(self.raw, cbs.as_ref()));
+        }
+        Ok(())
+    }
+
     /// Perform a push
     ///
     /// Perform all the steps for a push. If no refspecs are passed then the
@@ -256,6 +284,11 @@ impl<'repo> Remote<'repo> {
     }
 
     /// Get the statistics structure that is filled in by the fetch operation.
+    ///
+    /// After `fetch` or `download` returns this holds the final transfer
+    /// progress, such as the number of objects and bytes received, so there
+    /// is no need to record them from a `transfer_progress` callback. Use
+    /// `Progress::to_owned` to keep the numbers after the remote is dropped.
     pub fn stats(&self) -> Progress {
         unsafe {
             Binding::from_raw(raw::git_remote_stats(self.raw))
@@ -282,7 +315,23 @@ impl<'repo> Remote<'repo> {
                                 &[RemoteHead]>(slice))
         }
     }
-    
+
+    /// Get the name of the remote's default branch, i.e. the branch its
+    /// `HEAD` points to, such as `refs/heads/master`.
+    ///
+    /// The remote must have connected to the remote repository, and the
+    /// result remains available after disconnecting. If the server doesn't
+    /// report the target of its `HEAD`, the first branch pointing at the same
+    /// commit is returned. An error with the code `NotFound` is returned if the
+    /// remote has no `HEAD`, e.g. because it is empty.
+    pub fn default_branch(&self) -> Result<Buf, Error> {
+        let buf = Buf::new();
+        unsafe {
+            try_call!(raw::git_remote_default_branch(buf.raw(), self.raw));
+        }
+        Ok(buf)
+    }
+
     /// Get the remote's list of fetch refspecs
     pub fn fetch_refspecs(&self) -> Result<StringArray, Error> {
         unsafe {
@@ -528,6 +577,13 @@ impl<'repo, 'connection, 'cb> RemoteConnection<'repo, 'connection, 'cb> {
     pub fn list(&self) -> Result<&[RemoteHead], Error> {
         self.remote.list()
     }
+
+    /// Get the name of the remote's default branch.
+    ///
+    /// See `Remote::default_branch` for more information.
+    pub fn default_branch(&self) -> Result<Buf, Error> {
+        self.remote.default_branch()
+    }
 }
 
 impl<'repo, 'connection, 'cb> Drop for RemoteConnection<'repo, 'connection, 'cb> {
@@ -541,7 +597,7 @@ mod tests {
     use std::cell::Cell;
     use tempdir::TempDir;
     use {Repository, Remote, RemoteCallbacks, Direction, FetchOptions};
-    use {AutotagOption, PushOptions};
+    use {AutotagOption, Error, ErrorCode, PushOptions};
 
     #[test]
     fn smoke() {
@@ -633,8 +689,155 @@ mod tests {
     fn rename_remote() {
         let (_td, repo) = ::test::repo_init();
         repo.remote("origin", "foo").unwrap();
-        repo.remote_rename("origin", "foo").unwrap();
+        let problems = repo.remote_rename("origin", "foo").unwrap();
+        assert_eq!(problems.len(), 0);
+        assert!(repo.find_remote("origin").is_err());
+        {
+            let remote = repo.find_remote("foo").unwrap();
+            let fetch = remote.fetch_refspecs().unwrap();
+            assert_eq!(fetch.get(0), Some("+refs/heads/*:refs/remotes/foo/*"));
+        }
         repo.remote_delete("foo").unwrap();
+        assert!(repo.find_remote("foo").is_err());
+        assert_eq!(repo.remotes().unwrap().len(), 0);
+    }
+
+    #[test]
+    fn add_refspecs() {
+        let (_td, repo) = ::test::repo_init();
+        repo.remote("origin", "/path/to/nowhere").unwrap();
+        repo.remote_add_fetch("origin",
+                              "+refs/pull/*/head:refs/remotes/origin/pr/*")
+            .unwrap();
+        repo.remote_add_push("origin", "refs/heads/master").unwrap();
+
+        let origin = repo.find_remote("origin").unwrap();
+        let fetch = origin.fetch_refspecs().unwrap();
+        assert_eq!(fetch.len(), 2);
+        assert_eq!(fetch.get(0), Some("+refs/heads/*:refs/remotes/origin/*"));
+        assert_eq!(fetch.get(1),
+                   Some("+refs/pull/*/head:refs/remotes/origin/pr/*"));
+        let push = origin.push_refspecs().unwrap();
+        assert_eq!(push.len(), 1);
+        assert_eq!(push.get(0), Some("refs/heads/master"));
+    }
+
+    #[test]
+    fn default_branch() {
+        let (td, repo) = ::test::repo_init();
+        let head = repo.head().unwrap().target().unwrap();
+        repo.reference("refs/heads/trunk", head, false, "").unwrap();
+        repo.set_head("refs/heads/trunk").unwrap();
+        repo.find_reference("refs/heads/master").unwrap().delete().unwrap();
+
+        let td2 = TempDir::new("git").unwrap();
+        let other = Repository::init(td2.path()).unwrap();
+        let mut origin = other.remote("origin", &::test::path2url(td.path()))
+                              .unwrap();
+        assert!(origin.default_branch().is_err());
+        origin.connect(Direction::Fetch).unwrap();
+        assert_eq!(origin.default_branch().unwrap().as_str(),
+                   Some("refs/heads/trunk"));
+        origin.disconnect();
+        assert_eq!(origin.default_branch().unwrap().as_str(),
+                   Some("refs/heads/trunk"));
+
+        let td3 = TempDir::new("git").unwrap();
+        Repository::init_bare(td3.path()).unwrap();
+        let mut empty = other.remote_anonymous(&::test::path2url(td3.path()))
+                             .unwrap();
+        let connection = empty.connect_auth(Direction::Fetch, None, None)
+                              .unwrap();
+        match connection.default_branch() {
+            Ok(..) => panic!("empty remote has a default branch"),
+            Err(e) => assert_eq!(e.code(), ErrorCode::NotFound),
+        }
+    }
+
+    #[test]
+    fn download_then_update_tips() {
+        let (td, repo) = ::test::repo_init();
+        let head = repo.head().unwrap().target().unwrap();
+
+        let td2 = TempDir::new("git").unwrap();
+        let other = Repository::init(td2.path()).unwrap();
+        let mut origin = other.remote("origin", &::test::path2url(td.path()))
+                              .unwrap();
+        origin.download(&[], None).unwrap();
+        {
+            let heads = origin.list().unwrap();
+            assert!(heads.iter().any(|h| {
+                h.name() == "refs/heads/master" && h.oid() == head
+            }));
+        }
+        other.find_commit(head).unwrap();
+        assert!(other.find_reference("refs/remotes/origin/master").is_err());
+        assert!(!other.path().join("FETCH_HEAD").exists());
+        origin.disconnect();
+
+        let mut updated = Vec::new();
+        {
+            let mut callbacks = RemoteCallbacks::new();
+            callbacks.update_tips(|name, old, new| {
+                assert!(old.is_zero());
+                updated.push((name.to_string(), new));
+                true
+            });
+            origin.update_tips(Some(&mut callbacks), true,
+                               AutotagOption::Unspecified,
+                               Some("two-phase fetch")).unwrap();
+        }
+        assert_eq!(updated, [("refs/remotes/origin/master".to_string(), head)]);
+        let tip = other.find_reference("refs/remotes/origin/master").unwrap();
+        assert_eq!(tip.target(), Some(head));
+        assert!(other.path().join("FETCH_HEAD").exists());
+        let reflog = other.reflog("refs/remotes/origin/master").unwrap();
+        assert_eq!(reflog.get(0).unwrap().message(), Some("two-phase fetch"));
+    }
+
+    #[test]
+    fn prune() {
+        let (td, repo) = ::test::repo_init();
+        let head = repo.head().unwrap().target().unwrap();
+        let commit = repo.find_commit(head).unwrap();
+        repo.branch("gone", &commit, false).unwrap();
+        repo.branch("also-gone", &commit, false).unwrap();
+
+        let td2 = TempDir::new("git").unwrap();
+        let other = Repository::init(td2.path()).unwrap();
+        let mut origin = other.remote("origin", &::test::path2url(td.path()))
+                              .unwrap();
+        origin.fetch(&[], None, None).unwrap();
+        assert!(other.find_reference("refs/remotes/origin/gone").is_ok());
+        assert!(other.find_reference("refs/remotes/origin/also-gone").is_ok());
+
+        // pruning as part of a fetch
+        repo.find_branch("gone", ::BranchType::Local).unwrap()
+            .delete().unwrap();
+        let mut opts = FetchOptions::new();
+        opts.prune(::FetchPrune::On);
+        origin.fetch(&[], Some(&mut opts), None).unwrap();
+        assert!(other.find_reference("refs/remotes/origin/gone").is_err());
+        assert!(other.find_reference("refs/remotes/origin/master").is_ok());
+
+        // pruning on its own
+        repo.find_branch("also-gone", ::BranchType::Local).unwrap()
+            .delete().unwrap();
+        origin.connect(Direction::Fetch).unwrap();
+        let mut pruned = Vec::new();
+        {
+            let mut callbacks = RemoteCallbacks::new();
+            callbacks.update_tips(|name, _, new| {
+                assert!(new.is_zero());
+                pruned.push(name.to_string());
+                true
+            });
+            origin.prune(Some(&mut callbacks)).unwrap();
+        }
+        origin.disconnect();
+        assert_eq!(pruned, ["refs/remotes/origin/also-gone"]);
+        assert!(other.find_reference("refs/remotes/origin/also-gone").is_err());
+        assert!(other.find_reference("refs/remotes/origin/master").is_ok());
     }
 
     #[test]
@@ -661,18 +864,27 @@ mod tests {
 
         let repo = Repository::init(td2.path()).unwrap();
         let progress_hit = Cell::new(false);
+        let mut last_progress = None;
+        let final_stats;
         {
             let mut callbacks = RemoteCallbacks::new();
             let mut origin = repo.remote("origin", &url).unwrap();
 
-            callbacks.transfer_progress(|_progress| {
+            callbacks.transfer_progress(|progress| {
                 progress_hit.set(true);
+                last_progress = Some(progress.to_owned());
                 true
             });
             origin.fetch(&[],
                          Some(FetchOptions::new().remote_callbacks(callbacks)),
                          None).unwrap();
 
+            let stats = origin.stats();
+            assert!(stats.received_objects() > 0);
+            assert_eq!(stats.received_objects(), stats.total_objects());
+            assert!(stats.received_bytes() > 0);
+            final_stats = Some(stats.to_owned());
+
             let list = t!(origin.list());
             assert_eq!(list.len(), 2);
             assert_eq!(list[0].name(), "HEAD");
@@ -681,6 +893,17 @@ mod tests {
             assert!(!list[1].is_local());
         }
         assert!(progress_hit.get());
+
+        let progress = last_progress.unwrap();
+        assert!(progress.total_objects() > 0);
+        assert_eq!(progress.received_objects(), progress.total_objects());
+        assert_eq!(progress.indexed_objects(), progress.total_objects());
+        assert!(progress.indexed_deltas() <= progress.total_deltas());
+        assert_eq!(progress.local_objects(), 0);
+
+        let stats = final_stats.unwrap();
+        assert_eq!(stats.total_objects(), progress.total_objects());
+        assert!(stats.received_bytes() >= progress.received_bytes());
     }
 
     /// This test is meant to assure that the callbacks provided to connect will not cause
@@ -744,4 +967,21 @@ mod tests {
         let commit = repo.find_commit(commit).unwrap();
         assert_eq!(commit.message(), Some("initial"));
     }
+
+    #[test]
+    fn push_update_reference_error() {
+        let (_td, repo) = ::test::repo_init();
+        let td2 = TempDir::new("git1").unwrap();
+        let url = ::test::path2url(&td2.path());
+        Repository::init_bare(td2.path()).unwrap();
+
+        let mut remote = repo.remote("origin", &url).unwrap();
+        let mut callbacks = RemoteCallbacks::new();
+        callbacks.push_update_reference(|refname, _status| {
+            Err(Error::from_str(&format!("{} was rejected", refname)))
+        });
+        let mut options = PushOptions::new();
+        options.remote_callbacks(callbacks);
+        assert!(remote.push(&["refs/heads/master"], Some(&mut options)).is_err());
+    }
 }
@@ -1,4 +1,4 @@
-use std::ffi::CStr;
+use std::ffi::{CStr, CString};
 use std::marker;
 use std::mem;
 use std::slice;
@@ -68,7 +68,21 @@ pub type UpdateTips<'a> = FnMut(&str, Oid, Oid) -> bool + 'a;
 ///
 /// The second argument is the hostname for the connection is passed as the last
 /// argument.
-pub type CertificateCheck<'a> = FnMut(&Cert, &str) -> bool + 'a;
+///
+/// Returning an error rejects the certificate and fails the connection.
+pub type CertificateCheck<'a> = FnMut(&Cert, &str)
+                                      -> Result<CertificateCheckStatus, Error>
+                                      + 'a;
+
+/// The return value of a `CertificateCheck` callback.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CertificateCheckStatus {
+    /// The certificate is trusted and the connection may proceed.
+    CertificateOk,
+    /// The callback declines to make a decision, deferring to the validity
+    /// determination made by libgit2 itself.
+    CertificatePassthrough,
+}
 
 /// Callback for each updated reference on push.
 ///
@@ -133,8 +147,13 @@ impl<'a> RemoteCallbacks<'a> {
     /// If certificate verification fails, then this callback will be invoked to
     /// let the caller make the final decision of whether to allow the
     /// connection to proceed.
+    ///
+    /// Returning `CertificatePassthrough` honors libgit2's own verdict on the
+    /// certificate, while returning an error rejects it outright, with the
+    /// error's message reported as the reason the connection failed.
     pub fn certificate_check<F>(&mut self, cb: F) -> &mut RemoteCallbacks<'a>
-        where F: FnMut(&Cert, &str) -> bool + 'a
+        where F: FnMut(&Cert, &str) -> Result<CertificateCheckStatus, Error>
+                 + 'a
     {
         self.certificate_check = Some(Box::new(cb) as Box<CertificateCheck<'a>>);
         self
@@ -261,41 +280,59 @@ extern fn credentials_cb(ret: *mut *mut raw::git_cred,
                          allowed_types: c_uint,
                          payload: *mut c_void) -> c_int {
     unsafe {
-        let ok = panic::wrap(|| {
-            let payload = &mut *(payload as *mut RemoteCallbacks);
-            let callback = try!(payload.credentials.as_mut()
-                                       .ok_or(raw::GIT_PASSTHROUGH as c_int));
-            *ret = ptr::null_mut();
-            let url = try!(str::from_utf8(CStr::from_ptr(url).to_bytes())
-                              .map_err(|_| raw::GIT_PASSTHROUGH as c_int));
-            let username_from_url = match ::opt_bytes(&url, username_from_url) {
-                Some(username) => {
-                    Some(try!(str::from_utf8(username)
-                                 .map_err(|_| raw::GIT_PASSTHROUGH as c_int)))
-                }
-                None => None,
-            };
-
-            let cred_type = CredentialType::from_bits_truncate(allowed_types as u32);
-
-            callback(url, username_from_url, cred_type).map_err(|e| {
-                e.raw_code() as c_int
-            })
-        });
-        match ok {
-            Some(Ok(cred)) => {
-                // Turns out it's a memory safety issue if we pass through any
-                // and all credentials into libgit2
-                if allowed_types & (cred.credtype() as c_uint) != 0 {
-                    *ret = cred.unwrap();
-                    0
-                } else {
-                    raw::GIT_PASSTHROUGH as c_int
-                }
+        let payload = &mut *(payload as *mut RemoteCallbacks);
+        let callback = match payload.credentials {
+            Some(ref mut c) => c as *mut Box<Credentials> as *mut c_void,
+            None => ptr::null_mut(),
+        };
+        invoke_credentials(callback, ret, url, username_from_url, allowed_types)
+    }
+}
+
+/// Shared implementation of the `git_cred_acquire_cb` callbacks.
+///
+/// The `callback` is a pointer to a `Box<Credentials>`, or null if no
+/// credentials callback is configured.
+pub unsafe fn invoke_credentials(callback: *mut c_void,
+                                 ret: *mut *mut raw::git_cred,
+                                 url: *const c_char,
+                                 username_from_url: *const c_char,
+                                 allowed_types: c_uint) -> c_int {
+    let ok = panic::wrap(|| {
+        if callback.is_null() {
+            return Err(raw::GIT_PASSTHROUGH as c_int)
+        }
+        let callback = &mut *(callback as *mut Box<Credentials>);
+        *ret = ptr::null_mut();
+        let url = try!(str::from_utf8(CStr::from_ptr(url).to_bytes())
+                          .map_err(|_| raw::GIT_PASSTHROUGH as c_int));
+        let username_from_url = match ::opt_bytes(&url, username_from_url) {
+            Some(username) => {
+                Some(try!(str::from_utf8(username)
+                             .map_err(|_| raw::GIT_PASSTHROUGH as c_int)))
+            }
+            None => None,
+        };
+
+        let cred_type = CredentialType::from_bits_truncate(allowed_types as u32);
+
+        callback(url, username_from_url, cred_type).map_err(|e| {
+            e.raw_code() as c_int
+        })
+    });
+    match ok {
+        Some(Ok(cred)) => {
+            // Turns out it's a memory safety issue if we pass through any
+            // and all credentials into libgit2
+            if allowed_types & (cred.credtype() as c_uint) != 0 {
+                *ret = cred.unwrap();
+                0
+            } else {
+                raw::GIT_PASSTHROUGH as c_int
             }
-            Some(Err(e)) => e,
-            None => -1,
         }
+        Some(Err(e)) => e,
+        None => -1,
     }
 }
 
@@ -351,18 +388,29 @@ extern fn certificate_check_cb(cert: *mut raw::git_cert,
                                _valid: c_int,
                                hostname: *const c_char,
                                data: *mut c_void) -> c_int {
-    let ok = panic::wrap(|| unsafe {
+    panic::wrap(|| unsafe {
         let payload = &mut *(data as *mut RemoteCallbacks);
         let callback = match payload.certificate_check {
             Some(ref mut c) => c,
-            None => return true,
+            None => return 0,
         };
         let cert = Binding::from_raw(cert);
         let hostname = str::from_utf8(CStr::from_ptr(hostname).to_bytes())
                            .unwrap();
-        callback(&cert, hostname)
-    });
-    if ok == Some(true) {0} else {-1}
+        match callback(&cert, hostname) {
+            Ok(CertificateCheckStatus::CertificateOk) => 0,
+            Ok(CertificateCheckStatus::CertificatePassthrough) => {
+                raw::GIT_PASSTHROUGH as c_int
+            }
+            Err(e) => {
+                let msg = CString::new(e.message()).unwrap_or_else(|_| {
+                    CString::new("certificate check failed").unwrap()
+                });
+                raw::giterr_set_str(e.raw_class() as c_int, msg.as_ptr());
+                e.raw_code() as c_int
+            }
+        }
+    }).unwrap_or(-1)
 }
 
 extern fn push_update_reference_cb(refname: *const c_char,
@@ -1,5 +1,7 @@
 use std::env;
-use std::ffi::{CStr, CString, OsStr};
+use std::ffi::{CString, OsStr};
+use std::fs::OpenOptions;
+use std::io::Write;
 use std::iter::IntoIterator;
 use std::mem;
 use std::path::Path;
@@ -12,9 +14,21 @@ use {ResetType, Signature, Reference, References, Submodule, Blame, BlameOptions
 use {Branches, BranchType, Index, Config, Oid, Blob, BlobWriter, Branch, Commit, Tree};
 use {AnnotatedCommit, MergeOptions, SubmoduleIgnore, SubmoduleStatus, MergeAnalysis, MergePreference};
 use {ObjectType, Tag, Note, Notes, StatusOptions, Statuses, Status, Revwalk};
-use {RevparseMode, RepositoryInitMode, Reflog, IntoCString, Describe};
-use {DescribeOptions, TreeBuilder, Diff, DiffOptions, PackBuilder, Odb};
+use {Commits, SORT_TIME, SORT_TOPOLOGICAL};
+use {RevparseMode, RepositoryInitMode, Reflog, ReflogEntry, IntoCString};
+use {Describe, DescribeOptions, TreeBuilder, Diff, DiffOptions, PackBuilder, Odb};
+#[cfg(feature = "libgit2-0-28")]
+use {Worktree, WorktreeAddOptions};
+#[cfg(feature = "libgit2-0-28")]
+use Mailmap;
+use {FilterList, FilterMode, FilterFlags, Rebase, RebaseOptions, Transaction};
+use {IndexEntry, MergeFileOptions, MergeFileResult, RefdbBackend};
 use build::{RepoBuilder, CheckoutBuilder};
+use index;
+use refdb;
+use reflog;
+#[cfg(feature = "libgit2-0-28")]
+use mailinfo;
 use stash::{StashApplyOptions, StashCbData, stash_cb};
 use string_array::StringArray;
 use oid_array::OidArray;
@@ -62,6 +76,21 @@ impl Repository {
         }
     }
 
+    /// Attempt to open an already-existing bare repository at `path`.
+    ///
+    /// The path must point to the repository itself, such as a bare repository
+    /// or the `.git` folder of a normal one; no search is performed and any
+    /// working directory is ignored, making this faster than `open`.
+    pub fn open_bare<P: AsRef<Path>>(path: P) -> Result<Repository, Error> {
+        init();
+        let path = try!(path.as_ref().into_c_string());
+        let mut ret = ptr::null_mut();
+        unsafe {
+            try_call!(raw::git_repository_open_bare(&mut ret, path));
+            Ok(Binding::from_raw(ret))
+        }
+    }
+
     /// Find and open an existing repository, respecting git environment
     /// variables.  This acts like `open_ext` with the
     /// `REPOSITORY_OPEN_FROM_ENV` flag, but additionally respects `$GIT_DIR`.
@@ -279,6 +308,13 @@ impl Repository {
         unsafe { raw::git_repository_is_shallow(self.raw) == 1 }
     }
 
+    /// Tests whether this repository is a linked worktree rather than the
+    /// main repository.
+    #[cfg(feature = "libgit2-0-28")]
+    pub fn is_worktree(&self) -> bool {
+        unsafe { raw::git_repository_is_worktree(self.raw) == 1 }
+    }
+
     /// Tests whether this repository is empty.
     pub fn is_empty(&self) -> Result<bool, Error> {
         let empty = unsafe {
@@ -290,8 +326,30 @@ impl Repository {
     /// Returns the path to the `.git` folder for normal repositories or the
     /// repository itself for bare repositories.
     pub fn path(&self) -> &Path {
+        util::bytes2path(self.path_bytes())
+    }
+
+    /// Returns the path to the `.git` folder for normal repositories or the
+    /// repository itself for bare repositories, as a byte array.
+    ///
+    /// The returned slice borrows the path stored by libgit2.
+    pub fn path_bytes(&self) -> &[u8] {
         unsafe {
             let ptr = raw::git_repository_path(self.raw);
+            ::opt_bytes(self, ptr).unwrap()
+        }
+    }
+
+    /// Returns the path to the common directory of this repository, which
+    /// holds the objects, references and hooks shared by all of its worktrees.
+    ///
+    /// For a linked worktree this is the `.git` folder of the main repository,
+    /// whereas `path` is the worktree's own directory within it. Otherwise it
+    /// is the same as `path`.
+    #[cfg(feature = "libgit2-0-28")]
+    pub fn commondir(&self) -> &Path {
+        unsafe {
+            let ptr = raw::git_repository_commondir(self.raw);
             util::bytes2path(::opt_bytes(self, ptr).unwrap())
         }
     }
@@ -324,18 +382,28 @@ impl Repository {
         )
     }
 
+    /// Returns the name of the operation currently in progress in this
+    /// repository, such as `"merge"` or `"rebase -i"`, or `None` if the
+    /// repository is in a clean state.
+    ///
+    /// See `RepositoryState::operation_name`.
+    pub fn in_progress_operation_name(&self) -> Option<&'static str> {
+        self.state().operation_name()
+    }
+
     /// Get the path of the working directory for this repository.
     ///
     /// If this repository is bare, then `None` is returned.
     pub fn workdir(&self) -> Option<&Path> {
-        unsafe {
-            let ptr = raw::git_repository_workdir(self.raw);
-            if ptr.is_null() {
-                None
-            } else {
-                Some(util::bytes2path(CStr::from_ptr(ptr).to_bytes()))
-            }
-        }
+        self.workdir_bytes().map(util::bytes2path)
+    }
+
+    /// Get the path of the working directory for this repository as a byte
+    /// array.
+    ///
+    /// If this repository is bare, then `None` is returned.
+    pub fn workdir_bytes(&self) -> Option<&[u8]> {
+        unsafe { ::opt_bytes(self, raw::git_repository_workdir(self.raw)) }
     }
 
     /// Set the path to the working directory for this repository.
@@ -353,6 +421,18 @@ impl Repository {
         Ok(())
     }
 
+    /// Detach the working directory from this repository, making it bare.
+    ///
+    /// This clears the working directory so that `workdir` returns `None` and
+    /// sets `core.bare` to true in the repository's configuration. The files
+    /// of the former working directory are left untouched.
+    pub fn set_bare(&self) -> Result<(), Error> {
+        unsafe {
+            try_call!(raw::git_repository_set_bare(self.raw()));
+        }
+        Ok(())
+    }
+
     /// Get the currently active namespace for this repository.
     ///
     /// If there is no namespace, or the namespace is not a valid utf8 string,
@@ -552,6 +632,12 @@ impl Repository {
     ///
     /// Passing a `None` target will result in removing entries in the index
     /// matching the provided pathspecs.
+    ///
+    /// This is the equivalent of `git reset [<target>] -- <paths>`, which
+    /// unstages changes to the paths without touching the working directory.
+    /// To unstage against HEAD pass it as the target, e.g.
+    /// `Some(repo.head()?.peel(ObjectType::Commit)?)`; `None` is meant for
+    /// repositories whose HEAD is unborn.
     pub fn reset_default<T, I>(&self,
                                target: Option<&Object>,
                                paths: I) -> Result<(), Error>
@@ -634,6 +720,30 @@ impl Repository {
         }
     }
 
+    /// Create a new transaction for updating several references atomically.
+    pub fn transaction(&self) -> Result<Transaction, Error> {
+        let mut raw = ptr::null_mut();
+        unsafe {
+            try_call!(raw::git_transaction_new(&mut raw, self.raw));
+            Ok(Binding::from_raw(raw))
+        }
+    }
+
+    /// Get the names of all references in this repository at once.
+    ///
+    /// This is cheaper than iterating over `references` when only the names
+    /// are needed, as no `Reference` is created for each entry.
+    pub fn reference_names(&self) -> Result<StringArray, Error> {
+        let mut arr = raw::git_strarray {
+            strings: 0 as *mut *mut c_char,
+            count: 0,
+        };
+        unsafe {
+            try_call!(raw::git_reference_list(&mut arr, self.raw));
+            Ok(Binding::from_raw(arr))
+        }
+    }
+
     /// Load all submodules for this repository and return them.
     pub fn submodules(&self) -> Result<Vec<Submodule>, Error> {
         struct Data<'a, 'b:'a> {
@@ -772,6 +882,22 @@ impl Repository {
         }
     }
 
+    /// Get a read-only snapshot of the configuration file for this repository.
+    ///
+    /// Unlike `config`, the values in the snapshot do not change if the
+    /// configuration files are modified, giving a consistent view for reading
+    /// several related values. Reads are served from memory without checking
+    /// the files for changes, so a snapshot is also cheaper when many values
+    /// are read. Any attempt to modify the snapshot returns an error.
+    pub fn config_snapshot(&self) -> Result<Config, Error> {
+        let mut raw = ptr::null_mut();
+        unsafe {
+            try_call!(raw::git_repository_config_snapshot(&mut raw,
+                                                          self.raw()));
+            Ok(Binding::from_raw(raw))
+        }
+    }
+
     /// Write an in-memory buffer to the ODB as a blob.
     ///
     /// The Oid returned can in turn be passed to `find_blob` to get a handle to
@@ -802,12 +928,35 @@ impl Repository {
         }
     }
 
+    /// Calculate the hash of a file using repository filtering rules.
+    ///
+    /// This hashes the file at `path` the same way `git hash-object` would,
+    /// applying any filtering rules (e.g. crlf filters) before generating the
+    /// SHA. The file is never written to the object database.
+    ///
+    /// If `as_path` is specified, it is used to select the filters to apply
+    /// instead of `path`, so content can be hashed as if it lived at a
+    /// different location in the working directory.
+    pub fn hashfile(&self, path: &Path, kind: ObjectType,
+                    as_path: Option<&Path>) -> Result<Oid, Error> {
+        let path = try!(path.into_c_string());
+        let as_path = try!(::opt_cstr(as_path));
+        let mut raw = raw::git_oid { id: [0; raw::GIT_OID_RAWSZ] };
+        unsafe {
+            try_call!(raw::git_repository_hashfile(&mut raw, self.raw(), path,
+                                                   kind, as_path));
+            Ok(Binding::from_raw(&raw as *const _))
+        }
+    }
+
     /// Create a stream to write blob
     ///
     /// This function may need to buffer the data on disk and will in general
     /// not be the right choice if you know the size of the data to write.
     ///
-    /// Use `BlobWriter::commit()` to commit the write to the object db
+    /// The returned `BlobWriter` implements `std::io::Write`, so large content
+    /// can be piped into it (e.g. with `std::io::copy`) without being held in
+    /// memory. Use `BlobWriter::commit()` to commit the write to the object db
     /// and get the object id.
     ///
     /// If the `hintpath` parameter is filled, it will be used to determine
@@ -838,6 +987,44 @@ impl Repository {
         }
     }
 
+    /// Load the list of filters which apply to the file at `path`, as
+    /// determined by its gitattributes.
+    ///
+    /// The `blob` which is to be filtered may be given, in which case it is
+    /// used to determine how some filters (such as CRLF conversion) behave.
+    /// Returns `None` if no filters apply to the file.
+    pub fn filter_list_load(&self, blob: Option<&Blob>, path: &Path,
+                            mode: FilterMode, flags: FilterFlags)
+                            -> Result<Option<FilterList>, Error> {
+        let path = try!(path.into_c_string());
+        let blob = blob.map(|b| b.raw()).unwrap_or(ptr::null_mut());
+        let mut ret = ptr::null_mut();
+        unsafe {
+            try_call!(raw::git_filter_list_load(&mut ret, self.raw(), blob,
+                                                path, mode, flags.bits()));
+            Ok(Binding::from_raw_opt(ret))
+        }
+    }
+
+    /// Get the content of a blob as it would be checked out to the working
+    /// directory, with any filters such as CRLF conversion applied.
+    ///
+    /// The filters which apply are determined by the gitattributes of
+    /// `as_path`. If `check_for_binary` is true, no filters are applied to
+    /// content which looks like binary data.
+    pub fn blob_filtered_content(&self, blob: &Blob, as_path: &Path,
+                                 check_for_binary: bool)
+                                 -> Result<Buf, Error> {
+        let as_path = try!(as_path.into_c_string());
+        let buf = Buf::new();
+        unsafe {
+            try_call!(raw::git_blob_filtered_content(buf.raw(), blob.raw(),
+                                                     as_path,
+                                                     check_for_binary));
+        }
+        Ok(buf)
+    }
+
     /// Get the object database for this repository
     pub fn odb(&self) -> Result<Odb, Error> {
         let mut odb = ptr::null_mut();
@@ -847,6 +1034,51 @@ impl Repository {
         }
     }
 
+    /// Store the references of this repository in a custom backend instead
+    /// of the `refs` directory and `packed-refs` file.
+    ///
+    /// The backend replaces the current reference database entirely, so
+    /// references stored so far, including `HEAD`, are no longer visible
+    /// through this repository.
+    pub fn set_refdb<B: RefdbBackend>(&self, backend: B) -> Result<(), Error> {
+        unsafe {
+            let mut refdb = ptr::null_mut();
+            try_call!(raw::git_refdb_new(&mut refdb, self.raw));
+            let backend = match refdb::raw_backend(backend) {
+                Ok(backend) => backend,
+                Err(e) => {
+                    raw::git_refdb_free(refdb);
+                    return Err(e)
+                }
+            };
+            let rc = raw::git_refdb_set_backend(refdb, backend);
+            if rc == 0 {
+                raw::git_repository_set_refdb(self.raw, refdb);
+            } else {
+                // the backend isn't owned by the refdb unless it was set
+                ((*backend).free.unwrap())(backend);
+            }
+            raw::git_refdb_free(refdb);
+            try!(::call::try(rc));
+        }
+        Ok(())
+    }
+
+    /// Replace the object database of this repository, e.g. with one created
+    /// by `Odb::new` that is backed by a custom `OdbBackend`.
+    pub fn set_odb(&self, odb: &Odb) {
+        unsafe { raw::git_repository_set_odb(self.raw(), odb.raw()) }
+    }
+
+    /// Checks whether an object with the given id exists in this repository.
+    ///
+    /// The object database is consulted directly, so this is cheaper than
+    /// attempting to look the object up. Returns `false` if the object
+    /// database cannot be opened.
+    pub fn contains(&self, id: Oid) -> bool {
+        self.odb().map(|odb| odb.exists(id)).unwrap_or(false)
+    }
+
     /// Create a new branch pointing at a target commit
     ///
     /// A new direct reference will be created pointing to this target commit.
@@ -869,6 +1101,10 @@ impl Repository {
     }
 
     /// Lookup a branch by its name in a repository.
+    ///
+    /// The name is the short name of the branch, e.g. `master` rather than
+    /// `refs/heads/master`. Returns an error with the code `NotFound` if no
+    /// such branch exists.
     pub fn find_branch(&self, name: &str, branch_type: BranchType)
                        -> Result<Branch, Error> {
         let name = try!(CString::new(name));
@@ -880,6 +1116,37 @@ impl Repository {
         }
     }
 
+    /// Get the name of the remote a remote-tracking branch belongs to.
+    ///
+    /// Given `refs/remotes/origin/master` this returns `origin`. Returns an
+    /// error if `refname` is not a remote-tracking branch, or if it matches the
+    /// fetch refspecs of more than one remote.
+    pub fn branch_remote_name(&self, refname: &str) -> Result<Buf, Error> {
+        let refname = try!(CString::new(refname));
+        unsafe {
+            let buf = Buf::new();
+            try_call!(raw::git_branch_remote_name(buf.raw(), self.raw(),
+                                                  refname));
+            Ok(buf)
+        }
+    }
+
+    /// Get the name of the remote configured as the upstream of a local
+    /// branch, i.e. its `branch.<name>.remote` configuration.
+    ///
+    /// The `refname` is the full name of the branch, such as
+    /// `refs/heads/master`. Returns an error with the code `NotFound` if no
+    /// upstream remote is configured.
+    pub fn branch_upstream_remote(&self, refname: &str) -> Result<Buf, Error> {
+        let refname = try!(CString::new(refname));
+        unsafe {
+            let buf = Buf::new();
+            try_call!(raw::git_branch_upstream_remote(buf.raw(), self.raw(),
+                                                      refname));
+            Ok(buf)
+        }
+    }
+
     /// Create new commit in the repository
     ///
     /// If the `update_ref` is not `None`, name of the reference that will be
@@ -916,6 +1183,58 @@ impl Repository {
         }
     }
 
+    /// Create a commit object and return its raw content, without writing it
+    /// to the object database.
+    ///
+    /// The arguments are the same as those of `commit`. The returned buffer
+    /// is suitable for signing and passing to `commit_signed`.
+    pub fn commit_create_buffer(&self,
+                                author: &Signature,
+                                committer: &Signature,
+                                message: &str,
+                                tree: &Tree,
+                                parents: &[&Commit]) -> Result<Buf, Error> {
+        let mut parent_ptrs = parents.iter().map(|p| {
+            p.raw() as *const raw::git_commit
+        }).collect::<Vec<_>>();
+        let message = try!(CString::new(message));
+        let buf = Buf::new();
+        unsafe {
+            try_call!(raw::git_commit_create_buffer(buf.raw(),
+                                                    self.raw(),
+                                                    author.raw(),
+                                                    committer.raw(),
+                                                    ptr::null(),
+                                                    message,
+                                                    tree.raw(),
+                                                    parents.len() as size_t,
+                                                    parent_ptrs.as_mut_ptr()));
+        }
+        Ok(buf)
+    }
+
+    /// Create a commit object from the given raw content and signature,
+    /// returning its id.
+    ///
+    /// The `signature` is inserted into the commit header under the field
+    /// `signature_field`, or `gpgsig` if `None`. No references are updated.
+    pub fn commit_signed(&self,
+                         commit_content: &str,
+                         signature: &str,
+                         signature_field: Option<&str>) -> Result<Oid, Error> {
+        let commit_content = try!(CString::new(commit_content));
+        let signature = try!(CString::new(signature));
+        let signature_field = try!(::opt_cstr(signature_field));
+        let mut raw = raw::git_oid { id: [0; raw::GIT_OID_RAWSZ] };
+        unsafe {
+            try_call!(raw::git_commit_create_with_signature(&mut raw,
+                                                            self.raw(),
+                                                            commit_content,
+                                                            signature,
+                                                            signature_field));
+            Ok(Binding::from_raw(&raw as *const _))
+        }
+    }
 
     /// Lookup a reference to one of the commits in a repository.
     pub fn find_commit(&self, oid: Oid) -> Result<Commit, Error> {
@@ -926,6 +1245,21 @@ impl Repository {
         }
     }
 
+    /// Lookup a commit by a prefix of its hex-formatted id.
+    ///
+    /// Returns an error with the code `Ambiguous` if the prefix matches more
+    /// than one object or is shorter than 4 characters.
+    pub fn find_commit_by_prefix(&self, prefix: &str) -> Result<Commit, Error> {
+        let id = try!(Oid::from_str(prefix));
+        let mut raw = ptr::null_mut();
+        unsafe {
+            try_call!(raw::git_commit_lookup_prefix(&mut raw, self.raw(),
+                                                    id.raw(),
+                                                    prefix.len() as size_t));
+            Ok(Binding::from_raw(raw))
+        }
+    }
+
     /// Creates a `AnnotatedCommit` from the given commit id.
     pub fn find_annotated_commit(&self, id: Oid) -> Result<AnnotatedCommit, Error> {
         unsafe {
@@ -1084,6 +1418,10 @@ impl Repository {
     /// This function provides a quick way to resolve a reference name straight
     /// through to the object id that it refers to. This avoids having to
     /// allocate or free any `Reference` objects for simple situations.
+    ///
+    /// Symbolic references are followed to their final target. Returns an
+    /// error with the code `NotFound` if the reference, or the target of a
+    /// symbolic reference, does not exist.
     pub fn refname_to_id(&self, name: &str) -> Result<Oid, Error> {
         let name = try!(CString::new(name));
         let mut ret = raw::git_oid { id: [0; raw::GIT_OID_RAWSZ] };
@@ -1105,12 +1443,44 @@ impl Repository {
         }
     }
 
+    /// Creates a `AnnotatedCommit` from a revision string.
+    ///
+    /// See `revparse_single` for the accepted syntax. If the revspec names a
+    /// reference, such as `origin/master`, the annotated commit remembers the
+    /// reference name so that merge and rebase can mention it, for example in
+    /// `MERGE_MSG`. Other revspecs, such as a bare commit id, produce an
+    /// annotated commit without a reference name.
+    pub fn annotated_commit_from_revspec(&self, spec: &str)
+                                         -> Result<AnnotatedCommit, Error> {
+        let (object, reference) = try!(self.revparse_ext(spec));
+        if let Some(reference) = reference {
+            // `revparse_ext` also returns the base reference of expressions
+            // like `master~1`, so only use it if it names the same commit.
+            let id = try!(object.peel_to_commit()).id();
+            if try!(reference.peel_to_commit()).id() == id {
+                return self.reference_to_annotated_commit(&reference)
+            }
+        }
+        let spec = try!(CString::new(spec));
+        let mut ret = ptr::null_mut();
+        unsafe {
+            try_call!(raw::git_annotated_commit_from_revspec(&mut ret,
+                                                             self.raw(),
+                                                             spec));
+            Ok(AnnotatedCommit::from_raw(ret))
+        }
+    }
+
     /// Create a new action signature with default user and now timestamp.
     ///
     /// This looks up the user.name and user.email from the configuration and
     /// uses the current time as the timestamp, and creates a new signature
     /// based on that information. It will return `NotFound` if either the
     /// user.name or user.email are not set.
+    ///
+    /// The configuration is searched with the usual precedence of repository,
+    /// global and system files. Environment variables such as
+    /// `GIT_AUTHOR_NAME` are not consulted.
     pub fn signature(&self) -> Result<Signature<'static>, Error> {
         let mut ret = ptr::null_mut();
         unsafe {
@@ -1119,6 +1489,19 @@ impl Repository {
         }
     }
 
+    /// Load the mailmap for this repository.
+    ///
+    /// The mailmap is built from the `.mailmap` file in the working directory
+    /// along with the `mailmap.file` and `mailmap.blob` configuration options.
+    #[cfg(feature = "libgit2-0-28")]
+    pub fn mailmap(&self) -> Result<Mailmap, Error> {
+        let mut ret = ptr::null_mut();
+        unsafe {
+            try_call!(raw::git_mailmap_from_repository(&mut ret, self.raw));
+            Ok(Binding::from_raw(ret))
+        }
+    }
+
     /// Set up a new git submodule for checkout.
     ///
     /// This does "git submodule add" up to the fetch and checkout of the
@@ -1223,6 +1606,26 @@ impl Repository {
         }
     }
 
+    /// Create a new tag object in the repository without creating a
+    /// reference to it.
+    ///
+    /// The tag object is written to the object database and its id is
+    /// returned, but no `refs/tags/<name>` reference is created. Use
+    /// `reference` to point a reference at the returned id if needed.
+    pub fn tag_annotation(&self, name: &str, target: &Object,
+                          tagger: &Signature, message: &str)
+                          -> Result<Oid, Error> {
+        let name = try!(CString::new(name));
+        let message = try!(CString::new(message));
+        let mut raw = raw::git_oid { id: [0; raw::GIT_OID_RAWSZ] };
+        unsafe {
+            try_call!(raw::git_tag_annotation_create(&mut raw, self.raw, name,
+                                                     target.raw(),
+                                                     tagger.raw(), message));
+            Ok(Binding::from_raw(&raw as *const _))
+        }
+    }
+
     /// Create a new lightweight tag pointing at a target object
     ///
     /// A new direct reference will be created pointing to this target object.
@@ -1339,6 +1742,63 @@ impl Repository {
         Ok(())
     }
 
+    /// Switch to the branch named by `refname`, e.g. `refs/heads/master`.
+    ///
+    /// The tree of the branch is checked out first, and HEAD is only updated
+    /// to point at the branch once that has succeeded. Unless `force` is set,
+    /// the checkout is refused with an error of code `ErrorCode::Conflict` if
+    /// it would overwrite local changes.
+    pub fn checkout_branch(&self, refname: &str, force: bool)
+                           -> Result<(), Error> {
+        let reference = try!(self.find_reference(refname));
+        let tree = try!(reference.peel_to_tree());
+        let mut opts = CheckoutBuilder::new();
+        if force {
+            opts.force();
+        } else {
+            opts.safe();
+        }
+        try!(self.checkout_tree(tree.as_object(), Some(&mut opts)));
+        self.set_head(refname)
+    }
+
+    /// Fast-forward the reference `refname` to the commit `target`, writing
+    /// an entry to its reflog.
+    ///
+    /// If `refname` is the branch HEAD points to, the index and working
+    /// directory are updated to `target` with a safe checkout before the
+    /// reference is moved. An error is returned if `target` is not a
+    /// descendant of the commit the reference currently points to.
+    pub fn fast_forward(&self, refname: &str, target: Oid)
+                        -> Result<(), Error> {
+        let mut reference = try!(self.find_reference(refname));
+        let current = match reference.target() {
+            Some(id) => id,
+            None => return Err(Error::from_str("cannot fast-forward a \
+                                                symbolic reference")),
+        };
+        if current == target {
+            return Ok(())
+        }
+        if !try!(self.graph_descendant_of(target, current)) {
+            return Err(Error::from_str("target is not a descendant of the \
+                                        reference, cannot fast-forward"))
+        }
+
+        let is_head = match self.head() {
+            Ok(head) => head.name() == reference.name(),
+            Err(..) => false,
+        };
+        if is_head {
+            let object = try!(self.find_object(target, None));
+            try!(self.checkout_tree(&object,
+                                    Some(CheckoutBuilder::new().safe())));
+        }
+        let msg = format!("fast-forward: {}", target);
+        try!(reference.set_target(target, &msg));
+        Ok(())
+    }
+
     /// Merges the given commit(s) into HEAD, writing the results into the
     /// working directory. Any changes are staged for commit and any conflicts
     /// are written to the index. Callers should inspect the repository's index
@@ -1346,7 +1806,7 @@ impl Repository {
     ///
     /// For compatibility with git, the repository is put into a merging state.
     /// Once the commit is done (or if the uses wishes to abort), you should
-    /// clear this state by calling git_repository_state_cleanup().
+    /// clear this state by calling `cleanup_state`.
     pub fn merge(&self,
                  annotated_commits: &[&AnnotatedCommit],
                  merge_opts: Option<&mut MergeOptions>,
@@ -1406,6 +1866,40 @@ impl Repository {
         }
     }
 
+    /// Merge the contents of the blobs of three index entries, such as the
+    /// stages of a conflict, without touching the index.
+    ///
+    /// The result may contain conflict markers, labelled with the entries'
+    /// paths unless `opts` gives labels. `ancestor` may be `None` when the
+    /// sides have no common base, e.g. when both added the file.
+    pub fn merge_file_from_index(&self,
+                                 ancestor: Option<&IndexEntry>,
+                                 ours: &IndexEntry,
+                                 theirs: &IndexEntry,
+                                 opts: Option<&MergeFileOptions>)
+                                 -> Result<MergeFileResult, Error> {
+        let ancestor_path = match ancestor {
+            Some(entry) => Some(try!(CString::new(&entry.path[..]))),
+            None => None,
+        };
+        let ancestor = ancestor.map(|entry| {
+            index::raw_entry(entry, ancestor_path.as_ref().unwrap())
+        });
+        let ours_path = try!(CString::new(&ours.path[..]));
+        let ours = index::raw_entry(ours, &ours_path);
+        let theirs_path = try!(CString::new(&theirs.path[..]));
+        let theirs = index::raw_entry(theirs, &theirs_path);
+        unsafe {
+            let mut result = mem::zeroed();
+            try_call!(raw::git_merge_file_from_index(
+                &mut result, self.raw,
+                ancestor.as_ref().map(|a| a as *const _)
+                        .unwrap_or(ptr::null()),
+                &ours, &theirs, opts.map(|o| o.raw())));
+            Ok(Binding::from_raw(&result as *const _))
+        }
+    }
+
     /// Remove all the metadata associated with an ongoing command like merge,
     /// revert, cherry-pick, etc. For example: MERGE_HEAD, MERGE_MSG, etc.
     pub fn cleanup_state(&self) -> Result<(), Error> {
@@ -1436,10 +1930,49 @@ impl Repository {
         }
     }
 
+    /// Initializes a rebase of the commits reachable from `branch` but not
+    /// from `upstream`, replaying them on top of `onto`.
+    ///
+    /// `branch` defaults to the current HEAD and `onto` defaults to
+    /// `upstream`. The rebase state is written to disk unless the options
+    /// request an in-memory rebase; operations are then applied by iterating
+    /// over the returned `Rebase`.
+    pub fn rebase(&self,
+                  branch: Option<&AnnotatedCommit>,
+                  upstream: Option<&AnnotatedCommit>,
+                  onto: Option<&AnnotatedCommit>,
+                  opts: Option<&mut RebaseOptions>) -> Result<Rebase, Error> {
+        let mut rebase = ptr::null_mut();
+        unsafe {
+            try_call!(raw::git_rebase_init(&mut rebase,
+                                           self.raw(),
+                                           branch.map(|c| c.raw()),
+                                           upstream.map(|c| c.raw()),
+                                           onto.map(|c| c.raw()),
+                                           opts.map(|o| o.raw())
+                                               .unwrap_or(ptr::null())));
+            Ok(Binding::from_raw(rebase))
+        }
+    }
+
+    /// Opens an existing rebase that was previously started by either an
+    /// invocation of `rebase` or by another client.
+    pub fn open_rebase(&self, opts: Option<&mut RebaseOptions>)
+                       -> Result<Rebase, Error> {
+        let mut rebase = ptr::null_mut();
+        unsafe {
+            try_call!(raw::git_rebase_open(&mut rebase,
+                                           self.raw(),
+                                           opts.map(|o| o.raw())
+                                               .unwrap_or(ptr::null())));
+            Ok(Binding::from_raw(rebase))
+        }
+    }
+
     /// Add a note for an object
     ///
     /// The `notes_ref` argument is the canonical name of the reference to use,
-    /// defaulting to "refs/notes/commits". If `force` is specified then
+    /// defaulting to the one returned by `note_default_ref`. If `force` is specified then
     /// previous notes are overwritten.
     pub fn note(&self,
                 author: &Signature,
@@ -1465,6 +1998,10 @@ impl Repository {
     }
 
     /// Get the default notes reference for this repository
+    ///
+    /// This is the value of the `core.notesRef` configuration option, or
+    /// "refs/notes/commits" if it isn't set. It is used by the note functions
+    /// when no reference is given.
     pub fn note_default_ref(&self) -> Result<String, Error> {
         let ret = Buf::new();
         unsafe {
@@ -1476,7 +2013,7 @@ impl Repository {
     /// Creates a new iterator for notes in this repository.
     ///
     /// The `notes_ref` argument is the canonical name of the reference to use,
-    /// defaulting to "refs/notes/commits".
+    /// defaulting to the one returned by `note_default_ref`.
     ///
     /// The iterator returned yields pairs of (Oid, Oid) where the first element
     /// is the id of the note and the second id is the id the note is
@@ -1493,7 +2030,7 @@ impl Repository {
     /// Read the note for an object.
     ///
     /// The `notes_ref` argument is the canonical name of the reference to use,
-    /// defaulting to "refs/notes/commits".
+    /// defaulting to the one returned by `note_default_ref`.
     ///
     /// The id specified is the Oid of the git object to read the note from.
     pub fn find_note(&self, notes_ref: Option<&str>, id: Oid)
@@ -1510,7 +2047,7 @@ impl Repository {
     /// Remove the note for an object.
     ///
     /// The `notes_ref` argument is the canonical name of the reference to use,
-    /// defaulting to "refs/notes/commits".
+    /// defaulting to the one returned by `note_default_ref`.
     ///
     /// The id specified is the Oid of the git object to remove the note from.
     pub fn note_delete(&self,
@@ -1535,6 +2072,18 @@ impl Repository {
         }
     }
 
+    /// Iterate over the commits reachable from HEAD, newest first.
+    ///
+    /// This is a shorthand for creating a revwalk, pushing HEAD, sorting it
+    /// by time and topologically, and looking up each visited commit. Use
+    /// `revwalk` directly for more control over the walk.
+    pub fn log(&self) -> Result<Commits, Error> {
+        let mut walk = try!(self.revwalk());
+        walk.set_sorting(SORT_TIME | SORT_TOPOLOGICAL);
+        try!(walk.push_head());
+        Ok(walk.commits(self))
+    }
+
     /// Get the blame for a single file.
     pub fn blame_file(&self, path: &Path, opts: Option<&mut BlameOptions>)
                       -> Result<Blame, Error> {
@@ -1603,6 +2152,102 @@ impl Repository {
         }
     }
 
+    /// Determine if a commit is reachable from any of a list of commits.
+    ///
+    /// A commit is considered reachable from itself, so this returns `true` if
+    /// `commit` is one of `descendants` or an ancestor of any of them. All of
+    /// the `descendants` are traversed in a single revision walk rather than
+    /// one walk per commit.
+    pub fn graph_reachable_from_any(&self, commit: Oid, descendants: &[Oid])
+                                    -> Result<bool, Error> {
+        if descendants.is_empty() {
+            return Ok(false)
+        }
+        let mut walk = try!(self.revwalk());
+        for id in descendants {
+            try!(walk.push(*id));
+        }
+        for id in walk {
+            if try!(id) == commit {
+                return Ok(true)
+            }
+        }
+        Ok(false)
+    }
+
+    /// Mark a commit as good in the current bisection, starting one if none
+    /// is in progress.
+    ///
+    /// Like git, this creates a `refs/bisect/good-<id>` reference and appends
+    /// to `BISECT_LOG`, so `state` reports `RepositoryState::Bisect`.
+    pub fn bisect_good(&self, id: Oid) -> Result<(), Error> {
+        let name = format!("refs/bisect/good-{}", id);
+        try!(self.reference(&name, id, true, "bisect: good"));
+        self.bisect_log(&format!("git bisect good {}\n", id))
+    }
+
+    /// Mark a commit as bad in the current bisection, starting one if none
+    /// is in progress.
+    ///
+    /// This replaces any commit previously marked bad, as `refs/bisect/bad`.
+    pub fn bisect_bad(&self, id: Oid) -> Result<(), Error> {
+        try!(self.reference("refs/bisect/bad", id, true, "bisect: bad"));
+        self.bisect_log(&format!("git bisect bad {}\n", id))
+    }
+
+    /// Find the next commit to test in the current bisection.
+    ///
+    /// The candidates are the commits reachable from the bad commit but not
+    /// from any good one, excluding the bad commit itself. The candidate in
+    /// the middle of their topological order is returned, which halves the
+    /// range on linear history. Once no candidates remain `None` is returned,
+    /// and the commit marked bad is the first bad commit.
+    ///
+    /// An error is returned if no commit has been marked bad.
+    pub fn bisect_next(&self) -> Result<Option<Oid>, Error> {
+        let bad = try!(self.refname_to_id("refs/bisect/bad"));
+        let mut walk = try!(self.revwalk());
+        walk.set_sorting(SORT_TOPOLOGICAL);
+        try!(walk.push(bad));
+        for reference in try!(self.references_glob("refs/bisect/good-*")) {
+            if let Some(id) = try!(reference).target() {
+                try!(walk.hide(id));
+            }
+        }
+        let mut candidates = Vec::new();
+        for id in walk {
+            let id = try!(id);
+            if id != bad {
+                candidates.push(id);
+            }
+        }
+        Ok(candidates.get(candidates.len() / 2).cloned())
+    }
+
+    /// End the current bisection, deleting the references under
+    /// `refs/bisect/` and cleaning up the repository state.
+    pub fn bisect_reset(&self) -> Result<(), Error> {
+        let mut names = Vec::new();
+        for reference in try!(self.references_glob("refs/bisect/*")) {
+            if let Some(name) = try!(reference).name() {
+                names.push(name.to_string());
+            }
+        }
+        for name in names {
+            try!(try!(self.find_reference(&name)).delete());
+        }
+        self.cleanup_state()
+    }
+
+    fn bisect_log(&self, line: &str) -> Result<(), Error> {
+        OpenOptions::new().create(true).append(true)
+            .open(self.path().join("BISECT_LOG"))
+            .and_then(|mut f| f.write_all(line.as_bytes()))
+            .map_err(|e| {
+                Error::from_str(&format!("failed to write BISECT_LOG: {}", e))
+            })
+    }
+
     /// Read the reflog for the given reference
     ///
     /// If there is no reflog file for the given reference yet, an empty reflog
@@ -1616,6 +2261,16 @@ impl Repository {
         }
     }
 
+    /// Read the most recent entry of the reflog for the given reference.
+    ///
+    /// Returns `None` if the reference has no reflog entries. The whole
+    /// reflog is still parsed by libgit2, but only the newest entry is kept
+    /// and returned, without having to iterate over the others.
+    pub fn reflog_latest(&self, name: &str)
+                         -> Result<Option<ReflogEntry<'static>>, Error> {
+        Ok(reflog::latest(try!(self.reflog(name))))
+    }
+
     /// Delete the reflog for the given reference
     pub fn reflog_delete(&self, name: &str) -> Result<(), Error> {
         let name = try!(CString::new(name));
@@ -1724,6 +2379,10 @@ impl Repository {
     ///
     /// The first index will be used for the "old_file" side of the delta, and
     /// the second index will be used for the "new_file" side of the delta.
+    ///
+    /// Neither index needs to be backed by a file on disk; in-memory indexes
+    /// created with `Index::new` work as well, as long as the blobs they refer
+    /// to exist in this repository.
     pub fn diff_index_to_index(&self,
                                old_index: &Index,
                                new_index: &Index,
@@ -1817,6 +2476,51 @@ impl Repository {
         }
     }
 
+    /// Apply a diff to a tree, returning an index with the resulting entries.
+    ///
+    /// Neither the tree nor the repository's index and working directory are
+    /// modified. The index can be written out as a new tree with
+    /// `Index::write_tree_to`.
+    #[cfg(feature = "libgit2-0-28")]
+    pub fn apply_to_tree(&self, tree: &Tree, diff: &Diff)
+                         -> Result<Index, Error> {
+        let mut ret = ptr::null_mut();
+        unsafe {
+            try_call!(raw::git_apply_to_tree(&mut ret, self.raw, tree.raw(),
+                                             diff.raw(),
+                                             ptr::null::<raw::git_apply_options>()));
+            Ok(Binding::from_raw(ret))
+        }
+    }
+
+    /// Commit an emailed patch, as produced by `git format-patch`, on top of
+    /// HEAD, similarly to `git am`.
+    ///
+    /// The author and commit message are taken from the `From`, `Date` and
+    /// `Subject` headers and the body of the mail, while `committer` is used
+    /// as the committer. The patch is applied to HEAD's tree and the new commit
+    /// is checked out with a safe checkout before HEAD is moved to it, so this
+    /// fails without creating a commit if the patch does not apply, and fails
+    /// before moving HEAD if local changes conflict with it.
+    #[cfg(feature = "libgit2-0-28")]
+    pub fn commit_from_patch(&self, patch: &[u8], committer: &Signature)
+                             -> Result<Oid, Error> {
+        let info = try!(mailinfo::parse(patch));
+        let diff = try!(Diff::from_buffer(patch));
+        let mut head = try!(self.head());
+        let parent = try!(head.peel_to_commit());
+        let mut index = try!(self.apply_to_tree(&try!(parent.tree()), &diff));
+        let tree = try!(self.find_tree(try!(index.write_tree_to(self))));
+        let id = try!(self.commit(None, &info.author, committer, &info.message,
+                                  &tree, &[&parent]));
+
+        let commit = try!(self.find_object(id, None));
+        try!(self.checkout_tree(&commit, Some(CheckoutBuilder::new().safe())));
+        let summary = info.message.lines().next().unwrap_or("");
+        try!(head.set_target(id, &format!("am: {}", summary)));
+        Ok(id)
+    }
+
     /// Create a PackBuilder
     pub fn packbuilder(&self) -> Result<PackBuilder, Error> {
         let mut ret = ptr::null_mut();
@@ -1891,6 +2595,76 @@ impl Repository {
             Ok(())
         }
     }
+
+    /// List the names of the worktrees linked to this repository.
+    #[cfg(feature = "libgit2-0-28")]
+    pub fn worktrees(&self) -> Result<StringArray, Error> {
+        let mut arr = raw::git_strarray {
+            strings: ptr::null_mut(),
+            count: 0,
+        };
+        unsafe {
+            try_call!(raw::git_worktree_list(&mut arr, self.raw()));
+            Ok(Binding::from_raw(arr))
+        }
+    }
+
+    /// Lookup a worktree linked to this repository by its name.
+    #[cfg(feature = "libgit2-0-28")]
+    pub fn find_worktree(&self, name: &str) -> Result<Worktree, Error> {
+        let name = try!(name.into_c_string());
+        let mut raw = ptr::null_mut();
+        unsafe {
+            try_call!(raw::git_worktree_lookup(&mut raw, self.raw(), name));
+            Ok(Binding::from_raw(raw))
+        }
+    }
+
+    /// Add a new worktree named `name` with its working directory at `path`.
+    ///
+    /// This is the equivalent of `git worktree add`.
+    #[cfg(feature = "libgit2-0-28")]
+    pub fn worktree(&self, name: &str, path: &Path,
+                    opts: Option<&WorktreeAddOptions>)
+                    -> Result<Worktree, Error> {
+        let name = try!(name.into_c_string());
+        let path = try!(path.into_c_string());
+        let mut raw = ptr::null_mut();
+        unsafe {
+            try_call!(raw::git_worktree_add(&mut raw, self.raw(), name, path,
+                                            opts.map(|o| o.raw())));
+            Ok(Binding::from_raw(raw))
+        }
+    }
+
+    /// Open the repository of a worktree.
+    #[cfg(feature = "libgit2-0-28")]
+    pub fn open_from_worktree(worktree: &Worktree)
+                              -> Result<Repository, Error> {
+        let mut raw = ptr::null_mut();
+        unsafe {
+            try_call!(raw::git_repository_open_from_worktree(&mut raw,
+                                                             worktree.raw()));
+            Ok(Binding::from_raw(raw))
+        }
+    }
+
+    /// Create a `Repository` from a raw `git_repository` pointer.
+    ///
+    /// This is unsafe as the pointer must be valid, and ownership of it is
+    /// transferred to the returned `Repository`, which will free it when
+    /// dropped.
+    pub unsafe fn from_raw(ptr: *mut raw::git_repository) -> Repository {
+        Binding::from_raw(ptr)
+    }
+
+    /// Get the raw `git_repository` pointer underlying this repository.
+    ///
+    /// The pointer is still owned by this `Repository` and is only valid for
+    /// as long as it is alive.
+    pub fn raw(&self) -> *mut raw::git_repository {
+        self.raw
+    }
 }
 
 impl Binding for Repository {
@@ -2062,10 +2836,11 @@ impl RepositoryInitOptions {
 #[cfg(test)]
 mod tests {
     use std::ffi::OsStr;
-    use std::fs;
+    use std::fs::{self, File};
+    use std::io::prelude::*;
     use std::path::Path;
     use tempdir::TempDir;
-    use {Repository, Oid, ObjectType, ResetType};
+    use {Repository, Oid, ObjectType, ResetType, Signature, Time};
     use build::CheckoutBuilder;
 
     #[test]
@@ -2098,6 +2873,9 @@ mod tests {
         assert!(repo.is_empty().unwrap());
         assert_eq!(::test::realpath(&repo.path()).unwrap(),
                    ::test::realpath(&td.path().join(".git/")).unwrap());
+        assert_eq!(repo.path_bytes(), repo.path().to_str().unwrap().as_bytes());
+        assert_eq!(repo.workdir_bytes(),
+                   repo.workdir().map(|p| p.to_str().unwrap().as_bytes()));
         assert_eq!(repo.state(), ::RepositoryState::Clean);
     }
 
@@ -2111,6 +2889,122 @@ mod tests {
         assert!(repo.is_bare());
         assert_eq!(::test::realpath(&repo.path()).unwrap(),
                    ::test::realpath(&td.path().join("")).unwrap());
+
+        let repo = Repository::open_bare(path).unwrap();
+        assert!(repo.is_bare());
+        assert_eq!(::test::realpath(&repo.path()).unwrap(),
+                   ::test::realpath(&td.path().join("")).unwrap());
+        assert!(Repository::open_bare(path.join("objects")).is_err());
+
+        let (td, _repo) = ::test::repo_init();
+        assert!(Repository::open_bare(td.path()).is_err());
+        let repo = Repository::open_bare(td.path().join(".git")).unwrap();
+        assert!(repo.is_bare());
+        assert!(repo.workdir().is_none());
+    }
+
+    #[test]
+    fn smoke_log() {
+        let (_td, repo) = ::test::repo_init();
+        let initial = repo.head().unwrap().target().unwrap();
+        let tree = repo.find_commit(initial).unwrap().tree().unwrap();
+        let mut ids = vec![initial];
+        for i in 1..3 {
+            let parent = repo.find_commit(ids[ids.len() - 1]).unwrap();
+            let time = Time::new(parent.time().seconds() + i * 60, 0);
+            let sig = Signature::new("name", "email", &time).unwrap();
+            let msg = format!("commit {}", i);
+            ids.push(repo.commit(Some("HEAD"), &sig, &sig, &msg, &tree,
+                                 &[&parent]).unwrap());
+        }
+        ids.reverse();
+
+        let log = repo.log().unwrap().map(|c| c.unwrap().id())
+                      .collect::<Vec<_>>();
+        assert_eq!(log, ids);
+        let first = repo.log().unwrap().next().unwrap().unwrap();
+        assert_eq!(first.message(), Some("commit 2"));
+    }
+
+    #[test]
+    fn smoke_config_snapshot() {
+        let (_td, repo) = ::test::repo_init();
+        repo.config().unwrap().set_str("foo.bar", "baz").unwrap();
+
+        let mut snapshot = repo.config_snapshot().unwrap();
+        assert_eq!(snapshot.get_str("foo.bar").unwrap(), "baz");
+        assert!(snapshot.set_str("foo.bar", "qux").is_err());
+        assert!(snapshot.set_bool("foo.flag", true).is_err());
+        assert!(snapshot.remove("foo.bar").is_err());
+        assert_eq!(snapshot.get_str("foo.bar").unwrap(), "baz");
+        assert_eq!(repo.config().unwrap().get_string("foo.bar").unwrap(),
+                   "baz");
+    }
+
+    #[test]
+    fn smoke_set_bare() {
+        let (td, repo) = ::test::repo_init();
+        let snapshot = repo.config_snapshot().unwrap();
+        assert!(!snapshot.get_bool("core.bare").unwrap());
+
+        repo.set_bare().unwrap();
+        assert!(repo.is_bare());
+        assert!(repo.workdir().is_none());
+        assert!(!snapshot.get_bool("core.bare").unwrap());
+        assert!(repo.config_snapshot().unwrap().get_bool("core.bare").unwrap());
+
+        let repo = Repository::open(td.path().join(".git")).unwrap();
+        assert!(repo.is_bare());
+    }
+
+    #[test]
+    fn smoke_branch_remote_name() {
+        let (_td, repo) = ::test::repo_init();
+        let head = repo.head().unwrap().target().unwrap();
+        repo.remote("origin", "https://example.com/foo").unwrap();
+        repo.reference("refs/remotes/origin/main", head, false, "").unwrap();
+
+        let name = repo.branch_remote_name("refs/remotes/origin/main").unwrap();
+        assert_eq!(name.as_str(), Some("origin"));
+        assert!(repo.branch_remote_name("refs/heads/master").is_err());
+
+        match repo.branch_upstream_remote("refs/heads/master") {
+            Err(e) => assert_eq!(e.code(), ::ErrorCode::NotFound),
+            Ok(..) => panic!("master unexpectedly has an upstream remote"),
+        }
+        repo.config().unwrap().set_str("branch.master.remote", "origin").unwrap();
+        let name = repo.branch_upstream_remote("refs/heads/master").unwrap();
+        assert_eq!(name.as_str(), Some("origin"));
+    }
+
+    #[test]
+    fn smoke_reference_names() {
+        let (_td, repo) = ::test::repo_init();
+        let head = repo.head().unwrap().target().unwrap();
+        repo.reference("refs/tags/v1", head, false, "").unwrap();
+        repo.reference_symbolic("refs/heads/alias", "refs/heads/master", false,
+                                "").unwrap();
+
+        let names = repo.reference_names().unwrap();
+        let mut names = names.iter().map(|s| s.unwrap()).collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, ["refs/heads/alias", "refs/heads/master",
+                           "refs/tags/v1"]);
+    }
+
+    #[test]
+    fn smoke_refname_to_id() {
+        let (_td, repo) = ::test::repo_init();
+        let head = repo.head().unwrap().target().unwrap();
+        assert_eq!(repo.refname_to_id("HEAD").unwrap(), head);
+        assert_eq!(repo.refname_to_id("refs/heads/master").unwrap(), head);
+
+        repo.reference_symbolic("refs/heads/dangling", "refs/heads/missing",
+                                false, "").unwrap();
+        let err = repo.refname_to_id("refs/heads/dangling").unwrap_err();
+        assert_eq!(err.code(), ::ErrorCode::NotFound);
+        let err = repo.refname_to_id("refs/heads/missing").unwrap_err();
+        assert_eq!(err.code(), ::ErrorCode::NotFound);
     }
 
     #[test]
@@ -2136,6 +3030,68 @@ mod tests {
         t!(repo.reset(&obj, ResetType::Soft, Some(&mut opts)));
     }
 
+    #[test]
+    fn smoke_signature() {
+        let (_td, repo) = ::test::repo_init();
+        let sig = repo.signature().unwrap();
+        assert_eq!(sig.name(), Some("name"));
+        assert_eq!(sig.email(), Some("email"));
+
+        let mut config = repo.config().unwrap();
+        config.set_str("user.name", "other").unwrap();
+        assert_eq!(repo.signature().unwrap().name(), Some("other"));
+    }
+
+    #[test]
+    fn smoke_find_object() {
+        let (_td, repo) = ::test::repo_init();
+        let head = repo.head().unwrap().target().unwrap();
+        let obj = repo.find_object(head, None).unwrap();
+        assert_eq!(obj.id(), head);
+        assert_eq!(obj.kind(), Some(ObjectType::Commit));
+        assert!(repo.find_object(head, Some(ObjectType::Commit)).is_ok());
+        assert!(repo.find_object(head, Some(ObjectType::Tree)).is_err());
+
+        let missing = Oid::from_str("1111111111111111111111111111111111111111")
+                         .unwrap();
+        let err = repo.find_object(missing, None).err().unwrap();
+        assert_eq!(err.code(), ::ErrorCode::NotFound);
+    }
+
+    #[test]
+    fn smoke_blob_filtered_content() {
+        let (td, repo) = ::test::repo_init();
+        File::create(td.path().join(".gitattributes")).unwrap()
+            .write_all(b"*.txt text eol=crlf\n").unwrap();
+
+        let blob = repo.find_blob(repo.blob(b"a\nb\n").unwrap()).unwrap();
+        let buf = repo.blob_filtered_content(&blob, Path::new("foo.txt"),
+                                             false).unwrap();
+        assert_eq!(&*buf, b"a\r\nb\r\n");
+        let buf = repo.blob_filtered_content(&blob, Path::new("foo.bin"),
+                                             false).unwrap();
+        assert_eq!(&*buf, b"a\nb\n");
+
+        let blob = repo.find_blob(repo.blob(b"a\0\nb\n").unwrap()).unwrap();
+        let buf = repo.blob_filtered_content(&blob, Path::new("foo.txt"),
+                                             true).unwrap();
+        assert_eq!(&*buf, b"a\0\nb\n");
+    }
+
+    #[test]
+    fn smoke_find_commit_by_prefix() {
+        let (_td, repo) = ::test::repo_init();
+        let head = repo.head().unwrap().target().unwrap();
+        let prefix = &head.to_string()[..7];
+        let commit = repo.find_commit_by_prefix(prefix).unwrap();
+        assert_eq!(commit.id(), head);
+
+        let err = repo.find_commit_by_prefix(&prefix[..3]).err().unwrap();
+        assert_eq!(err.code(), ::ErrorCode::Ambiguous);
+        let tree = commit.tree_id().to_string();
+        assert!(repo.find_commit_by_prefix(&tree[..7]).is_err());
+    }
+
     #[test]
     fn makes_dirs() {
         let td = TempDir::new("foo").unwrap();
@@ -2223,6 +3179,65 @@ mod tests {
         assert!(!repo.graph_descendant_of(head_parent_id, head_id).unwrap());
     }
 
+    #[test]
+    fn smoke_graph_reachable_from_any() {
+        let (_td, repo) = graph_repo_init();
+        let head = repo.head().unwrap().target().unwrap();
+        let head = repo.find_commit(head).unwrap();
+        let head_id = head.id();
+        let head_parent_id = head.parent(0).unwrap().id();
+        let sig = repo.signature().unwrap();
+        let tree = head.tree().unwrap();
+        let side = repo.commit(None, &sig, &sig, "side", &tree,
+                               &[&head.parent(0).unwrap()]).unwrap();
+
+        assert!(repo.graph_reachable_from_any(head_parent_id,
+                                              &[side]).unwrap());
+        assert!(repo.graph_reachable_from_any(head_id,
+                                              &[side, head_id]).unwrap());
+        assert!(!repo.graph_reachable_from_any(head_id, &[side]).unwrap());
+        assert!(!repo.graph_reachable_from_any(side,
+                                               &[head_parent_id]).unwrap());
+        assert!(!repo.graph_reachable_from_any(head_id, &[]).unwrap());
+    }
+
+    #[test]
+    fn smoke_bisect() {
+        let (_td, repo) = ::test::repo_init();
+        let sig = repo.signature().unwrap();
+        let mut ids = vec![repo.head().unwrap().target().unwrap()];
+        for i in 1..8 {
+            let parent = repo.find_commit(ids[i - 1]).unwrap();
+            let tree = parent.tree().unwrap();
+            let msg = format!("commit {}", i);
+            ids.push(repo.commit(Some("HEAD"), &sig, &sig, &msg, &tree,
+                                 &[&parent]).unwrap());
+        }
+        assert!(repo.bisect_next().is_err());
+
+        // ids[5] introduced the breakage
+        repo.bisect_bad(ids[7]).unwrap();
+        repo.bisect_good(ids[0]).unwrap();
+        assert_eq!(repo.state(), ::RepositoryState::Bisect);
+        let mut tested = 0;
+        while let Some(id) = repo.bisect_next().unwrap() {
+            let idx = ids.iter().position(|i| *i == id).unwrap();
+            assert!(idx > 0 && idx < 7);
+            if idx >= 5 {
+                repo.bisect_bad(id).unwrap();
+            } else {
+                repo.bisect_good(id).unwrap();
+            }
+            tested += 1;
+        }
+        assert_eq!(tested, 3);
+        assert_eq!(repo.refname_to_id("refs/bisect/bad").unwrap(), ids[5]);
+
+        repo.bisect_reset().unwrap();
+        assert_eq!(repo.state(), ::RepositoryState::Clean);
+        assert_eq!(repo.references_glob("refs/bisect/*").unwrap().count(), 0);
+    }
+
     #[test]
     fn smoke_reference_has_log_ensure_log() {
         let (_td, repo) = ::test::repo_init();
@@ -2262,6 +3277,227 @@ mod tests {
         assert_eq!(repo.head().unwrap().target().unwrap(), master_oid);
     }
 
+    #[test]
+    fn smoke_annotated_commit_from_revspec() {
+        let (_td, repo) = ::test::repo_init();
+        let sig = repo.signature().unwrap();
+        let head = repo.head().unwrap().target().unwrap();
+        let base = repo.find_commit(head).unwrap();
+        let tree = base.tree().unwrap();
+        let next = repo.commit(Some("HEAD"), &sig, &sig, "next", &tree,
+                               &[&base]).unwrap();
+
+        let commit = repo.annotated_commit_from_revspec("master").unwrap();
+        assert_eq!(commit.id(), next);
+        assert_eq!(commit.refname(), Some("refs/heads/master"));
+
+        let commit = repo.annotated_commit_from_revspec("master~1").unwrap();
+        assert_eq!(commit.id(), head);
+        assert_eq!(commit.refname(), None);
+
+        let spec = head.to_string();
+        let commit = repo.annotated_commit_from_revspec(&spec).unwrap();
+        assert_eq!(commit.id(), head);
+        assert_eq!(commit.refname(), None);
+
+        assert!(repo.annotated_commit_from_revspec("nope").is_err());
+    }
+
+    #[test]
+    fn smoke_merge_conflict() {
+        let (td, repo) = ::test::repo_init();
+        let sig = repo.signature().unwrap();
+        let head = repo.head().unwrap().target().unwrap();
+        let base = repo.find_commit(head).unwrap();
+        let path = td.path().join("foo");
+
+        let commit_foo = |update_ref: &str, content: &[u8]| {
+            File::create(&path).unwrap().write_all(content).unwrap();
+            let mut index = repo.index().unwrap();
+            index.add_path(Path::new("foo")).unwrap();
+            index.write().unwrap();
+            let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+            repo.commit(Some(update_ref), &sig, &sig, "foo", &tree,
+                        &[&base]).unwrap()
+        };
+        let theirs = commit_foo("refs/heads/theirs", b"theirs\n");
+        commit_foo("HEAD", b"ours\n");
+
+        let theirs = repo.find_annotated_commit(theirs).unwrap();
+        repo.merge(&[&theirs], None, None).unwrap();
+        assert!(repo.index().unwrap().has_conflicts());
+        assert_eq!(repo.state(), ::RepositoryState::Merge);
+        assert!(repo.path().join("MERGE_HEAD").exists());
+        assert!(repo.path().join("MERGE_MSG").exists());
+
+        repo.cleanup_state().unwrap();
+        assert_eq!(repo.state(), ::RepositoryState::Clean);
+    }
+
+    #[test]
+    fn smoke_checkout_branch() {
+        let (td, repo) = ::test::repo_init();
+        let sig = repo.signature().unwrap();
+        let head = repo.head().unwrap().target().unwrap();
+        let base = repo.find_commit(head).unwrap();
+        repo.branch("other", &base, false).unwrap();
+
+        let path = td.path().join("foo");
+        File::create(&path).unwrap().write_all(b"foo\n").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("foo")).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        repo.commit(Some("HEAD"), &sig, &sig, "foo", &tree, &[&base]).unwrap();
+
+        repo.checkout_branch("refs/heads/other", false).unwrap();
+        assert_eq!(repo.head().unwrap().name(), Some("refs/heads/other"));
+        assert!(!path.exists());
+
+        // an untracked file in the way blocks a safe checkout
+        File::create(&path).unwrap().write_all(b"bar\n").unwrap();
+        let err = repo.checkout_branch("refs/heads/master", false).unwrap_err();
+        assert_eq!(err.code(), ::ErrorCode::Conflict);
+        assert_eq!(repo.head().unwrap().name(), Some("refs/heads/other"));
+
+        repo.checkout_branch("refs/heads/master", true).unwrap();
+        assert_eq!(repo.head().unwrap().name(), Some("refs/heads/master"));
+        let mut s = String::new();
+        File::open(&path).unwrap().read_to_string(&mut s).unwrap();
+        assert_eq!(s, "foo\n");
+        assert!(repo.checkout_branch("refs/heads/missing", false).is_err());
+    }
+
+    #[test]
+    fn smoke_fast_forward() {
+        let (td, repo) = ::test::repo_init();
+        let sig = repo.signature().unwrap();
+        let head = repo.head().unwrap().target().unwrap();
+        let base = repo.find_commit(head).unwrap();
+
+        let blob = repo.blob(b"foo\n").unwrap();
+        let mut builder = repo.treebuilder(Some(&base.tree().unwrap())).unwrap();
+        builder.insert("foo", blob, 0o100644).unwrap();
+        let tree = repo.find_tree(builder.write().unwrap()).unwrap();
+        let target = repo.commit(Some("refs/heads/other"), &sig, &sig, "foo",
+                                 &tree, &[&base]).unwrap();
+
+        repo.fast_forward("refs/heads/master", target).unwrap();
+        assert_eq!(repo.head().unwrap().target(), Some(target));
+        assert!(td.path().join("foo").exists());
+        assert!(repo.statuses(None).unwrap().is_empty());
+        let reflog = repo.reflog("refs/heads/master").unwrap();
+        assert_eq!(reflog.get(0).unwrap().message(),
+                   Some(&*format!("fast-forward: {}", target)));
+
+        assert!(repo.fast_forward("refs/heads/master", head).is_err());
+        assert_eq!(repo.head().unwrap().target(), Some(target));
+    }
+
+    #[test]
+    fn smoke_reset_default() {
+        let (td, repo) = ::test::repo_init();
+        let sig = repo.signature().unwrap();
+        let mut index = repo.index().unwrap();
+        for name in &["a", "b"] {
+            File::create(td.path().join(name)).unwrap()
+                .write_all(b"one\n").unwrap();
+            index.add_path(Path::new(name)).unwrap();
+        }
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let parent = repo.head().unwrap().peel_to_commit().unwrap();
+        repo.commit(Some("HEAD"), &sig, &sig, "a and b", &tree,
+                    &[&parent]).unwrap();
+
+        for name in &["a", "b", "c"] {
+            File::create(td.path().join(name)).unwrap()
+                .write_all(b"two\n").unwrap();
+            index.add_path(Path::new(name)).unwrap();
+        }
+        index.write().unwrap();
+
+        let head = repo.head().unwrap().peel(ObjectType::Commit).unwrap();
+        repo.reset_default(Some(&head), &["a"]).unwrap();
+        repo.reset_default(None, &["c"]).unwrap();
+
+        assert_eq!(repo.status_file(Path::new("a")).unwrap(),
+                   ::STATUS_WT_MODIFIED);
+        assert_eq!(repo.status_file(Path::new("b")).unwrap(),
+                   ::STATUS_INDEX_MODIFIED);
+        assert_eq!(repo.status_file(Path::new("c")).unwrap(), ::STATUS_WT_NEW);
+        let mut contents = String::new();
+        File::open(td.path().join("a")).unwrap()
+            .read_to_string(&mut contents).unwrap();
+        assert_eq!(contents, "two\n");
+    }
+
+    #[test]
+    #[cfg(feature = "libgit2-0-28")]
+    fn smoke_commit_from_patch() {
+        let (td, repo) = ::test::repo_init();
+        let committer = repo.signature().unwrap();
+        let head = repo.head().unwrap().target().unwrap();
+        let patch = b"From 0000000000000000000000000000000000000000 Mon Sep 17 00:00:00 2001\n\
+                      From: A U Thor <author@example.com>\n\
+                      Date: Fri, 13 Feb 2009 23:31:30 +0000\n\
+                      Subject: [PATCH] Add foo\n\
+                      \n\
+                      Some details.\n\
+                      ---\n \
+                      foo | 1 +\n \
+                      1 file changed, 1 insertion(+)\n\
+                      \n\
+                      diff --git a/foo b/foo\n\
+                      new file mode 100644\n\
+                      --- /dev/null\n\
+                      +++ b/foo\n\
+                      @@ -0,0 +1 @@\n\
+                      +foo\n\
+                      -- \n\
+                      2.17.0\n";
+
+        let id = repo.commit_from_patch(patch, &committer).unwrap();
+        assert_eq!(repo.head().unwrap().target(), Some(id));
+        let commit = repo.find_commit(id).unwrap();
+        assert_eq!(commit.message(), Some("Add foo\n\nSome details.\n"));
+        assert_eq!(commit.author().name(), Some("A U Thor"));
+        assert_eq!(commit.author().email(), Some("author@example.com"));
+        assert_eq!(commit.author().when().seconds(), 1234567890);
+        assert_eq!(commit.committer().name(), Some("name"));
+        assert_eq!(commit.parent_id(0).unwrap(), head);
+        let entry = commit.tree().unwrap().get_name("foo").unwrap().id();
+        assert_eq!(repo.find_blob(entry).unwrap().content(), b"foo\n");
+        let mut contents = String::new();
+        File::open(td.path().join("foo")).unwrap()
+            .read_to_string(&mut contents).unwrap();
+        assert_eq!(contents, "foo\n");
+        assert!(repo.statuses(None).unwrap().is_empty());
+        let reflog = repo.reflog("HEAD").unwrap();
+        assert_eq!(reflog.get(0).unwrap().message(), Some("am: Add foo"));
+
+        // the file now exists, so the same patch no longer applies
+        assert!(repo.commit_from_patch(patch, &committer).is_err());
+        assert_eq!(repo.head().unwrap().target(), Some(id));
+    }
+
+    #[test]
+    fn smoke_in_progress_operation_name() {
+        let (_td, repo) = ::test::repo_init();
+        assert!(!repo.state().is_in_progress());
+        assert_eq!(repo.in_progress_operation_name(), None);
+
+        let dir = repo.path().join("rebase-merge");
+        fs::create_dir(&dir).unwrap();
+        File::create(dir.join("interactive")).unwrap();
+        assert_eq!(repo.state(), ::RepositoryState::RebaseInteractive);
+        assert!(repo.state().is_in_progress());
+        assert_eq!(repo.in_progress_operation_name(), Some("rebase -i"));
+
+        repo.cleanup_state().unwrap();
+        assert_eq!(repo.in_progress_operation_name(), None);
+    }
+
     /// create an octopus:
     ///   /---o2-o4
     /// o1      X
@@ -2346,6 +3582,18 @@ mod tests {
 	    assert_eq!(merge_bases.len(), 2);
     }
 
+    #[test]
+    fn smoke_hashfile() {
+        let (td, repo) = ::test::repo_init();
+        let path = td.path().join("foo");
+        File::create(&path).unwrap().write_all(b"foo\n").unwrap();
+
+        let id = repo.hashfile(&path, ObjectType::Blob, None).unwrap();
+        assert_eq!(id.to_string(), "257cc5642cb1a054f08cc83f2d943e56fd3ebe99");
+        assert!(repo.find_blob(id).is_err());
+        assert_eq!(repo.blob_path(&path).unwrap(), id);
+    }
+
     #[test]
     fn smoke_revparse_ext() {
         let (_td, repo) = graph_repo_init();
@@ -2366,5 +3614,11 @@ mod tests {
             let (_obj, reference) = repo.revparse_ext("HEAD^").unwrap();
             assert!(reference.is_none());
         }
+        {
+            let id = repo.head().unwrap().target().unwrap();
+            let (obj, reference) = repo.revparse_ext(&id.to_string()).unwrap();
+            assert_eq!(obj.id(), id);
+            assert!(reference.is_none());
+        }
     }
 }
@@ -2,16 +2,26 @@ use std::marker;
 use std::ffi::CString;
 use libc::c_uint;
 
-use {raw, Error, Sort, Oid, Repository};
+use {raw, Commit, Error, Sort, Oid, Repository};
 use util::Binding;
 
 /// A revwalk allows traversal of the commit graph defined by including one or
 /// more leaves and excluding one or more roots.
+///
+/// Iterating over a revwalk yields the ids of the commits visited, which are
+/// owned values and do not borrow the walk. Use `Repository::find_commit` to
+/// look up a commit from its id, or `commits` to do so while iterating.
 pub struct Revwalk<'repo> {
     raw: *mut raw::git_revwalk,
     _marker: marker::PhantomData<&'repo Repository>,
 }
 
+/// An iterator over the commits of a revwalk, created by `Revwalk::commits`.
+pub struct Commits<'repo> {
+    walk: Revwalk<'repo>,
+    repo: &'repo Repository,
+}
+
 impl<'repo> Revwalk<'repo> {
     /// Reset a revwalk to allow re-configuring it.
     ///
@@ -147,6 +157,15 @@ impl<'repo> Revwalk<'repo> {
         }
         Ok(())
     }
+
+    /// Convert this revwalk into an iterator over the commits it visits,
+    /// looking each one up in `repo`.
+    ///
+    /// The returned iterator takes ownership of the walk and only borrows the
+    /// repository. `repo` must be the repository this walk was created from.
+    pub fn commits(self, repo: &'repo Repository) -> Commits<'repo> {
+        Commits { walk: self, repo: repo }
+    }
 }
 
 impl<'repo> Binding for Revwalk<'repo> {
@@ -174,6 +193,13 @@ impl<'repo> Iterator for Revwalk<'repo> {
     }
 }
 
+impl<'repo> Iterator for Commits<'repo> {
+    type Item = Result<Commit<'repo>, Error>;
+    fn next(&mut self) -> Option<Result<Commit<'repo>, Error>> {
+        self.walk.next().map(|id| id.and_then(|id| self.repo.find_commit(id)))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     #[test]
@@ -200,4 +226,26 @@ mod tests {
         walk.hide_head().unwrap();
         assert_eq!(walk.by_ref().count(), 0);
     }
+
+    #[test]
+    fn commits() {
+        let (_td, repo) = ::test::repo_init();
+        let target = repo.head().unwrap().target().unwrap();
+
+        let mut walk = repo.revwalk().unwrap();
+        walk.push_head().unwrap();
+        let mut oids = Vec::new();
+        for id in walk {
+            oids.push(id.unwrap());
+        }
+        assert_eq!(oids, vec![target]);
+
+        let mut walk = repo.revwalk().unwrap();
+        walk.push_head().unwrap();
+        let commits = walk.commits(&repo).collect::<Result<Vec<_>, _>>()
+                          .unwrap();
+        assert_eq!(commits.len(), 1);
+        assert_eq!(commits[0].id(), target);
+        assert_eq!(commits[0].message(), Some("initial"));
+    }
 }
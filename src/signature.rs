@@ -59,6 +59,20 @@ impl<'a> Signature<'a> {
         }
     }
 
+    /// Parse a signature from a buffer in the format used by commit headers,
+    /// e.g. `A U Thor <author@example.com> 1234567890 +0000`.
+    ///
+    /// Returns an error if the buffer is not a well-formed signature.
+    pub fn from_buffer(buf: &[u8]) -> Result<Signature<'static>, Error> {
+        ::init();
+        let mut ret = ptr::null_mut();
+        let buf = try!(CString::new(buf));
+        unsafe {
+            try_call!(raw::git_signature_from_buffer(&mut ret, buf));
+            Ok(Binding::from_raw(ret))
+        }
+    }
+
     /// Gets the name on the signature.
     ///
     /// Returns `None` if the name is not valid utf-8
@@ -172,4 +186,21 @@ mod tests {
         drop(s.clone());
         drop(s.to_owned());
     }
+
+    #[test]
+    fn from_buffer() {
+        let s = Signature::from_buffer(b"A U Thor <a@b.c> 1234567890 +0130")
+                          .unwrap();
+        assert_eq!(s.name(), Some("A U Thor"));
+        assert_eq!(s.email(), Some("a@b.c"));
+        assert_eq!(s.when().seconds(), 1234567890);
+        assert_eq!(s.when().offset_minutes(), 90);
+
+        let s = Signature::from_buffer(b"name <email> 89 -0200").unwrap();
+        assert_eq!(s.when().offset_minutes(), -120);
+
+        assert!(Signature::from_buffer(b"no email here").is_err());
+        assert!(Signature::from_buffer(b"").is_err());
+        assert!(Signature::from_buffer(b"name <email> \0 89 +0000").is_err());
+    }
 }
@@ -1,4 +1,4 @@
-use {raw, panic, Oid, StashApplyProgress};
+use {raw, panic, Oid, StashApplyFlags, StashApplyProgress};
 use std::ffi::{CStr};
 use util::{Binding};
 use libc::{c_int, c_char, size_t, c_void};
@@ -49,6 +49,17 @@ impl<'cb> StashApplyOptions<'cb> {
         self
     }
 
+    /// Set the flags controlling how the stash is applied.
+    ///
+    /// With `STASH_APPLY_REINSTATE_INDEX` the changes which were staged when
+    /// the stash was created are staged again, instead of only being restored
+    /// to the working directory.
+    pub fn flags(&mut self, flags: StashApplyFlags)
+                 -> &mut StashApplyOptions<'cb> {
+        self.raw_opts.flags = flags.bits() as raw::git_stash_apply_flags;
+        self
+    }
+
     /// Options to use when writing files to the working directory
     pub fn checkout_options(&mut self, opts: CheckoutBuilder<'cb>) -> &mut StashApplyOptions<'cb> {
         self.checkout_options = Some(opts);
@@ -64,7 +75,6 @@ impl<'cb> StashApplyOptions<'cb> {
     {
         self.progress = Some(Box::new(callback) as Box<StashApplyProgressCb<'cb>>);
         self.raw_opts.progress_cb = stash_apply_progress_cb;
-        self.raw_opts.progress_payload = self as *mut _ as *mut _;
         self
     }
 
@@ -75,6 +85,11 @@ impl<'cb> StashApplyOptions<'cb> {
                 opts.configure(&mut self.raw_opts.checkout_options);
             }
         }
+        // the options may have moved since `progress_cb` was called, so only
+        // take their address once they're about to be used
+        if self.progress.is_some() {
+            self.raw_opts.progress_payload = self as *mut _ as *mut _;
+        }
         &self.raw_opts
     }
 }
@@ -138,11 +153,13 @@ extern fn stash_apply_progress_cb(progress: raw::git_stash_apply_progress_t,
 #[cfg(test)]
 mod tests {
     use stash::{StashApplyOptions};
-    use std::io::{Write};
+    use std::io::{Read, Write};
     use std::fs;
     use std::path::Path;
     use test::{repo_init};
     use {Repository, STATUS_WT_NEW, STASH_INCLUDE_UNTRACKED};
+    use {StashApplyProgress, STASH_APPLY_REINSTATE_INDEX};
+    use {STATUS_INDEX_MODIFIED, STATUS_WT_MODIFIED};
 
     fn make_stash<C>(next: C) where C: FnOnce(&mut Repository) {
         let (_td, mut repo) = repo_init();
@@ -207,4 +224,56 @@ mod tests {
             assert!(count_stash(repo) == 1)
         })
     }
+
+    #[test]
+    fn stash_apply_reinstate_index() {
+        let (td, mut repo) = repo_init();
+        let signature = repo.signature().unwrap();
+        let path = td.path().join("file");
+        fs::File::create(&path).unwrap().write_all(b"one\n").unwrap();
+        {
+            let mut index = repo.index().unwrap();
+            index.add_path(Path::new("file")).unwrap();
+            index.write().unwrap();
+            let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+            let head = repo.head().unwrap().target().unwrap();
+            let parent = repo.find_commit(head).unwrap();
+            repo.commit(Some("HEAD"), &signature, &signature, "add file",
+                        &tree, &[&parent]).unwrap();
+        }
+
+        // stage one change and leave another unstaged on top of it
+        fs::File::create(&path).unwrap().write_all(b"two\n").unwrap();
+        {
+            let mut index = repo.index().unwrap();
+            index.add_path(Path::new("file")).unwrap();
+            index.write().unwrap();
+        }
+        fs::File::create(&path).unwrap().write_all(b"three\n").unwrap();
+        let status = STATUS_INDEX_MODIFIED | STATUS_WT_MODIFIED;
+        assert_eq!(repo.status_file(Path::new("file")).unwrap(), status);
+        repo.stash_save(&signature, "staged", None).unwrap();
+        assert!(repo.statuses(None).unwrap().is_empty());
+
+        let mut progress = Vec::new();
+        {
+            let mut options = StashApplyOptions::new();
+            options.flags(STASH_APPLY_REINSTATE_INDEX);
+            options.progress_cb(|p| { progress.push(p); true });
+            // moving the options must not invalidate the progress callback
+            let mut options = options;
+            repo.stash_apply(0, Some(&mut options)).unwrap();
+        }
+        assert_eq!(repo.status_file(Path::new("file")).unwrap(), status);
+        let mut contents = String::new();
+        fs::File::open(&path).unwrap().read_to_string(&mut contents).unwrap();
+        assert_eq!(contents, "three\n");
+        let index = repo.index().unwrap();
+        let staged = index.get_path(Path::new("file"), 0).unwrap();
+        assert_eq!(repo.find_blob(staged.id).unwrap().content(), b"two\n");
+
+        assert!(progress.contains(&StashApplyProgress::AnalyzeIndex));
+        assert!(progress.contains(&StashApplyProgress::CheckoutModified));
+        assert_eq!(progress.last(), Some(&StashApplyProgress::Done));
+    }
 }
@@ -404,4 +404,27 @@ mod tests {
         let status = t!(repo.status_file(Path::new("foo")));
         assert!(status.contains(::STATUS_WT_NEW));
     }
+
+    #[test]
+    fn status_predicates() {
+        let (td, repo) = ::test::repo_init();
+        t!(t!(File::create(td.path().join("foo"))).write_all(b"foo"));
+        let status = t!(repo.status_file(Path::new("foo")));
+        assert!(status.is_wt_new());
+        assert!(!status.is_index_new());
+        assert!(!status.is_conflicted());
+
+        let mut index = t!(repo.index());
+        t!(index.add_path(Path::new("foo")));
+        t!(index.write());
+        let status = t!(repo.status_file(Path::new("foo")));
+        assert!(status.is_index_new());
+        assert!(!status.is_wt_new());
+
+        t!(t!(File::create(td.path().join("foo"))).write_all(b"foobar"));
+        let status = t!(repo.status_file(Path::new("foo")));
+        assert!(status.is_index_new());
+        assert!(status.is_wt_modified());
+        assert!(!status.is_wt_deleted());
+    }
 }
@@ -267,8 +267,7 @@ mod tests {
     use tempdir::TempDir;
     use url::Url;
 
-    use Repository;
-    use SubmoduleUpdateOptions;
+    use {Repository, SubmoduleIgnore, SubmoduleUpdateOptions};
 
     #[test]
     fn smoke() {
@@ -314,6 +313,42 @@ mod tests {
         t!(s.add_finalize());
     }
 
+    #[test]
+    fn status() {
+        let (_td, repo1) = ::test::repo_init();
+        let (td, repo2) = ::test::repo_init();
+
+        let url = Url::from_file_path(&repo1.workdir().unwrap()).unwrap();
+        let mut s = repo2.submodule(&url.to_string(), Path::new("bar"),
+                                    true).unwrap();
+        t!(fs::remove_dir_all(td.path().join("bar")));
+        let sub = t!(Repository::clone(&url.to_string(),
+                                       td.path().join("bar")));
+        t!(s.add_to_index(false));
+        t!(s.add_finalize());
+
+        let status = t!(repo2.submodule_status("bar", SubmoduleIgnore::None));
+        assert!(status.is_in_index());
+        assert!(status.is_in_config());
+        assert!(status.is_in_wd());
+        assert!(!status.is_in_head());
+        assert!(status.is_index_added());
+        assert!(!status.is_unmodified());
+        assert!(!status.is_wd_dirty());
+
+        t!(fs::File::create(td.path().join("bar/new")));
+        let status = t!(repo2.submodule_status("bar", SubmoduleIgnore::None));
+        assert!(status.is_wd_untracked());
+        assert!(status.is_wd_dirty());
+        let status = t!(repo2.submodule_status("bar",
+                                               SubmoduleIgnore::Untracked));
+        assert!(!status.is_wd_untracked());
+        drop(sub);
+
+        assert!(repo2.submodule_status("missing", SubmoduleIgnore::None)
+                     .is_err());
+    }
+
     #[test]
     fn update_submodule() {
         // -----------------------------------
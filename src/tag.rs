@@ -47,6 +47,9 @@ impl<'repo> Tag<'repo> {
     }
 
     /// Recursively peel a tag until a non tag git_object is found
+    ///
+    /// A chain of tags pointing at tags, such as `tag -> tag -> commit`, is
+    /// followed all the way to the commit.
     pub fn peel(&self) -> Result<Object<'repo>, Error> {
         let mut ret = ptr::null_mut();
         unsafe {
@@ -86,7 +89,9 @@ impl<'repo> Tag<'repo> {
         unsafe { Binding::from_raw(raw::git_tag_target_id(&*self.raw)) }
     }
 
-    /// Get the OID of the tagged object of a tag
+    /// Get the type of the tagged object of a tag
+    ///
+    /// This does not perform a lookup of the target object.
     pub fn target_type(&self) -> Option<ObjectType> {
         unsafe { ObjectType::from_raw(raw::git_tag_target_type(&*self.raw)) }
     }
@@ -140,6 +145,8 @@ impl<'repo> Drop for Tag<'repo> {
 
 #[cfg(test)]
 mod tests {
+    use ObjectType;
+
     #[test]
     fn smoke() {
         let (_td, repo) = ::test::repo_init();
@@ -188,4 +195,45 @@ mod tests {
         let tags = t!(repo.tag_names(Some("b*")));
         assert_eq!(tags.len(), 0);
     }
+
+    #[test]
+    fn peel_nested() {
+        let (_td, repo) = ::test::repo_init();
+        let head = t!(repo.head());
+        let id = head.target().unwrap();
+        let commit = t!(repo.find_object(id, None));
+        let sig = t!(repo.signature());
+        let inner_id = t!(repo.tag("inner", &commit, &sig, "inner", false));
+        let inner = t!(repo.find_tag(inner_id));
+        assert_eq!(inner.target_type(), Some(ObjectType::Commit));
+
+        let outer_id = t!(repo.tag("outer", inner.as_object(), &sig,
+                                   "outer", false));
+        let outer = t!(repo.find_tag(outer_id));
+        assert_eq!(outer.target_type(), Some(ObjectType::Tag));
+        assert_eq!(t!(outer.target()).id(), inner_id);
+
+        let peeled = t!(outer.peel());
+        assert_eq!(peeled.id(), id);
+        assert_eq!(peeled.kind(), Some(ObjectType::Commit));
+    }
+
+    #[test]
+    fn annotation() {
+        let (_td, repo) = ::test::repo_init();
+        let head = t!(repo.head());
+        let id = head.target().unwrap();
+        let obj = t!(repo.find_object(id, None));
+        let sig = t!(repo.signature());
+        let tag_id = t!(repo.tag_annotation("foo", &obj, &sig, "msg"));
+        let tag = t!(repo.find_tag(tag_id));
+        assert_eq!(tag.name(), Some("foo"));
+        assert_eq!(tag.target_id(), id);
+        assert_eq!(tag.message(), Some("msg"));
+        assert!(repo.find_reference("refs/tags/foo").is_err());
+        assert_eq!(t!(repo.tag_names(None)).len(), 0);
+
+        t!(repo.reference("refs/tags/foo", tag_id, false, "tag foo"));
+        assert_eq!(t!(repo.refname_to_id("refs/tags/foo")), tag_id);
+    }
 }
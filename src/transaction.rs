@@ -0,0 +1,153 @@
+use std::ffi::CString;
+use std::marker;
+
+use {raw, Error, Oid, Repository, Signature};
+use util::Binding;
+
+/// A structure representing a transactional update of a repository's
+/// references.
+///
+/// References are locked with `lock_ref` before being updated. The updates are
+/// only applied when `commit` is called, and other writers are blocked from
+/// the locked references until then. Dropping the transaction without
+/// committing it discards the updates and releases the locks.
+pub struct Transaction<'repo> {
+    raw: *mut raw::git_transaction,
+    _marker: marker::PhantomData<&'repo Repository>,
+}
+
+impl<'repo> Transaction<'repo> {
+    /// Lock the reference named `refname` for updating within this
+    /// transaction.
+    ///
+    /// Returns an error with the code `Locked` if the reference is already
+    /// locked by another writer.
+    pub fn lock_ref(&mut self, refname: &str) -> Result<(), Error> {
+        let refname = try!(CString::new(refname));
+        unsafe {
+            try_call!(raw::git_transaction_lock_ref(self.raw, refname));
+        }
+        Ok(())
+    }
+
+    /// Set the target of the locked reference `refname` to the object `id`.
+    ///
+    /// The `signature` is used for the reflog entry, defaulting to the
+    /// repository's identity when `None`.
+    pub fn set_target(&mut self, refname: &str, id: Oid,
+                      signature: Option<&Signature>, reflog_message: &str)
+                      -> Result<(), Error> {
+        let refname = try!(CString::new(refname));
+        let reflog_message = try!(CString::new(reflog_message));
+        unsafe {
+            try_call!(raw::git_transaction_set_target(self.raw, refname,
+                                                      id.raw(),
+                                                      signature.map(|s| s.raw()),
+                                                      reflog_message));
+        }
+        Ok(())
+    }
+
+    /// Make the locked reference `refname` a symbolic reference to the
+    /// reference named `target`.
+    ///
+    /// The `signature` is used for the reflog entry, defaulting to the
+    /// repository's identity when `None`.
+    pub fn set_symbolic_target(&mut self, refname: &str, target: &str,
+                               signature: Option<&Signature>,
+                               reflog_message: &str) -> Result<(), Error> {
+        let refname = try!(CString::new(refname));
+        let target = try!(CString::new(target));
+        let reflog_message = try!(CString::new(reflog_message));
+        unsafe {
+            try_call!(raw::git_transaction_set_symbolic_target(
+                self.raw, refname, target, signature.map(|s| s.raw()),
+                reflog_message));
+        }
+        Ok(())
+    }
+
+    /// Remove the locked reference `refname` when the transaction is
+    /// committed.
+    pub fn remove(&mut self, refname: &str) -> Result<(), Error> {
+        let refname = try!(CString::new(refname));
+        unsafe {
+            try_call!(raw::git_transaction_remove(self.raw, refname));
+        }
+        Ok(())
+    }
+
+    /// Apply all of the updates in this transaction and release its locks.
+    pub fn commit(self) -> Result<(), Error> {
+        unsafe { try_call!(raw::git_transaction_commit(self.raw)); }
+        Ok(())
+    }
+}
+
+impl<'repo> Binding for Transaction<'repo> {
+    type Raw = *mut raw::git_transaction;
+    unsafe fn from_raw(raw: *mut raw::git_transaction) -> Transaction<'repo> {
+        Transaction { raw: raw, _marker: marker::PhantomData }
+    }
+    fn raw(&self) -> *mut raw::git_transaction { self.raw }
+}
+
+impl<'repo> Drop for Transaction<'repo> {
+    fn drop(&mut self) {
+        unsafe { raw::git_transaction_free(self.raw) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn smoke() {
+        let (_td, repo) = ::test::repo_init();
+        let head = repo.head().unwrap().target().unwrap();
+        let sig = repo.signature().unwrap();
+        let tree = repo.find_commit(head).unwrap().tree().unwrap();
+        let parent = repo.find_commit(head).unwrap();
+        let next = repo.commit(None, &sig, &sig, "next", &tree,
+                               &[&parent]).unwrap();
+        repo.reference("refs/heads/old", head, false, "").unwrap();
+
+        {
+            let mut tx = repo.transaction().unwrap();
+            tx.lock_ref("refs/heads/a").unwrap();
+            tx.lock_ref("refs/heads/master").unwrap();
+            tx.lock_ref("refs/heads/old").unwrap();
+            tx.set_target("refs/heads/a", head, None, "create a").unwrap();
+            tx.set_target("refs/heads/master", next, Some(&sig),
+                          "advance master").unwrap();
+            tx.remove("refs/heads/old").unwrap();
+            assert!(tx.set_target("refs/heads/unlocked", head, None,
+                                  "").is_err());
+
+            // nothing is visible, and other writers are blocked, until commit
+            assert!(repo.find_reference("refs/heads/a").is_err());
+            assert_eq!(repo.refname_to_id("refs/heads/master").unwrap(), head);
+            match repo.reference("refs/heads/master", next, true, "") {
+                Ok(..) => panic!("wrote to a locked reference"),
+                Err(e) => assert_eq!(e.code(), ::ErrorCode::Locked),
+            }
+
+            tx.commit().unwrap();
+        }
+        assert_eq!(repo.refname_to_id("refs/heads/a").unwrap(), head);
+        assert_eq!(repo.refname_to_id("refs/heads/master").unwrap(), next);
+        assert!(repo.find_reference("refs/heads/old").is_err());
+        let reflog = repo.reflog("refs/heads/master").unwrap();
+        assert_eq!(reflog.get(0).unwrap().message(), Some("advance master"));
+
+        // dropping a transaction discards its updates
+        {
+            let mut tx = repo.transaction().unwrap();
+            tx.lock_ref("refs/heads/a").unwrap();
+            tx.set_symbolic_target("refs/heads/a", "refs/heads/master", None,
+                                   "").unwrap();
+        }
+        let a = repo.find_reference("refs/heads/a").unwrap();
+        assert_eq!(a.target(), Some(head));
+        repo.reference("refs/heads/a", next, true, "").unwrap();
+    }
+}
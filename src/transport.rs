@@ -324,3 +324,56 @@ extern fn stream_free(stream: *mut raw::git_smart_subtransport_stream) {
         mem::transmute::<_, Box<RawSmartSubtransportStream>>(stream);
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::prelude::*;
+    use std::io;
+
+    use Error;
+    use super::*;
+
+    struct FailingSubtransport;
+
+    struct FailingStream;
+
+    impl SmartSubtransport for FailingSubtransport {
+        fn action(&self, _url: &str, _action: Service)
+                  -> Result<Box<SmartSubtransportStream>, Error> {
+            Ok(Box::new(FailingStream))
+        }
+
+        fn close(&self) -> Result<(), Error> {
+            Ok(())
+        }
+    }
+
+    impl Read for FailingStream {
+        fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+            Err(io::Error::new(io::ErrorKind::Other, "channel is down"))
+        }
+    }
+
+    impl Write for FailingStream {
+        fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+            Ok(data.len())
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn register_smart() {
+        unsafe {
+            register("git2-rs-test", |remote| {
+                Transport::smart(remote, false, FailingSubtransport)
+            }).unwrap();
+        }
+        let (_td, repo) = ::test::repo_init();
+        let mut remote = repo.remote_anonymous("git2-rs-test://example")
+                             .unwrap();
+        let err = remote.fetch(&[], None, None).unwrap_err();
+        assert!(err.message().contains("channel is down"));
+    }
+}
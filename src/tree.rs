@@ -1,14 +1,14 @@
 use std::mem;
 use std::cmp::Ordering;
-use std::ffi::CString;
+use std::ffi::{CStr, CString};
 use std::ops::Range;
 use std::marker;
 use std::path::Path;
 use std::ptr;
 use std::str;
-use libc;
+use libc::{self, c_char, c_int, c_void};
 
-use {raw, Oid, Repository, Error, Object, ObjectType};
+use {raw, panic, Oid, Repository, Error, Object, ObjectType};
 use util::{Binding, IntoCString};
 
 /// A structure to represent a git [tree][1]
@@ -33,12 +33,53 @@ pub struct TreeIter<'tree> {
     tree: &'tree Tree<'tree>,
 }
 
+/// The order in which `Tree::walk` visits the entries of a tree.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TreeWalkMode {
+    /// Visit each tree before the entries it contains.
+    PreOrder,
+    /// Visit each tree after the entries it contains.
+    PostOrder,
+}
+
+/// The return value of a `Tree::walk` callback, controlling how the walk
+/// continues.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TreeWalkResult {
+    /// Continue the walk as normal.
+    Ok,
+    /// Do not descend into the current entry.
+    ///
+    /// This only has an effect on trees visited in `PreOrder` mode.
+    Skip,
+    /// Stop the walk, causing `walk` to return an error.
+    Abort,
+}
+
+type TreeWalkCb<'a> = FnMut(&[u8], &TreeEntry) -> TreeWalkResult + 'a;
+
+struct TreeWalkCbData<'a> {
+    callback: &'a mut TreeWalkCb<'a>,
+}
+
 impl<'repo> Tree<'repo> {
     /// Get the id (SHA1) of a repository object
     pub fn id(&self) -> Oid {
         unsafe { Binding::from_raw(raw::git_tree_id(&*self.raw)) }
     }
 
+    /// Create an independently-owned handle to this tree.
+    ///
+    /// The new handle refers to the same underlying tree and remains valid
+    /// after this one is dropped.
+    pub fn dup(&self) -> Result<Tree<'repo>, Error> {
+        let mut raw = ptr::null_mut();
+        unsafe {
+            try_call!(raw::git_tree_dup(&mut raw, self.raw));
+            Ok(Binding::from_raw(raw))
+        }
+    }
+
     /// Get the number of entries listed in this tree.
     pub fn len(&self) -> usize {
         unsafe { raw::git_tree_entrycount(&*self.raw) as usize }
@@ -50,10 +91,39 @@ impl<'repo> Tree<'repo> {
     }
 
     /// Returns an iterator over the entries in this tree.
+    ///
+    /// Entries are always yielded in git's canonical tree order, which is the
+    /// same as the entries' indices for `get`. This sorts entries by name
+    /// bytewise, except that the names of subtrees compare as though they
+    /// ended with a `/`, so `a.txt` comes before a subtree named `a`. Two
+    /// trees may therefore be compared by zipping their iterators.
     pub fn iter(&self) -> TreeIter {
         TreeIter { range: 0..self.len(), tree: self }
     }
 
+    /// Traverse the entries in this tree and all of its subtrees.
+    ///
+    /// The callback is given the path of the entry's parent relative to this
+    /// tree (empty for entries of this tree itself, otherwise ending in `/`)
+    /// along with the entry, and returns a `TreeWalkResult` controlling the
+    /// rest of the walk. The path is given as bytes as it may not be valid
+    /// utf-8.
+    pub fn walk<C>(&self, mode: TreeWalkMode, mut callback: C)
+                   -> Result<(), Error>
+        where C: FnMut(&[u8], &TreeEntry) -> TreeWalkResult
+    {
+        let mode = match mode {
+            TreeWalkMode::PreOrder => raw::GIT_TREEWALK_PRE,
+            TreeWalkMode::PostOrder => raw::GIT_TREEWALK_POST,
+        };
+        unsafe {
+            let mut data = TreeWalkCbData { callback: &mut callback };
+            try_call!(raw::git_tree_walk(self.raw(), mode, treewalk_cb,
+                                         &mut data as *mut _ as *mut c_void));
+        }
+        Ok(())
+    }
+
     /// Lookup a tree entry by SHA value.
     pub fn get_id(&self, id: Oid) -> Option<TreeEntry> {
         unsafe {
@@ -154,6 +224,21 @@ impl<'repo, 'iter> IntoIterator for &'iter Tree<'repo> {
     }
 }
 
+extern fn treewalk_cb(root: *const c_char,
+                      entry: *const raw::git_tree_entry,
+                      payload: *mut c_void) -> c_int {
+    panic::wrap(|| unsafe {
+        let data = &mut *(payload as *mut TreeWalkCbData);
+        let root = CStr::from_ptr(root).to_bytes();
+        let entry = entry_from_raw_const(entry);
+        match (data.callback)(root, &entry) {
+            TreeWalkResult::Ok => 0,
+            TreeWalkResult::Skip => 1,
+            TreeWalkResult::Abort => -1,
+        }
+    }).unwrap_or(-1)
+}
+
 /// Create a new tree entry from the raw pointer provided.
 ///
 /// The lifetime of the entry is tied to the tree provided and the function
@@ -294,10 +379,12 @@ impl<'tree> ExactSizeIterator for TreeIter<'tree> {}
 #[cfg(test)]
 mod tests {
     use {Repository,Tree,TreeEntry,ObjectType,Object};
+    use {TreeWalkMode, TreeWalkResult};
     use tempdir::TempDir;
-    use std::fs::File;
+    use std::fs::{self, File};
     use std::io::prelude::*;
     use std::path::Path;
+    use std::str;
 
     pub struct TestTreeIter<'a> {
         entries: Vec<TreeEntry<'a>>,
@@ -403,4 +490,83 @@ mod tests {
         repo.find_object(commit.tree_id(), None).unwrap().as_tree().unwrap();
         repo.find_object(commit.tree_id(), None).unwrap().into_tree().ok().unwrap();
     }
+
+    #[test]
+    fn iter_order() {
+        let (_td, repo) = ::test::repo_init();
+        let blob = repo.blob(b"x").unwrap();
+        let subtree = {
+            let mut builder = repo.treebuilder(None).unwrap();
+            builder.insert("file", blob, 0o100644).unwrap();
+            builder.write().unwrap()
+        };
+        let mut builder = repo.treebuilder(None).unwrap();
+        builder.insert("b", blob, 0o100644).unwrap();
+        builder.insert("a", subtree, 0o040000).unwrap();
+        builder.insert("B", blob, 0o100644).unwrap();
+        builder.insert("a.txt", blob, 0o100644).unwrap();
+        let id = builder.write().unwrap();
+
+        let dup = {
+            let tree = repo.find_tree(id).unwrap();
+            let names = tree.iter().map(|e| e.name().unwrap().to_string())
+                            .collect::<Vec<_>>();
+            assert_eq!(names, ["B", "a.txt", "a", "b"]);
+            for (i, entry) in tree.iter().enumerate() {
+                assert!(entry == tree.get(i).unwrap());
+            }
+            tree.dup().unwrap()
+        };
+        assert_eq!(dup.id(), id);
+        let names = dup.iter().rev().map(|e| e.name().unwrap().to_string())
+                       .collect::<Vec<_>>();
+        assert_eq!(names, ["b", "a", "a.txt", "B"]);
+    }
+
+    #[test]
+    fn tree_walk() {
+        let (td, repo) = ::test::repo_init();
+        let mut index = repo.index().unwrap();
+        fs::create_dir(td.path().join("dir")).unwrap();
+        for path in &["dir/a", "dir/b", "foo"] {
+            File::create(&td.path().join(path)).unwrap().write_all(b"x").unwrap();
+            index.add_path(Path::new(path)).unwrap();
+        }
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+
+        let mut seen = Vec::new();
+        tree.walk(TreeWalkMode::PreOrder, |root, entry| {
+            seen.push(format!("{}{}", str::from_utf8(root).unwrap(),
+                              entry.name().unwrap()));
+            TreeWalkResult::Ok
+        }).unwrap();
+        assert_eq!(seen, ["dir", "dir/a", "dir/b", "foo"]);
+
+        let mut seen = Vec::new();
+        tree.walk(TreeWalkMode::PostOrder, |root, entry| {
+            seen.push(format!("{}{}", str::from_utf8(root).unwrap(),
+                              entry.name().unwrap()));
+            TreeWalkResult::Ok
+        }).unwrap();
+        assert_eq!(seen, ["dir/a", "dir/b", "dir", "foo"]);
+
+        let mut seen = Vec::new();
+        tree.walk(TreeWalkMode::PreOrder, |root, entry| {
+            seen.push(format!("{}{}", str::from_utf8(root).unwrap(),
+                              entry.name().unwrap()));
+            if entry.kind() == Some(ObjectType::Tree) {
+                TreeWalkResult::Skip
+            } else {
+                TreeWalkResult::Ok
+            }
+        }).unwrap();
+        assert_eq!(seen, ["dir", "foo"]);
+
+        let mut count = 0;
+        assert!(tree.walk(TreeWalkMode::PreOrder, |_, _| {
+            count += 1;
+            TreeWalkResult::Abort
+        }).is_err());
+        assert_eq!(count, 1);
+    }
 }
@@ -0,0 +1,303 @@
+use std::marker;
+use std::mem;
+use std::path::Path;
+use std::ptr;
+use std::str;
+use libc::c_int;
+
+use {raw, Buf, Error, Reference, Repository};
+use util::{self, Binding};
+
+/// An owned git worktree
+///
+/// This structure corresponds to a `git_worktree` in libgit2. A worktree
+/// describes one of the working trees linked to a repository, as created by
+/// `git worktree add`.
+pub struct Worktree {
+    raw: *mut raw::git_worktree,
+}
+
+/// Options which can be used to configure how a worktree is added.
+pub struct WorktreeAddOptions<'a> {
+    raw: raw::git_worktree_add_options,
+    _marker: marker::PhantomData<Reference<'a>>,
+}
+
+/// Options which can be used to configure how a worktree is pruned.
+pub struct WorktreePruneOptions {
+    raw: raw::git_worktree_prune_options,
+}
+
+/// The lock status of a worktree.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum WorktreeLockStatus {
+    /// The worktree is not locked.
+    Unlocked,
+    /// The worktree is locked, with an optional reason for the lock.
+    Locked(Option<String>),
+}
+
+impl Worktree {
+    /// Open the worktree corresponding to a repository.
+    ///
+    /// This returns an error if `repo` is not itself a linked worktree.
+    pub fn open_from_repository(repo: &Repository) -> Result<Worktree, Error> {
+        let mut raw = ptr::null_mut();
+        unsafe {
+            try_call!(raw::git_worktree_open_from_repository(&mut raw,
+                                                             repo.raw()));
+            Ok(Binding::from_raw(raw))
+        }
+    }
+
+    /// Get the name of this worktree.
+    ///
+    /// Returns `None` if the name is not valid utf-8.
+    pub fn name(&self) -> Option<&str> {
+        str::from_utf8(self.name_bytes()).ok()
+    }
+
+    /// Get the name of this worktree, in bytes.
+    pub fn name_bytes(&self) -> &[u8] {
+        unsafe { ::opt_bytes(self, raw::git_worktree_name(self.raw)).unwrap() }
+    }
+
+    /// Get the path to the working directory of this worktree.
+    pub fn path(&self) -> &Path {
+        unsafe {
+            let ptr = raw::git_worktree_path(self.raw);
+            util::bytes2path(::opt_bytes(self, ptr).unwrap())
+        }
+    }
+
+    /// Check that this worktree is valid.
+    ///
+    /// A valid worktree has its git data directory, its common directory and
+    /// its working directory all present on disk.
+    pub fn validate(&self) -> Result<(), Error> {
+        unsafe { try_call!(raw::git_worktree_validate(self.raw)); }
+        Ok(())
+    }
+
+    /// Lock this worktree, with an optional reason for the lock.
+    ///
+    /// Locked worktrees are not pruned unless explicitly requested.
+    pub fn lock(&self, reason: Option<&str>) -> Result<(), Error> {
+        let reason = try!(::opt_cstr(reason));
+        unsafe { try_call!(raw::git_worktree_lock(self.raw, reason)); }
+        Ok(())
+    }
+
+    /// Unlock this worktree.
+    pub fn unlock(&self) -> Result<(), Error> {
+        unsafe { try_call!(raw::git_worktree_unlock(self.raw)); }
+        Ok(())
+    }
+
+    /// Check whether this worktree is locked, returning the reason if one was
+    /// given.
+    pub fn is_locked(&self) -> Result<WorktreeLockStatus, Error> {
+        let buf = Buf::new();
+        unsafe {
+            match try_call!(raw::git_worktree_is_locked(buf.raw(), self.raw)) {
+                0 => Ok(WorktreeLockStatus::Unlocked),
+                _ => {
+                    let reason = match buf.len() {
+                        0 => None,
+                        _ => Some(String::from_utf8_lossy(&buf).into_owned()),
+                    };
+                    Ok(WorktreeLockStatus::Locked(reason))
+                }
+            }
+        }
+    }
+
+    /// Check whether this worktree can be pruned with the given options.
+    ///
+    /// By default a worktree is only prunable if it is invalid (e.g. its
+    /// working directory was deleted) and not locked.
+    pub fn is_prunable(&self, opts: Option<&mut WorktreePruneOptions>)
+                       -> Result<bool, Error> {
+        let opts = opts.map(|o| &mut o.raw);
+        let rv = unsafe {
+            try_call!(raw::git_worktree_is_prunable(self.raw, opts))
+        };
+        Ok(rv != 0)
+    }
+
+    /// Prune this worktree, removing its git data structures on disk.
+    ///
+    /// Whether the working directory itself is removed as well is determined
+    /// by the given options.
+    pub fn prune(&self, opts: Option<&mut WorktreePruneOptions>)
+                 -> Result<(), Error> {
+        let opts = opts.map(|o| &mut o.raw);
+        unsafe { try_call!(raw::git_worktree_prune(self.raw, opts)); }
+        Ok(())
+    }
+}
+
+impl Binding for Worktree {
+    type Raw = *mut raw::git_worktree;
+    unsafe fn from_raw(ptr: *mut raw::git_worktree) -> Worktree {
+        Worktree { raw: ptr }
+    }
+    fn raw(&self) -> *mut raw::git_worktree { self.raw }
+}
+
+impl Drop for Worktree {
+    fn drop(&mut self) {
+        unsafe { raw::git_worktree_free(self.raw) }
+    }
+}
+
+impl<'a> Default for WorktreeAddOptions<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> WorktreeAddOptions<'a> {
+    /// Creates a default set of add options.
+    ///
+    /// By default the worktree is not locked and a new branch named after the
+    /// worktree is checked out in it.
+    pub fn new() -> WorktreeAddOptions<'a> {
+        let mut opts = WorktreeAddOptions {
+            raw: unsafe { mem::zeroed() },
+            _marker: marker::PhantomData,
+        };
+        assert_eq!(unsafe {
+            raw::git_worktree_add_init_options(&mut opts.raw,
+                                raw::GIT_WORKTREE_ADD_OPTIONS_VERSION)
+        }, 0);
+        opts
+    }
+
+    /// Lock the newly created worktree.
+    pub fn lock(&mut self, enabled: bool) -> &mut WorktreeAddOptions<'a> {
+        self.raw.lock = enabled as c_int;
+        self
+    }
+
+    /// Check out the given reference in the new worktree instead of creating
+    /// a new branch.
+    pub fn reference(&mut self, reference: Option<&'a Reference>)
+                     -> &mut WorktreeAddOptions<'a> {
+        self.raw.reference = match reference {
+            Some(reference) => reference.raw(),
+            None => ptr::null_mut(),
+        };
+        self
+    }
+
+    /// Get a pointer to the raw `git_worktree_add_options`.
+    pub fn raw(&self) -> *const raw::git_worktree_add_options {
+        &self.raw as *const _
+    }
+}
+
+impl Default for WorktreePruneOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WorktreePruneOptions {
+    /// Creates a default set of prune options.
+    ///
+    /// By default only invalid, unlocked worktrees are pruned and their
+    /// working directories are left on disk.
+    pub fn new() -> WorktreePruneOptions {
+        let mut opts = WorktreePruneOptions {
+            raw: unsafe { mem::zeroed() },
+        };
+        assert_eq!(unsafe {
+            raw::git_worktree_prune_init_options(&mut opts.raw,
+                                raw::GIT_WORKTREE_PRUNE_OPTIONS_VERSION)
+        }, 0);
+        opts
+    }
+
+    /// Prune the worktree even if it is still valid.
+    ///
+    /// Defaults to false.
+    pub fn valid(&mut self, enabled: bool) -> &mut WorktreePruneOptions {
+        self.flag(raw::GIT_WORKTREE_PRUNE_VALID, enabled)
+    }
+
+    /// Prune the worktree even if it is locked.
+    ///
+    /// Defaults to false.
+    pub fn locked(&mut self, enabled: bool) -> &mut WorktreePruneOptions {
+        self.flag(raw::GIT_WORKTREE_PRUNE_LOCKED, enabled)
+    }
+
+    /// Remove the checked out working directory as well.
+    ///
+    /// Defaults to false.
+    pub fn working_tree(&mut self, enabled: bool) -> &mut WorktreePruneOptions {
+        self.flag(raw::GIT_WORKTREE_PRUNE_WORKING_TREE, enabled)
+    }
+
+    fn flag(&mut self, flag: raw::git_worktree_prune_t, on: bool)
+            -> &mut WorktreePruneOptions {
+        if on {
+            self.raw.flags |= flag as u32;
+        } else {
+            self.raw.flags &= !(flag as u32);
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use tempdir::TempDir;
+
+    use {Repository, Worktree, WorktreeLockStatus, WorktreePruneOptions};
+
+    #[test]
+    fn smoke() {
+        let (_td, repo) = ::test::repo_init();
+        let td = TempDir::new("worktree").unwrap();
+        let path = td.path().join("tree");
+
+        let wt = repo.worktree("tree", &path, None).unwrap();
+        assert_eq!(wt.name(), Some("tree"));
+        assert_eq!(::test::realpath(wt.path()).unwrap(),
+                   ::test::realpath(&path).unwrap());
+        wt.validate().unwrap();
+
+        let names = repo.worktrees().unwrap();
+        assert_eq!(names.len(), 1);
+        assert_eq!(names.get(0), Some("tree"));
+
+        let wt = repo.find_worktree("tree").unwrap();
+        let wt_repo = Repository::open_from_worktree(&wt).unwrap();
+        assert!(wt_repo.is_worktree());
+        assert!(!repo.is_worktree());
+        assert_eq!(::test::realpath(wt_repo.commondir()).unwrap(),
+                   ::test::realpath(repo.path()).unwrap());
+        assert_eq!(::test::realpath(wt_repo.path()).unwrap(),
+                   ::test::realpath(&repo.path().join("worktrees/tree")).unwrap());
+        assert_eq!(repo.commondir(), repo.path());
+        assert_eq!(Worktree::open_from_repository(&wt_repo).unwrap().name(),
+                   Some("tree"));
+        assert!(Worktree::open_from_repository(&repo).is_err());
+
+        assert_eq!(wt.is_locked().unwrap(), WorktreeLockStatus::Unlocked);
+        wt.lock(Some("because")).unwrap();
+        assert_eq!(wt.is_locked().unwrap(),
+                   WorktreeLockStatus::Locked(Some("because".to_string())));
+        wt.unlock().unwrap();
+
+        assert!(!wt.is_prunable(None).unwrap());
+        fs::remove_dir_all(&path).unwrap();
+        assert!(wt.validate().is_err());
+        assert!(wt.is_prunable(None).unwrap());
+        wt.prune(Some(&mut WorktreePruneOptions::new())).unwrap();
+        assert_eq!(repo.worktrees().unwrap().len(), 0);
+    }
+}
@@ -10,12 +10,16 @@ fn main() {
     cfg.header("git2.h")
        .header("git2/sys/transport.h")
        .header("git2/sys/repository.h")
+       .header("git2/sys/odb_backend.h")
+       .header("git2/sys/refdb_backend.h")
+       .header("git2/sys/refs.h")
        .header("git2/cred_helpers.h")
        .include(root.join("include"))
        .type_name(|s, _| s.to_string());
     cfg.field_name(|_, f| {
         match f {
             "kind" => "type".to_string(),
+            "reference" => "ref".to_string(),
             _ => f.to_string(),
         }
     });